@@ -11,6 +11,8 @@
 use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::HashMap;
 
+use crate::pagination::Page;
+
 /// If the object is empty, return None
 pub fn object_empty_as_none<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
 where
@@ -35,7 +37,6 @@ where
     }
 }
 
-
 /// GET https://api.replicate.com/v1/models/{model_owner}/{model_name}
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct GetModel {
@@ -43,8 +44,8 @@ pub struct GetModel {
 
     pub owner: String,
     pub name: String,
-    pub description: String,
-    pub visibility: String,
+    pub description: Option<String>,
+    pub visibility: ModelVisibility,
 
     pub github_url: Option<String>,
     pub paper_url: Option<String>,
@@ -61,6 +62,9 @@ pub struct GetModel {
     pub latest_version: Option<GetModelVersion>,
 }
 
+/// GET https://api.replicate.com/v1/models
+pub type ListModels = Page<GetModel>;
+
 /// GET https://api.replicate.com/v1/collections/{collection_slug}
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct GetCollectionModels {
@@ -77,6 +81,10 @@ pub struct GetCollectionModels {
 pub struct PredictionsUrls {
     pub cancel: String,
     pub get: String,
+
+    /// Url of the server-sent events stream, when the prediction was created with streaming enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream: Option<String>,
 }
 
 /// POST https://api.replicate.com/v1/predictions
@@ -103,7 +111,7 @@ pub struct GetPrediction {
     // Input and Outputs of the prediction
     pub input: HashMap<String, serde_json::Value>,
 
-    // Either a vector of string or a simple string
+    // Either a vector of string or a simple string, decoded with `output_as` or the helpers of the `output` module
     pub output: Option<serde_json::Value>,
 
     pub error: Option<String>,
@@ -189,20 +197,23 @@ pub struct ListCollectionModelsItem {
 }
 
 /// GET https://api.replicate.com/v1/collections
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct ListCollectionModels {
-    pub previous: Option<String>,
-    pub next: Option<String>,
-
-    pub results: Vec<ListCollectionModelsItem>,
-}
+pub type ListCollectionModels = Page<ListCollectionModelsItem>;
 
 /// Represents a prediction in the list of predictions
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct PredictionsListItem {
     pub id: String,
+
+    /// Model of the prediction, as `{owner}/{name}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+
     pub version: String,
 
+    /// Deployment of the prediction, as `{owner}/{name}`, when it was created with a deployment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deployment: Option<String>,
+
     pub urls: PredictionsUrls,
 
     pub created_at: String,
@@ -215,23 +226,10 @@ pub struct PredictionsListItem {
 }
 
 /// GET https://api.replicate.com/v1/predictions
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct ListPredictions {
-    pub previous: Option<String>,
-    pub next: Option<String>,
-
-    pub results: Vec<PredictionsListItem>,
-}
+pub type ListPredictions = Page<PredictionsListItem>;
 
 /// GET https://api.replicate.com/v1/models/{model_owner}/{model_name}/versions
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct ListModelVersions {
-    pub previous: Option<String>,
-
-    pub next: Option<String>,
-
-    pub results: Vec<GetModelVersion>,
-}
+pub type ListModelVersions = Page<GetModelVersion>;
 
 /// Each item of the list of trainings
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -251,14 +249,100 @@ pub struct ListTrainingItem {
 }
 
 /// GET https://api.replicate.com/v1/trainings
+pub type ListTraining = Page<ListTrainingItem>;
+
+/// GET https://api.replicate.com/v1/account
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GetAccount {
+    /// Either `user` or `organization`.
+    #[serde(rename = "type")]
+    pub account_type: String,
+
+    pub username: String,
+    pub name: Option<String>,
+    pub github_url: Option<String>,
+}
+
+/// GET https://api.replicate.com/v1/webhooks/default/secret
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GetWebhookSecret {
+    /// Signing secret of the webhooks, starting with `whsec_`.
+    pub key: String,
+}
+
+/// Hardware and scaling of a deployment release
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct ListTraining {
-    pub previous: Option<String>,
-    pub next: Option<String>,
+pub struct DeploymentConfiguration {
+    /// SKU of the hardware, e.g. `gpu-t4`.
+    pub hardware: String,
+    pub min_instances: u32,
+    pub max_instances: u32,
+}
+
+/// Release of a deployment, created every time the deployment is updated
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DeploymentRelease {
+    pub number: u32,
+
+    /// Model of the release, as `{owner}/{name}`.
+    pub model: String,
+    pub version: String,
+    pub created_at: String,
+    pub created_by: Option<GetAccount>,
+    pub configuration: DeploymentConfiguration,
+}
 
-    pub results: Vec<ListTrainingItem>,
+/// GET https://api.replicate.com/v1/deployments/{deployment_owner}/{deployment_name}
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GetDeployment {
+    pub owner: String,
+    pub name: String,
+
+    #[serde(deserialize_with = "object_empty_as_none", default)]
+    pub current_release: Option<DeploymentRelease>,
 }
 
+/// GET https://api.replicate.com/v1/deployments
+pub type ListDeployments = Page<GetDeployment>;
+
+/// Checksums of a file uploaded to the Files API
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FileChecksums {
+    pub sha256: Option<String>,
+    pub md5: Option<String>,
+}
+
+/// Url of a file uploaded to the Files API
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FileUrls {
+    /// Url to pass as an input of a prediction, or to get the file.
+    pub get: String,
+}
+
+/// POST https://api.replicate.com/v1/files
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FileObject {
+    pub id: String,
+    pub name: String,
+    pub content_type: String,
+
+    /// Size of the file, in bytes.
+    pub size: u64,
+    pub etag: Option<String>,
+    pub checksums: FileChecksums,
+
+    #[serde(default)]
+    pub metadata: serde_json::Value,
+    pub created_at: String,
+
+    /// Time after which the file is deleted.
+    pub expires_at: Option<String>,
+    pub urls: FileUrls,
+}
+
+/// GET https://api.replicate.com/v1/files
+pub type ListFiles = Page<FileObject>;
+
 ///////////////////////////////////////////////////////////
 ///
 /// Implement Display for all the structs
@@ -288,51 +372,184 @@ impl_display! {
     CreatePrediction,
     GetModelVersion,
     ListCollectionModelsItem,
-    ListCollectionModels,
     PredictionsListItem,
-    ListPredictions,
-    ListModelVersions,
     ListTrainingItem,
-    ListTraining
+    GetAccount,
+    GetWebhookSecret,
+    DeploymentConfiguration,
+    DeploymentRelease,
+    GetDeployment,
+    FileChecksums,
+    FileUrls,
+    FileObject
 }
 
 ///////////////////////////////////////////////////////////
+///
+/// Implement the parsed timestamps and the durations for the structs with timestamps
+///
+///////////////////////////////////////////////////////////
+#[cfg(feature = "chrono")]
+macro_rules! impl_timestamps {
+    ($($t:ty { $($field:ident => $parsed:ident),* }),*) => ($(
+        impl $t {
+            $(
+                #[doc = concat!("`", stringify!($field), "` parsed into a `chrono::DateTime<Utc>`. `None` if not set, an error if not a valid RFC 3339 timestamp.")]
+                pub fn $parsed(&self) -> Result<Option<chrono::DateTime<chrono::Utc>>, crate::errors::ReplicateError> {
+                    TimestampField::parse(&self.$field)
+                }
+            )*
+        }
+    )*)
+}
+
+#[cfg(feature = "chrono")]
+impl_timestamps! {
+    GetPrediction { created_at => created_at_parsed, started_at => started_at_parsed, completed_at => completed_at_parsed },
+    PredictionsListItem { created_at => created_at_parsed, started_at => started_at_parsed, completed_at => completed_at_parsed },
+    GetTraining { created_at => created_at_parsed, started_at => started_at_parsed, completed_at => completed_at_parsed },
+    CreateTraining { created_at => created_at_parsed, started_at => started_at_parsed, completed_at => completed_at_parsed },
+    ListTrainingItem { created_at => created_at_parsed, started_at => started_at_parsed, completed_at => completed_at_parsed },
+    CreatePrediction { created_at => created_at_parsed },
+    GetModelVersion { created_at => created_at_parsed },
+    DeploymentRelease { created_at => created_at_parsed },
+    FileObject { created_at => created_at_parsed, expires_at => expires_at_parsed }
+}
+
+#[cfg(feature = "chrono")]
+macro_rules! impl_durations {
+    ($($t:ty),*) => ($(
+        impl $t {
+            /// Time spent in the queue, from the creation to the start. `None` if not started yet, an error if a timestamp is invalid.
+            pub fn queue_duration(&self) -> Result<Option<chrono::Duration>, crate::errors::ReplicateError> {
+                Ok(elapsed(self.created_at_parsed()?, self.started_at_parsed()?))
+            }
+
+            /// Time spent running, from the start to the completion. `None` if not completed yet, an error if a timestamp is invalid.
+            pub fn run_duration(&self) -> Result<Option<chrono::Duration>, crate::errors::ReplicateError> {
+                Ok(elapsed(self.started_at_parsed()?, self.completed_at_parsed()?))
+            }
+
+            /// Total time, from the creation to the completion. `None` if not completed yet, an error if a timestamp is invalid.
+            pub fn total_duration(&self) -> Result<Option<chrono::Duration>, crate::errors::ReplicateError> {
+                Ok(elapsed(self.created_at_parsed()?, self.completed_at_parsed()?))
+            }
+        }
+    )*)
+}
+
+#[cfg(feature = "chrono")]
+impl_durations! {
+    GetPrediction,
+    PredictionsListItem,
+    GetTraining,
+    CreateTraining,
+    ListTrainingItem
+}
+
+/// Time between two timestamps, `None` if either is not set.
+#[cfg(feature = "chrono")]
+fn elapsed(
+    start: Option<chrono::DateTime<chrono::Utc>>,
+    end: Option<chrono::DateTime<chrono::Utc>>,
+) -> Option<chrono::Duration> {
+    Some(end? - start?)
+}
+
+/// Timestamp field, either always set or optional.
+#[cfg(feature = "chrono")]
+trait TimestampField {
+    /// Timestamp parsed from its RFC 3339 string, `None` if not set.
+    fn parse(&self)
+        -> Result<Option<chrono::DateTime<chrono::Utc>>, crate::errors::ReplicateError>;
+}
+
+#[cfg(feature = "chrono")]
+impl TimestampField for String {
+    fn parse(
+        &self,
+    ) -> Result<Option<chrono::DateTime<chrono::Utc>>, crate::errors::ReplicateError> {
+        chrono::DateTime::parse_from_rfc3339(self)
+            .map(|timestamp| Some(timestamp.with_timezone(&chrono::Utc)))
+            .map_err(|_| crate::errors::ReplicateError::InvalidTimestamp(self.clone()))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TimestampField for Option<String> {
+    fn parse(
+        &self,
+    ) -> Result<Option<chrono::DateTime<chrono::Utc>>, crate::errors::ReplicateError> {
+        match self {
+            Some(timestamp) => TimestampField::parse(timestamp),
+            None => Ok(None),
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////
+
+// Every enum of the API ends with an untagged `Unknown` variant, holding the values added to the API after this version of the crate,
+// so that a new value does not fail the deserialization of a whole response.
 
 /// Source of the prediction, either from the API or from the web
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
-#[allow(non_camel_case_types)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
 pub enum PredictionSource {
-    api,
-    web,
+    Api,
+    Web,
+
+    /// A source not known by this version of the crate.
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 /// Status of the prediction, either starting, processing, succeeded, failed or canceled
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
-#[allow(non_camel_case_types)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
 pub enum PredictionStatus {
-    starting,
-    processing,
-    succeeded,
-    failed,
-    canceled,
+    Starting,
+    Processing,
+    Succeeded,
+    Failed,
+    Canceled,
+
+    /// A status not known by this version of the crate.
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+/// Visibility of a model, either public or private
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelVisibility {
+    Public,
+    Private,
+
+    /// A visibility not known by this version of the crate.
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 /// Events of the webhook, either start, output, logs or completed
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
-#[allow(non_camel_case_types)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
 pub enum WebhookEvents {
-    start,
-    output,
-    logs,
-    completed,
+    Start,
+    Output,
+    Logs,
+    Completed,
+
+    /// An event not known by this version of the crate.
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 ///////////////////////////////////////////////////////////
 ///
 /// Implement Display for the enums
 ///
-/// ///////////////////////////////////////////////////////
-
+///////////////////////////////////////////////////////////
 macro_rules! impl_display {
     ($($t:ty),*) => ($(
         impl std::fmt::Display for $t {
@@ -350,5 +567,6 @@ macro_rules! impl_display {
 impl_display! {
     PredictionSource,
     PredictionStatus,
+    ModelVisibility,
     WebhookEvents
 }
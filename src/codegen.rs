@@ -0,0 +1,555 @@
+//! Used to generate typed bindings for a model version from its schema, e.g. in a build script.
+//!
+//! [`Bindings`] reads the OpenAPI schema of a model version, usually a JSON file saved from [`Version::get`](crate::version::Version::get)
+//! and checked into the repository, so that the generation works offline. It generates a module named after the model, containing:
+//! * an `Input` struct with a typed field for every input of the model, and an enum for every input with a fixed set of string values,
+//! * an `Output` type decoding the output of the model,
+//! * a `run` function creating a prediction of the version and waiting for its output.
+//!
+//! The generated code derives `serde` traits, so the crate using it must also depend on `serde` with the `derive` feature.
+//!
+//! # Example
+//!
+//! In `build.rs`, with `replicate-rust` in the `[build-dependencies]`:
+//!
+//! ```no_run
+//! use replicate_rust::codegen::Bindings;
+//!
+//! let out_dir = std::env::var("OUT_DIR").unwrap();
+//!
+//! Bindings::from_file(
+//!     "stability-ai/stable-diffusion:27b93a2413e7f36cd83da926f3656280b2931564ff050bf9575f1fdf9bcd7478",
+//!     "schemas/stable-diffusion.json",
+//! )?
+//! .write(format!("{}/stable_diffusion.rs", out_dir))?;
+//!
+//! println!("cargo:rerun-if-changed=schemas/stable-diffusion.json");
+//! # Ok::<(), replicate_rust::errors::ReplicateError>(())
+//! ```
+//!
+//! Then in the crate:
+//!
+//! ```ignore
+//! include!(concat!(env!("OUT_DIR"), "/stable_diffusion.rs"));
+//!
+//! let output = stable_diffusion::run(
+//!     &replicate,
+//!     &stable_diffusion::Input {
+//!         prompt: String::from("a 19th century portrait of a wombat gentleman"),
+//!         scheduler: Some(stable_diffusion::Scheduler::KEuler),
+//!         ..Default::default()
+//!     },
+//! )?;
+//! ```
+
+use std::{collections::HashMap, fmt::Write, path::Path};
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use crate::{
+    api_definitions::{GetPrediction, PredictionStatus},
+    errors::ReplicateError,
+    model_ref::ModelRef,
+    schema::{Input, Output, SchemaType, VersionSchema},
+    Replicate,
+};
+
+/// Typed bindings of a model version, generated from its schema. See the [module documentation](self).
+#[derive(Clone, Debug)]
+pub struct Bindings {
+    version: String,
+    module: String,
+    nonblocking: bool,
+    schema: VersionSchema,
+}
+
+impl Bindings {
+    /// Create the bindings of a model version, as `{owner}/{name}:{version_id}`, from its OpenAPI schema.
+    pub fn new(
+        version: &str,
+        openapi_schema: &HashMap<String, Value>,
+    ) -> Result<Self, ReplicateError> {
+        // The bindings pin the version, so that the generated types match its schema.
+        let name = match version.parse()? {
+            ModelRef::Version { name, .. } => name,
+            _ => return Err(ReplicateError::InvalidVersionString(version.to_string())),
+        };
+
+        Ok(Self {
+            version: version.to_string(),
+            module: identifier(&name),
+            nonblocking: false,
+            schema: VersionSchema::from_openapi(openapi_schema)?,
+        })
+    }
+
+    /// Create the bindings of a model version from a JSON file, containing either the response of
+    /// [`Version::get`](crate::version::Version::get) or only its `openapi_schema`.
+    pub fn from_file(version: &str, path: impl AsRef<Path>) -> Result<Self, ReplicateError> {
+        let mut document: HashMap<String, Value> = serde_json::from_slice(&std::fs::read(path)?)?;
+
+        if let Some(openapi_schema) = document.remove("openapi_schema") {
+            document = serde_json::from_value(openapi_schema)?;
+        }
+
+        Self::new(version, &document)
+    }
+
+    /// Name of the generated module. Defaults to the name of the model in snake case, e.g. `stable_diffusion`.
+    pub fn module(mut self, module: &str) -> Self {
+        self.module = identifier(module);
+        self
+    }
+
+    /// Also generate a `run_async` function, using the client of the [`nonblocking`](crate::nonblocking) module.
+    /// The crate using the bindings must then enable the `async` feature.
+    pub fn nonblocking(mut self, nonblocking: bool) -> Self {
+        self.nonblocking = nonblocking;
+        self
+    }
+
+    /// Generate the source code of the bindings.
+    pub fn generate(&self) -> String {
+        let mut enums = String::new();
+        let mut fields = String::new();
+        let mut field_names = Vec::new();
+
+        for input in &self.schema.inputs {
+            let field_type = match input_enum(input) {
+                Some(values) => {
+                    let name = type_name(&input.name);
+                    enums.push_str(&string_enum(&name, input, values));
+                    name
+                }
+                None => value_type(input.input_type.as_ref(), input.items_type.as_ref()),
+            };
+
+            fields.push_str(&doc_comment(
+                input.description.as_deref().or(input.title.as_deref()),
+                "        ",
+            ));
+            let field_name = unique(identifier(&input.name), &mut field_names);
+            if field_name.trim_start_matches("r#") != input.name {
+                writeln!(fields, "        #[serde(rename = {:?})]", input.name).unwrap();
+            }
+            match input.required {
+                true => writeln!(fields, "        pub {}: {},", field_name, field_type).unwrap(),
+                false => {
+                    fields
+                        .push_str("        #[serde(skip_serializing_if = \"Option::is_none\")]\n");
+                    writeln!(
+                        fields,
+                        "        pub {}: Option<{}>,",
+                        field_name, field_type
+                    )
+                    .unwrap();
+                }
+            }
+        }
+
+        let output = match &self.schema.output {
+            Some(output) if output.output_type == Some(SchemaType::Object) => object_output(output),
+            Some(output) => format!(
+                "    /// Output of the model.\n    pub type Output = {};\n",
+                output_type(output)
+            ),
+            None => String::from(
+                "    /// Output of the model.\n    pub type Output = ::serde_json::Value;\n",
+            ),
+        };
+
+        let mut code = String::new();
+        writeln!(
+            code,
+            "// Generated by replicate_rust::codegen from the schema of {}. Do not edit.\n",
+            self.version
+        )
+        .unwrap();
+        writeln!(
+            code,
+            "/// Typed bindings of the model version `{}`.",
+            self.version
+        )
+        .unwrap();
+        writeln!(code, "#[allow(dead_code, clippy::all)]").unwrap();
+        writeln!(code, "pub mod {} {{", self.module).unwrap();
+        writeln!(code, "    /// Model version of the bindings.").unwrap();
+        writeln!(code, "    pub const VERSION: &str = {:?};\n", self.version).unwrap();
+        code.push_str(&enums);
+        writeln!(code, "    /// Inputs of the model.").unwrap();
+        writeln!(
+            code,
+            "    #[derive(Clone, Debug, Default, PartialEq, ::serde::Serialize)]"
+        )
+        .unwrap();
+        writeln!(code, "    pub struct Input {{\n{}    }}\n", fields).unwrap();
+        writeln!(code, "{}", output).unwrap();
+        code.push_str(
+            "    /// Run the model with the given inputs, and wait for its output.\n    pub fn run(\n        replicate: &::replicate_rust::Replicate,\n        input: &Input,\n    ) -> Result<Output, ::replicate_rust::errors::ReplicateError> {\n        ::replicate_rust::codegen::run(replicate, VERSION, input)\n    }\n",
+        );
+        if self.nonblocking {
+            code.push_str(
+                "\n    /// Run the model with the given inputs, and wait for its output.\n    pub async fn run_async(\n        replicate: &::replicate_rust::nonblocking::Replicate,\n        input: &Input,\n    ) -> Result<Output, ::replicate_rust::errors::ReplicateError> {\n        ::replicate_rust::codegen::run_async(replicate, VERSION, input).await\n    }\n",
+            );
+        }
+        code.push_str("}\n");
+
+        code
+    }
+
+    /// Generate the source code of the bindings, and write it to a file, e.g. in the `OUT_DIR` of a build script.
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), ReplicateError> {
+        std::fs::write(path, self.generate())?;
+        Ok(())
+    }
+}
+
+/// Run a model version with typed inputs, and decode its output. Used by the generated `run` functions.
+///
+/// Returns a [`ReplicateError::PredictionFailed`] error if the prediction failed or was canceled.
+pub fn run<I: Serialize, O: DeserializeOwned>(
+    replicate: &Replicate,
+    version: &str,
+    input: &I,
+) -> Result<O, ReplicateError> {
+    decode(replicate.run(version, inputs(input)?)?)
+}
+
+/// Async version of [`run`]. Used by the generated `run_async` functions.
+#[cfg(feature = "async")]
+pub async fn run_async<I: Serialize, O: DeserializeOwned>(
+    replicate: &crate::nonblocking::Replicate,
+    version: &str,
+    input: &I,
+) -> Result<O, ReplicateError> {
+    decode(replicate.run(version, inputs(input)?).await?)
+}
+
+/// Convert typed inputs into the map sent to the API.
+fn inputs<I: Serialize>(input: &I) -> Result<HashMap<String, Value>, ReplicateError> {
+    Ok(serde_json::from_value(serde_json::to_value(input)?)?)
+}
+
+/// Decode the output of a completed prediction.
+fn decode<O: DeserializeOwned>(prediction: GetPrediction) -> Result<O, ReplicateError> {
+    match prediction.status {
+        PredictionStatus::Succeeded => prediction.output_as(),
+        status => Err(ReplicateError::PredictionFailed {
+            id: prediction.id,
+            status,
+            error: prediction.error,
+        }),
+    }
+}
+
+/// Allowed values of an input, if they are all strings.
+fn input_enum(input: &Input) -> Option<&Vec<Value>> {
+    input
+        .allowed_values
+        .as_ref()
+        .filter(|values| !values.is_empty() && values.iter().all(Value::is_string))
+}
+
+/// Rust type of a value of the given JSON type.
+fn value_type(schema_type: Option<&SchemaType>, items_type: Option<&SchemaType>) -> String {
+    match schema_type {
+        Some(SchemaType::String) => String::from("String"),
+        Some(SchemaType::Integer) => String::from("i64"),
+        Some(SchemaType::Number) => String::from("f64"),
+        Some(SchemaType::Boolean) => String::from("bool"),
+        Some(SchemaType::Array) => format!("Vec<{}>", value_type(items_type, None)),
+        Some(SchemaType::Object) | Some(SchemaType::Unknown(_)) | None => {
+            String::from("::serde_json::Value")
+        }
+    }
+}
+
+/// Rust type of an output, or of the items of an output.
+fn output_type(output: &Output) -> String {
+    match (&output.output_type, &output.items) {
+        (Some(SchemaType::Array), Some(items)) => format!("Vec<{}>", output_type(items)),
+        (output_type, _) => value_type(output_type.as_ref(), None),
+    }
+}
+
+/// Struct decoding an object output, with a field for every property.
+fn object_output(output: &Output) -> String {
+    let mut properties: Vec<_> = output.properties.iter().collect();
+    properties.sort_by_key(|(name, _)| name.as_str());
+
+    let mut code = String::from(
+        "    /// Output of the model.\n    #[derive(Clone, Debug, PartialEq, ::serde::Deserialize)]\n    pub struct Output {\n",
+    );
+    let mut field_names = Vec::new();
+    for (name, property) in properties {
+        let field_name = unique(identifier(name), &mut field_names);
+        if field_name.trim_start_matches("r#") != name {
+            writeln!(code, "        #[serde(rename = {:?})]", name).unwrap();
+        }
+        match output.required.contains(name) {
+            true => writeln!(
+                code,
+                "        pub {}: {},",
+                field_name,
+                output_type(property)
+            ),
+            false => {
+                code.push_str("        #[serde(default)]\n");
+                writeln!(
+                    code,
+                    "        pub {}: Option<{}>,",
+                    field_name,
+                    output_type(property)
+                )
+            }
+        }
+        .unwrap();
+    }
+    code.push_str("    }\n");
+
+    code
+}
+
+/// Allowed value of an input used as the default of its enum: the default value of the input, or the first allowed value without one.
+fn enum_default<'a>(input: &Input, values: &'a [Value]) -> Option<&'a Value> {
+    match &input.default {
+        Some(default) => values.iter().find(|value| *value == default),
+        None => values.first(),
+    }
+}
+
+/// Enum of the allowed values of an input, defaulting to the default value of the input.
+/// The enum has no default when the default value of the input is not one of the allowed values.
+/// Such inputs are never required, so that the `Input` struct can still derive `Default`.
+fn string_enum(name: &str, input: &Input, values: &[Value]) -> String {
+    let mut code = doc_comment(input.description.as_deref(), "    ");
+    let default = enum_default(input, values);
+    writeln!(
+        code,
+        "    #[derive(Clone, Copy, Debug, {}PartialEq, Eq, Hash, ::serde::Serialize, ::serde::Deserialize)]",
+        if default.is_some() { "Default, " } else { "" }
+    )
+    .unwrap();
+    writeln!(code, "    pub enum {} {{", name).unwrap();
+
+    let mut variants: Vec<String> = Vec::new();
+    for value in values {
+        let value_str = value.as_str().unwrap_or_default();
+        let mut variant = type_name(value_str);
+        while variants.contains(&variant) {
+            variant.push('_');
+        }
+
+        if Some(value) == default {
+            code.push_str("        #[default]\n");
+        }
+        writeln!(code, "        #[serde(rename = {:?})]", value_str).unwrap();
+        writeln!(code, "        {},", variant).unwrap();
+        variants.push(variant);
+    }
+    code.push_str("    }\n\n");
+
+    code
+}
+
+/// Doc comment with the given indentation, empty without text.
+fn doc_comment(text: Option<&str>, indent: &str) -> String {
+    text.map(|text| {
+        text.lines()
+            .map(|line| format!("{}/// {}\n", indent, line.trim_end()))
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// Split a name into words, on non alphanumeric characters.
+fn words(name: &str) -> impl Iterator<Item = &str> {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+}
+
+/// Name in snake case. Names without any alphanumeric character become `value`.
+fn snake_case(name: &str) -> String {
+    let mut snake = words(name)
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("_");
+
+    if snake.is_empty() {
+        snake.push_str("value");
+    } else if snake.starts_with(|c: char| c.is_ascii_digit()) {
+        snake.insert(0, '_');
+    }
+
+    snake
+}
+
+/// Field or module name, in snake case, escaping the keywords.
+/// The keywords that cannot be raw identifiers, e.g. `crate`, get a trailing underscore instead.
+fn identifier(name: &str) -> String {
+    let identifier = snake_case(name);
+
+    if RESERVED.contains(&identifier.as_str()) {
+        identifier + "_"
+    } else if KEYWORDS.contains(&identifier.as_str()) {
+        format!("r#{}", identifier)
+    } else {
+        identifier
+    }
+}
+
+/// Identifier not used yet by another field, adding trailing underscores until it is unique.
+fn unique(mut identifier: String, used: &mut Vec<String>) -> String {
+    while used.contains(&identifier) {
+        identifier.push('_');
+    }
+    used.push(identifier.clone());
+
+    identifier
+}
+
+/// Type or variant name, in camel case. Upper case words are only capitalized, e.g. `K_EULER` becomes `KEuler`.
+fn type_name(name: &str) -> String {
+    let mut camel: String = words(name)
+        .map(|word| {
+            let word = match word.chars().any(|c| c.is_ascii_lowercase()) {
+                true => word.to_string(),
+                false => word.to_ascii_lowercase(),
+            };
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect();
+
+    if camel.is_empty() || camel.starts_with(|c: char| c.is_ascii_digit()) {
+        camel.insert(0, 'V');
+    }
+    if ["Input", "Output", "Self"].contains(&camel.as_str()) {
+        camel.push_str("Value");
+    }
+
+    camel
+}
+
+/// Keywords that cannot be used as field names without escaping.
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where", "while",
+    "abstract", "become", "box", "do", "final", "macro", "override", "priv", "try", "typeof",
+    "unsized", "virtual", "yield",
+];
+
+/// Keywords that cannot be used as raw identifiers either.
+const RESERVED: &[&str] = &["crate", "self", "super"];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_names() {
+        assert_eq!(snake_case("stable-diffusion"), "stable_diffusion");
+        assert_eq!(snake_case("sdxl"), "sdxl");
+        assert_eq!(snake_case("--"), "value");
+        assert_eq!(snake_case("1024x1024"), "_1024x1024");
+        assert_eq!(identifier("type"), "r#type");
+        assert_eq!(identifier("crate"), "crate_");
+        assert_eq!(identifier("Self"), "self_");
+        assert_eq!(type_name("K_EULER"), "KEuler");
+        assert_eq!(type_name("DPMSolverMultistep"), "DPMSolverMultistep");
+        assert_eq!(type_name("1024x1024"), "V1024x1024");
+        assert_eq!(type_name("input"), "InputValue");
+    }
+
+    #[test]
+    fn test_generate() -> Result<(), ReplicateError> {
+        let openapi_schema = serde_json::from_value(json!({
+            "components": {
+                "schemas": {
+                    "Input": {
+                        "type": "object",
+                        "required": ["prompt"],
+                        "properties": {
+                            "prompt": {"type": "string", "x-order": 0, "description": "Input prompt"},
+                            "num-outputs": {"type": "integer", "default": 1, "x-order": 1},
+                            "scheduler": {
+                                "allOf": [{"$ref": "#/components/schemas/scheduler"}],
+                                "default": "K_EULER",
+                                "x-order": 2,
+                            },
+                            "type": {"type": "array", "items": {"type": "number"}, "x-order": 3},
+                        },
+                    },
+                    "Output": {"type": "array", "items": {"type": "string", "format": "uri"}},
+                    "scheduler": {"enum": ["DDIM", "K_EULER"], "type": "string"},
+                },
+            },
+        }))?;
+
+        let code = Bindings::new(
+            "stability-ai/stable-diffusion:27b93a2413e7f36cd83da926f3656280b2931564ff050bf9575f1fdf9bcd7478",
+            &openapi_schema,
+        )?
+        .generate();
+
+        for expected in [
+            "pub mod stable_diffusion {",
+            "pub const VERSION: &str = \"stability-ai/stable-diffusion:27b93a2413e7f36cd83da926f3656280b2931564ff050bf9575f1fdf9bcd7478\";",
+            "        /// Input prompt\n        pub prompt: String,",
+            "        #[serde(rename = \"num-outputs\")]\n        #[serde(skip_serializing_if = \"Option::is_none\")]\n        pub num_outputs: Option<i64>,",
+            "        pub scheduler: Option<Scheduler>,",
+            "        pub r#type: Option<Vec<f64>>,",
+            "        #[serde(rename = \"DDIM\")]\n        Ddim,",
+            "        #[default]\n        #[serde(rename = \"K_EULER\")]\n        KEuler,",
+            "    pub type Output = Vec<String>;",
+            "::replicate_rust::codegen::run(replicate, VERSION, input)",
+        ] {
+            assert!(code.contains(expected), "{} not found in {}", expected, code);
+        }
+        assert!(!code.contains("run_async"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode() {
+        let prediction = |status: &str, output: Value| -> GetPrediction {
+            serde_json::from_value(json!({
+                "id": "ufawqhfynnddngldkgtslldrkq",
+                "version": "5c7d5dc6dd8bf75c1acaa8565735e7986bc5b66206b55cca93cb72c9bf15ccaa",
+                "urls": {
+                    "get": "https://api.replicate.com/v1/predictions/ufawqhfynnddngldkgtslldrkq",
+                    "cancel": "https://api.replicate.com/v1/predictions/ufawqhfynnddngldkgtslldrkq/cancel",
+                },
+                "created_at": "2022-04-26T22:13:06.224088Z",
+                "status": status,
+                "input": {"text": "Alice"},
+                "output": output,
+                "error": None::<String>,
+                "logs": None::<String>,
+                "metrics": None::<Value>,
+                "started_at": None::<String>,
+                "completed_at": None::<String>,
+                "source": None::<String>,
+            }))
+            .unwrap()
+        };
+
+        let output: String = decode(prediction("succeeded", json!("hello Alice"))).unwrap();
+        assert_eq!(output, "hello Alice");
+
+        assert!(matches!(
+            decode::<String>(prediction("failed", Value::Null)),
+            Err(ReplicateError::PredictionFailed {
+                status: PredictionStatus::Failed,
+                ..
+            })
+        ));
+    }
+}
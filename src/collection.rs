@@ -4,7 +4,7 @@
 //!
 //! # Example
 //!
-//! ```
+//! ```no_run
 //! use replicate_rust::{Replicate, config::Config};
 //!
 //! let config = Config::default();
@@ -17,9 +17,14 @@
 //! ```
 //!
 
+use reqwest::Method;
+
 use crate::{
-    api_definitions::{GetCollectionModels, ListCollectionModels},
+    api_definitions::{GetCollectionModels, ListCollectionModels, ListCollectionModelsItem},
+    config::Config,
     errors::ReplicateError,
+    pagination::Paginator,
+    transport::Request,
 };
 
 /// Used to interact with the [Collection Endpoints](https://replicate.com/docs/reference/http#collections.get).
@@ -35,11 +40,18 @@ impl Collection {
         Self { parent: rep }
     }
 
+    /// Return a copy of this endpoint using the given policy to retry its requests, e.g. to override the client policy for a single call.
+    pub fn with_retry_policy(&self, policy: crate::retry::RetryPolicy) -> Self {
+        let mut config = self.parent.clone();
+        config.retry_policy = policy;
+        Self::new(config)
+    }
+
     /// Get a collection by slug.
     ///
     /// # Example
     ///
-    /// ```
+    /// ```no_run
     /// use replicate_rust::{Replicate, config::Config};
     ///
     /// let config = Config::default();
@@ -51,32 +63,15 @@ impl Collection {
     /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
     /// ```
     pub fn get(&self, collection_slug: &str) -> Result<GetCollectionModels, ReplicateError> {
-        let client = reqwest::blocking::Client::new();
-
-        let response = client
-            .get(format!(
-                "{}/collections/{}",
-                self.parent.base_url, collection_slug
-            ))
-            .header("Authorization", format!("Token {}", self.parent.auth))
-            .header("User-Agent", &self.parent.user_agent)
-            .send()?;
-
-        if !response.status().is_success() {
-            return Err(ReplicateError::ResponseError(response.text()?));
-        }
-
-        let response_string = response.text()?;
-        let response_struct: GetCollectionModels = serde_json::from_str(&response_string)?;
-
-        Ok(response_struct)
+        self.parent
+            .send_json(get_request(&self.parent, collection_slug))
     }
 
     /// List all collections present in Replicate.
     ///
     /// # Example
     ///
-    /// ```
+    /// ```no_run
     /// use replicate_rust::{Replicate, config::Config};
     ///
     /// let config = Config::default();
@@ -88,23 +83,38 @@ impl Collection {
     /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
     /// ```
     pub fn list(&self) -> Result<ListCollectionModels, ReplicateError> {
-        let client = reqwest::blocking::Client::new();
-
-        let response = client
-            .get(format!("{}/collections", self.parent.base_url))
-            .header("Authorization", format!("Token {}", self.parent.auth))
-            .header("User-Agent", &self.parent.user_agent)
-            .send()?;
+        self.parent.send_json(list_request(&self.parent))
+    }
 
-        if !response.status().is_success() {
-            return Err(ReplicateError::ResponseError(response.text()?));
-        }
+    /// Iterate over all the collections, following the pages lazily. See [`Paginator`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// for collection in replicate.collections.list_all() {
+    ///     println!("Collection : {:?}", collection?);
+    /// }
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn list_all(&self) -> Paginator<ListCollectionModelsItem> {
+        Paginator::new(self.parent.clone(), list_request(&self.parent).url)
+    }
+}
 
-        let response_string = response.text()?;
-        let response_struct: ListCollectionModels = serde_json::from_str(&response_string)?;
+/// Request getting a collection, shared with the [async client](crate::nonblocking::collection).
+pub(crate) fn get_request(config: &Config, collection_slug: &str) -> Request {
+    config.request(Method::GET, &format!("/collections/{}", collection_slug))
+}
 
-        Ok(response_struct)
-    }
+/// Request listing the collections, shared with the [async client](crate::nonblocking::collection).
+pub(crate) fn list_request(config: &Config) -> Request {
+    config.request(Method::GET, "/collections")
 }
 
 #[cfg(test)]
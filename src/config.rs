@@ -1,9 +1,9 @@
 //! The config module contains the Config struct, which is used to initialize configuration for the API.
-//! Currently contains the `API token`, the `user agent` and the `base url`.
+//! Currently contains the `API token`, the `user agent`, the `base url` and the settings of the HTTP client shared by all the endpoints.
 //!
 //!
 //! # Example
-//! ```
+//! ```no_run
 //! use replicate_rust::{Replicate, config::Config};
 //!
 //! let config = Config {
@@ -17,15 +17,54 @@
 //!
 //! The Config struct implements the `Default` trait, so you can also use `Default::default()` to initialize the config.
 //!
-//! ```
+//! ```no_run
 //! use replicate_rust::{Replicate, config::Config};
 //!
 //! let config = Config::default();
 //!
 //! let replicate = Replicate::new(config);
-//! ```    
+//! ```
+//!
+//! ## Configuring the HTTP client
+//!
+//! All the endpoints of a [`Replicate`](crate::Replicate) client share a single [transport](crate::transport), built from the config when the client is created.
+//! Use [`Config::builder`] to set timeouts, a proxy, extra default headers or custom TLS root certificates, to inject a pre-built `reqwest` client
+//! or to plug in a custom [`Transport`].
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use replicate_rust::{Replicate, config::Config};
+//!
+//! let config = Config::builder()
+//!     .auth("REPLICATE_API_TOKEN")
+//!     .connect_timeout(Duration::from_secs(5))
+//!     .timeout(Duration::from_secs(60))
+//!     .proxy(reqwest::Proxy::all("http://proxy.example.com:8080")?)
+//!     .build();
+//!
+//! let replicate = Replicate::new(config);
+//! # Ok::<(), reqwest::Error>(())
+//! ```
 
-/// The Config struct is used to initialize configuration for the API. Currently contains the `API token`, the `user agent` and the `base url`.
+use std::{sync::Arc, time::Duration};
+
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, USER_AGENT},
+    Certificate, Method, Proxy,
+};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    errors::ApiError,
+    errors::ReplicateError,
+    retry::RetryPolicy,
+    transport::{HttpTransport, Request, Response, StreamingResponse, Transport},
+};
+
+#[cfg(feature = "async")]
+use crate::transport::{AsyncHttpTransport, AsyncStreamingResponse, AsyncTransport};
+
+/// The Config struct is used to initialize configuration for the API. Currently contains the `API token`, the `user agent`, the `base url` and the settings of the HTTP client.
 #[derive(Clone, Debug)]
 pub struct Config {
     /// The API token to use for authentication.
@@ -36,6 +75,64 @@ pub struct Config {
 
     /// The base url to use for the API requests. Defaults to `https://api.replicate.com/v1`.
     pub base_url: String,
+
+    /// Timeout for establishing a connection. Defaults to no timeout.
+    pub connect_timeout: Option<Duration>,
+
+    /// Timeout for a whole request, from connecting until the response body has been read. Defaults to the `reqwest` default.
+    pub timeout: Option<Duration>,
+
+    /// Proxy to route all the requests through.
+    pub proxy: Option<Proxy>,
+
+    /// Headers sent with every request, in addition to the `Authorization` and `User-Agent` headers.
+    pub default_headers: HeaderMap,
+
+    /// Extra root certificates to trust, in addition to the system ones.
+    pub root_certificates: Vec<Certificate>,
+
+    /// Policy used to retry the requests failing with a transient error. See the [`retry`](crate::retry) module.
+    pub retry_policy: RetryPolicy,
+
+    /// Policy used to space out the status checks while waiting for a prediction to complete. Defaults to [`RetryPolicy::polling`].
+    pub poll_policy: RetryPolicy,
+
+    /// Transport used by the blocking endpoints. When set, the timeout, proxy, default headers and root certificates settings are ignored.
+    ///
+    /// Set by [`Replicate::try_new`](crate::Replicate::try_new) when empty, so that all the endpoints share one transport.
+    /// A config used without a client builds a new transport for each request.
+    pub transport: Option<Arc<dyn Transport>>,
+
+    /// Transport used by the async endpoints. When set, the timeout, proxy, default headers and root certificates settings are ignored.
+    ///
+    /// Set by [`nonblocking::Replicate::try_new`](crate::nonblocking::Replicate::try_new) when empty, so that all the endpoints share one transport.
+    /// A config used without a client builds a new transport for each request.
+    #[cfg(feature = "async")]
+    pub async_transport: Option<Arc<dyn AsyncTransport>>,
+}
+
+/// Apply the timeout, proxy, default headers and root certificates settings of a config to a `reqwest` client builder.
+/// The blocking and async builders share these methods, but no trait.
+macro_rules! client_settings {
+    ($builder:expr, $config:expr) => {{
+        let config: &Config = $config;
+        let mut builder = $builder.default_headers(config.default_headers.clone());
+
+        if let Some(timeout) = config.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = config.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(proxy) = &config.proxy {
+            builder = builder.proxy(proxy.clone());
+        }
+        for certificate in &config.root_certificates {
+            builder = builder.add_root_certificate(certificate.clone());
+        }
+
+        builder
+    }};
 }
 
 // Default implementation for Client
@@ -44,35 +141,343 @@ impl Default for Config {
     /// Create a new Config struct with the default values.
     fn default() -> Self {
         Self {
-            auth: match std::env::var("REPLICATE_API_TOKEN") {
-                Ok(token) => token,
-                Err(_) => String::new(),
-            },
+            auth: std::env::var("REPLICATE_API_TOKEN").unwrap_or_default(),
             user_agent: format!("replicate-rust/{}", env!("CARGO_PKG_VERSION")),
             base_url: String::from("https://api.replicate.com/v1"),
+            connect_timeout: None,
+            timeout: None,
+            proxy: None,
+            default_headers: HeaderMap::new(),
+            root_certificates: Vec::new(),
+            retry_policy: RetryPolicy::default(),
+            poll_policy: RetryPolicy::polling(),
+            transport: None,
+            #[cfg(feature = "async")]
+            async_transport: None,
         }
     }
 }
 
 impl Config {
-    /// Check if auth is set and exit if not.
+    /// Create a new [`ConfigBuilder`], starting from the default values.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use replicate_rust::config::Config;
+    ///
+    /// let config = Config::builder()
+    ///     .auth("REPLICATE_API_TOKEN")
+    ///     .timeout(Duration::from_secs(60))
+    ///     .build();
+    ///
+    /// assert_eq!(config.timeout, Some(Duration::from_secs(60)));
+    /// ```
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::default()
+    }
+
+    /// Check if auth is set.
     /// The auth token can be set in the environment variable `REPLICATE_API_TOKEN`.
     /// Otherwise, it can be set in the `Config` struct.
-    pub fn check_auth(&self) {
-        // Check if auth is set.
+    ///
+    /// Only checks that a token is present, use [`Replicate::validate_token`](crate::Replicate::validate_token) to check it against the API.
+    pub fn check_auth(&self) -> Result<(), ReplicateError> {
         if self.auth.is_empty() {
-            eprintln!("No API token provided. You need to set the REPLICATE_API_TOKEN environment variable or create a client with `Config {{auth: String::from('REPLICATE_API_TOKEN'), ..Default::default()}}`.
+            return Err(ReplicateError::MissingCredentials);
+        }
+
+        Ok(())
+    }
+
+    /// Build a blocking HTTP client from the timeout, proxy, default headers and root certificates settings.
+    pub fn build_http_client(&self) -> Result<reqwest::blocking::Client, ReplicateError> {
+        Ok(client_settings!(reqwest::blocking::Client::builder(), self).build()?)
+    }
+
+    /// Build an async HTTP client from the timeout, proxy, default headers and root certificates settings.
+    #[cfg(feature = "async")]
+    pub fn build_async_http_client(&self) -> Result<reqwest::Client, ReplicateError> {
+        Ok(client_settings!(reqwest::Client::builder(), self).build()?)
+    }
 
-You can find your API key on https://replicate.com");
+    /// Return the blocking transport, or build one from the settings if none has been set.
+    pub(crate) fn transport(&self) -> Result<Arc<dyn Transport>, ReplicateError> {
+        match &self.transport {
+            Some(transport) => Ok(transport.clone()),
+            None => Ok(Arc::new(HttpTransport::new(self.build_http_client()?))),
+        }
+    }
 
-            std::process::exit(1);
+    /// Return the async transport, or build one from the settings if none has been set.
+    #[cfg(feature = "async")]
+    pub(crate) fn async_transport(&self) -> Result<Arc<dyn AsyncTransport>, ReplicateError> {
+        match &self.async_transport {
+            Some(transport) => Ok(transport.clone()),
+            None => Ok(Arc::new(AsyncHttpTransport::new(
+                self.build_async_http_client()?,
+            ))),
         }
     }
+
+    /// Url of a path of the API, as `{base_url}{path}`.
+    pub(crate) fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// Create a request to `{base_url}{path}` with the authentication and user agent headers set.
+    pub(crate) fn request(&self, method: Method, path: &str) -> Request {
+        self.request_url(method, self.url(path))
+    }
+
+    /// Create a request to an absolute url returned by the API, with the authentication and user agent headers set.
+    pub(crate) fn request_url(&self, method: Method, url: impl Into<String>) -> Request {
+        let mut request = Request::new(method, url);
+
+        if let Ok(value) = HeaderValue::from_str(&format!("Token {}", self.auth)) {
+            request.headers.insert(AUTHORIZATION, value);
+        }
+        if let Ok(value) = HeaderValue::from_str(&self.user_agent) {
+            request.headers.insert(USER_AGENT, value);
+        }
+
+        request
+    }
+
+    /// Send a request through the blocking transport, turning non 2xx responses into errors.
+    /// Transient errors are retried according to the retry policy.
+    pub(crate) fn send(&self, request: Request) -> Result<Response, ReplicateError> {
+        let transport = self.transport()?;
+        let mut backoff = self.retry_policy.backoff();
+
+        loop {
+            let error = match transport
+                .send(request.clone())
+                .and_then(|response| response.error_for_status(&request))
+            {
+                Ok(response) => return Ok(response),
+                Err(error) => error,
+            };
+
+            match backoff.retry(&error) {
+                Some(delay) => std::thread::sleep(delay),
+                None => return Err(error),
+            }
+        }
+    }
+
+    /// Send a request through the blocking transport without waiting for the response body, turning non 2xx responses into errors.
+    /// Transient errors are retried according to the retry policy.
+    pub(crate) fn send_streaming(
+        &self,
+        request: Request,
+    ) -> Result<StreamingResponse, ReplicateError> {
+        let transport = self.transport()?;
+        let mut backoff = self.retry_policy.backoff();
+
+        loop {
+            let error = match transport.send_streaming(request.clone()) {
+                Ok(response) if response.status.is_success() => return Ok(response),
+                Ok(response) => match response.into_response() {
+                    Ok(response) => {
+                        ReplicateError::ResponseError(Box::new(ApiError::new(&request, &response)))
+                    }
+                    Err(error) => error,
+                },
+                Err(error) => error,
+            };
+
+            match backoff.retry(&error) {
+                Some(delay) => std::thread::sleep(delay),
+                None => return Err(error),
+            }
+        }
+    }
+
+    /// Send a request through the blocking transport and parse the JSON response.
+    pub(crate) fn send_json<T: DeserializeOwned>(
+        &self,
+        request: Request,
+    ) -> Result<T, ReplicateError> {
+        self.send(request)?.json()
+    }
+
+    /// Send a request through the async transport, turning non 2xx responses into errors.
+    /// Transient errors are retried according to the retry policy.
+    #[cfg(feature = "async")]
+    pub(crate) async fn async_send(&self, request: Request) -> Result<Response, ReplicateError> {
+        let transport = self.async_transport()?;
+        let mut backoff = self.retry_policy.backoff();
+
+        loop {
+            let error = match transport.send(request.clone()).await {
+                Ok(response) => match response.error_for_status(&request) {
+                    Ok(response) => return Ok(response),
+                    Err(error) => error,
+                },
+                Err(error) => error,
+            };
+
+            match backoff.retry(&error) {
+                Some(delay) => tokio::time::sleep(delay).await,
+                None => return Err(error),
+            }
+        }
+    }
+
+    /// Send a request through the async transport without waiting for the response body, turning non 2xx responses into errors.
+    /// Transient errors are retried according to the retry policy.
+    #[cfg(feature = "async")]
+    pub(crate) async fn async_send_streaming(
+        &self,
+        request: Request,
+    ) -> Result<AsyncStreamingResponse, ReplicateError> {
+        let transport = self.async_transport()?;
+        let mut backoff = self.retry_policy.backoff();
+
+        loop {
+            let error = match transport.send_streaming(request.clone()).await {
+                Ok(response) if response.status.is_success() => return Ok(response),
+                Ok(response) => match response.into_response().await {
+                    Ok(response) => {
+                        ReplicateError::ResponseError(Box::new(ApiError::new(&request, &response)))
+                    }
+                    Err(error) => error,
+                },
+                Err(error) => error,
+            };
+
+            match backoff.retry(&error) {
+                Some(delay) => tokio::time::sleep(delay).await,
+                None => return Err(error),
+            }
+        }
+    }
+
+    /// Send a request through the async transport and parse the JSON response.
+    #[cfg(feature = "async")]
+    pub(crate) async fn async_send_json<T: DeserializeOwned>(
+        &self,
+        request: Request,
+    ) -> Result<T, ReplicateError> {
+        self.async_send(request).await?.json()
+    }
+}
+
+/// Builder for [`Config`]. Created with [`Config::builder`].
+#[derive(Clone, Debug, Default)]
+pub struct ConfigBuilder {
+    config: Config,
+}
+
+impl ConfigBuilder {
+    /// Set the API token to use for authentication.
+    pub fn auth(mut self, auth: impl Into<String>) -> Self {
+        self.config.auth = auth.into();
+        self
+    }
+
+    /// Set the user agent to use for the API requests.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.config.user_agent = user_agent.into();
+        self
+    }
+
+    /// Set the base url to use for the API requests.
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.config.base_url = base_url.into();
+        self
+    }
+
+    /// Set the timeout for establishing a connection.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.config.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the timeout for a whole request, from connecting until the response body has been read.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeout = Some(timeout);
+        self
+    }
+
+    /// Route all the requests through the given proxy.
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.config.proxy = Some(proxy);
+        self
+    }
+
+    /// Add a header sent with every request.
+    pub fn default_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.config.default_headers.insert(name, value);
+        self
+    }
+
+    /// Add several headers sent with every request.
+    pub fn default_headers(mut self, headers: HeaderMap) -> Self {
+        self.config.default_headers.extend(headers);
+        self
+    }
+
+    /// Trust an extra root certificate, e.g. the one of a corporate proxy.
+    pub fn add_root_certificate(mut self, certificate: Certificate) -> Self {
+        self.config.root_certificates.push(certificate);
+        self
+    }
+
+    /// Set the policy used to retry the requests failing with a transient error.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.config.retry_policy = policy;
+        self
+    }
+
+    /// Set the policy used to space out the status checks while waiting for a prediction to complete.
+    pub fn poll_policy(mut self, policy: RetryPolicy) -> Self {
+        self.config.poll_policy = policy;
+        self
+    }
+
+    /// Use a pre-built blocking `reqwest` client instead of building one from the settings.
+    pub fn http_client(self, client: reqwest::blocking::Client) -> Self {
+        self.transport(HttpTransport::new(client))
+    }
+
+    /// Send the blocking requests through a custom transport.
+    pub fn transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.config.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Use a pre-built async `reqwest` client instead of building one from the settings.
+    #[cfg(feature = "async")]
+    pub fn async_http_client(self, client: reqwest::Client) -> Self {
+        self.async_transport(AsyncHttpTransport::new(client))
+    }
+
+    /// Send the async requests through a custom transport.
+    #[cfg(feature = "async")]
+    pub fn async_transport(mut self, transport: impl AsyncTransport + 'static) -> Self {
+        self.config.async_transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Build the [`Config`].
+    pub fn build(self) -> Config {
+        self.config
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Replicate;
+
+    use crate::retry::{RetryPolicy, RetryStrategy};
+    use httpmock::{
+        Method::{GET, POST},
+        MockServer,
+    };
+    use serde_json::json;
 
     #[test]
     fn test_default() {
@@ -84,15 +489,242 @@ mod tests {
             format!("replicate-rust/{}", env!("CARGO_PKG_VERSION"))
         );
         assert_eq!(config.base_url, "https://api.replicate.com/v1");
+        assert!(config.transport.is_none());
+    }
+
+    #[test]
+    fn test_transport_built_once() -> Result<(), ReplicateError> {
+        let config = Config::builder().auth("test").build();
+        assert!(config.transport.is_none());
+
+        // The client builds the transport, and all its endpoints share it.
+        let replicate = Replicate::try_new(config)?;
+        let transport = replicate.config.transport()?;
+        assert!(Arc::ptr_eq(&transport, &replicate.config.transport()?));
+        assert!(Arc::ptr_eq(
+            &transport,
+            &replicate.collections.parent.transport()?
+        ));
+        assert!(Arc::ptr_eq(
+            &transport,
+            &replicate.predictions.parent.transport()?
+        ));
+        assert!(Arc::ptr_eq(
+            &transport,
+            &replicate
+                .collections
+                .with_retry_policy(RetryPolicy::none())
+                .parent
+                .transport()?
+        ));
+
+        Ok(())
     }
 
-    // Check if auth is set. It is supposed to exit with code 1.
     #[test]
     fn test_check_auth() {
         let config = Config {
             auth: "Test".to_string(),
             ..Default::default()
         };
-        config.check_auth();
+        assert!(config.check_auth().is_ok());
+
+        let config = Config {
+            auth: String::new(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.check_auth(),
+            Err(ReplicateError::MissingCredentials)
+        ));
+    }
+
+    #[test]
+    fn test_shared_client_settings() -> Result<(), ReplicateError> {
+        let server = MockServer::start();
+
+        let get_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/collections/super-resolution")
+                .header("x-custom", "1")
+                .header("authorization", "Token test");
+            then.status(200).json_body_obj(&json!({
+                "name": "Super resolution",
+                "slug": "super-resolution",
+                "description": "Upscaling models that create high-quality images from low-quality images.",
+                "models": [],
+            }));
+        });
+
+        let config = Config::builder()
+            .auth("test")
+            .base_url(server.base_url())
+            .default_header(
+                HeaderName::from_static("x-custom"),
+                HeaderValue::from_static("1"),
+            )
+            .build();
+        let replicate = Replicate::new(config);
+
+        // The transport is built once and shared by the endpoints.
+        assert!(replicate.collections.parent.transport.is_some());
+
+        replicate.collections.get("super-resolution")?;
+        get_mock.assert();
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_timeout() {
+        let server = MockServer::start();
+
+        server.mock(|when, then| {
+            when.method(GET).path("/collections");
+            then.status(200)
+                .delay(Duration::from_millis(500))
+                .json_body_obj(
+                    &json!({"results": [], "next": None::<String>, "previous": None::<String>}),
+                );
+        });
+
+        let config = Config::builder()
+            .auth("test")
+            .base_url(server.base_url())
+            .timeout(Duration::from_millis(50))
+            .build();
+        let replicate = Replicate::new(config);
+
+        let result = replicate.collections.list();
+        assert!(matches!(result, Err(ReplicateError::ReqwestError(e)) if e.is_timeout()));
+    }
+
+    #[test]
+    fn test_injected_http_client() -> Result<(), ReplicateError> {
+        let server = MockServer::start();
+
+        let get_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/collections")
+                .header("x-injected", "yes");
+            then.status(200).json_body_obj(
+                &json!({"results": [], "next": None::<String>, "previous": None::<String>}),
+            );
+        });
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-injected", HeaderValue::from_static("yes"));
+        let client = reqwest::blocking::Client::builder()
+            .default_headers(headers)
+            .build()?;
+
+        let config = Config::builder()
+            .auth("test")
+            .base_url(server.base_url())
+            .http_client(client)
+            .build();
+        let replicate = Replicate::new(config);
+
+        replicate.collections.list()?;
+        get_mock.assert();
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_builder() {
+        let config = Config::builder()
+            .auth("test")
+            .base_url("http://localhost")
+            .connect_timeout(Duration::from_secs(1))
+            .timeout(Duration::from_secs(2))
+            .default_header(
+                HeaderName::from_static("x-custom"),
+                HeaderValue::from_static("1"),
+            )
+            .build();
+
+        assert_eq!(config.auth, "test");
+        assert_eq!(config.base_url, "http://localhost");
+        assert_eq!(config.connect_timeout, Some(Duration::from_secs(1)));
+        assert_eq!(config.timeout, Some(Duration::from_secs(2)));
+        assert_eq!(config.default_headers["x-custom"], "1");
+    }
+
+    #[test]
+    fn test_retry_server_error() {
+        let server = MockServer::start();
+
+        let get_mock = server.mock(|when, then| {
+            when.method(GET).path("/collections");
+            then.status(503).body("Service unavailable");
+        });
+
+        let config = Config::builder()
+            .auth("test")
+            .base_url(server.base_url())
+            .retry_policy(RetryPolicy::new(2, RetryStrategy::FixedDelay(1)))
+            .build();
+        let replicate = Replicate::new(config);
+
+        let result = replicate.collections.list();
+        assert!(matches!(result, Err(ReplicateError::ResponseError(_))));
+
+        // The first attempt and the two retries.
+        get_mock.assert_hits(3);
+
+        // Overriding the policy for a single call.
+        let result = replicate
+            .collections
+            .with_retry_policy(RetryPolicy::none())
+            .list();
+        assert!(result.is_err());
+        get_mock.assert_hits(4);
+    }
+
+    #[test]
+    fn test_no_retry_non_idempotent() {
+        let server = MockServer::start();
+
+        let post_mock = server.mock(|when, then| {
+            when.method(POST).path("/trainings/t1/cancel");
+            then.status(500).body("Internal server error");
+        });
+
+        let config = Config::builder()
+            .auth("test")
+            .base_url(server.base_url())
+            .retry_policy(RetryPolicy::new(2, RetryStrategy::FixedDelay(1)))
+            .build();
+        let replicate = Replicate::new(config);
+
+        assert!(replicate.trainings.cancel("t1").is_err());
+        post_mock.assert_hits(1);
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let server = MockServer::start();
+
+        let post_mock = server.mock(|when, then| {
+            when.method(POST).path("/trainings/t1/cancel");
+            then.status(429)
+                .header("retry-after", "0")
+                .body("Request was throttled.");
+        });
+
+        // The strategy delay is long, so the test only finishes quickly if Retry-After is honored.
+        let config = Config::builder()
+            .auth("test")
+            .base_url(server.base_url())
+            .retry_policy(RetryPolicy::new(1, RetryStrategy::FixedDelay(60_000)))
+            .build();
+        let replicate = Replicate::new(config);
+
+        let result = replicate.trainings.cancel("t1");
+        assert!(
+            matches!(result, Err(ReplicateError::ResponseError(e)) if e.body == "Request was throttled.")
+        );
+        post_mock.assert_hits(2);
     }
 }
@@ -0,0 +1,542 @@
+//! Used to interact with the [Deployment Endpoints](https://replicate.com/docs/reference/http#deployments.get).
+//!
+//! A deployment runs a model version on dedicated hardware, scaling between a minimum and a maximum number of instances.
+//! Updating a deployment creates a new release, see [`GetDeployment::current_release`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use replicate_rust::{Replicate, config::Config};
+//!
+//! let config = Config::default();
+//! let replicate = Replicate::new(config);
+//!
+//! // Construct the inputs.
+//! let mut inputs = std::collections::HashMap::new();
+//! inputs.insert("prompt", "a  19th century portrait of a wombat gentleman");
+//!
+//! // Run the model of the deployment.
+//! let result = replicate
+//!     .deployments
+//!     .predictions
+//!     .create("acme", "image-upscaler", inputs)?
+//!     .wait()?;
+//!
+//! println!("Result : {:?}", result.output);
+//!
+//! # Ok::<(), replicate_rust::errors::ReplicateError>(())
+//! ```
+
+use std::collections::HashMap;
+
+use reqwest::Method;
+use serde::Serialize;
+
+use crate::{
+    api_definitions::{GetDeployment, ListDeployments},
+    config::Config,
+    errors::ReplicateError,
+    pagination::Paginator,
+    prediction::{PredictionOptions, PredictionPayload},
+    prediction_client::PredictionClient,
+    transport::Request,
+};
+
+/// Contains all the options for creating a deployment.
+#[derive(Clone, Debug, Serialize)]
+pub struct DeploymentOptions {
+    /// Name of the deployment.
+    pub name: String,
+
+    /// Model run by the deployment, as `{owner}/{name}`.
+    pub model: String,
+
+    /// Version of the model run by the deployment.
+    pub version: String,
+
+    /// SKU of the hardware running the model, e.g. `gpu-t4`.
+    pub hardware: String,
+
+    /// Minimum number of instances, kept running even without traffic.
+    pub min_instances: u32,
+
+    /// Maximum number of instances the deployment scales up to.
+    pub max_instances: u32,
+}
+
+/// Contains the options for updating a deployment. Fields left to `None` are not changed.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct DeploymentUpdate {
+    /// Model run by the deployment, as `{owner}/{name}`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+
+    /// Version of the model run by the deployment.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+
+    /// SKU of the hardware running the model, e.g. `gpu-t4`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hardware: Option<String>,
+
+    /// Minimum number of instances, kept running even without traffic.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_instances: Option<u32>,
+
+    /// Maximum number of instances the deployment scales up to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_instances: Option<u32>,
+}
+
+/// Used to interact with the [Deployment Endpoints](https://replicate.com/docs/reference/http#deployments.get).
+#[derive(Clone, Debug)]
+pub struct Deployment {
+    /// Holds a reference to a Configuration struct, which contains the base url,  auth token among other settings.
+    pub parent: crate::config::Config,
+
+    /// Holds a reference to a DeploymentPredictions struct. Use to run predictions on a deployment.
+    pub predictions: DeploymentPredictions,
+}
+
+impl Deployment {
+    /// Create a new Deployment struct.
+    pub fn new(rep: crate::config::Config) -> Self {
+        let predictions = DeploymentPredictions::new(rep.clone());
+        Self {
+            parent: rep,
+            predictions,
+        }
+    }
+
+    /// Return a copy of this endpoint using the given policy to retry its requests, e.g. to override the client policy for a single call.
+    pub fn with_retry_policy(&self, policy: crate::retry::RetryPolicy) -> Self {
+        let mut config = self.parent.clone();
+        config.retry_policy = policy;
+        Self::new(config)
+    }
+
+    /// Get a deployment, along with its current release.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let deployment = replicate.deployments.get("acme", "image-upscaler")?;
+    /// println!("Release : {:?}", deployment.current_release);
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn get(
+        &self,
+        deployment_owner: &str,
+        deployment_name: &str,
+    ) -> Result<GetDeployment, ReplicateError> {
+        self.parent
+            .send_json(get_request(&self.parent, deployment_owner, deployment_name))
+    }
+
+    /// List the deployments of the user.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let deployments = replicate.deployments.list()?;
+    /// println!("Deployments : {:?}", deployments);
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn list(&self) -> Result<ListDeployments, ReplicateError> {
+        self.parent.send_json(list_request(&self.parent))
+    }
+
+    /// Iterate over all the deployments, following the pages lazily. See [`Paginator`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// for deployment in replicate.deployments.list_all() {
+    ///     println!("Deployment : {}", deployment?.name);
+    /// }
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn list_all(&self) -> Paginator<GetDeployment> {
+        Paginator::new(self.parent.clone(), list_request(&self.parent).url)
+    }
+
+    /// Create a deployment, owned by the user.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config, deployment::DeploymentOptions};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let deployment = replicate.deployments.create(DeploymentOptions {
+    ///     name: String::from("image-upscaler"),
+    ///     model: String::from("nightmareai/real-esrgan"),
+    ///     version: String::from("42fed1c4974146d4d2414e2be2c5277c7fcf05fcc3a73abf41610695738c1d7b"),
+    ///     hardware: String::from("gpu-t4"),
+    ///     min_instances: 0,
+    ///     max_instances: 2,
+    /// })?;
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn create(&self, options: DeploymentOptions) -> Result<GetDeployment, ReplicateError> {
+        self.parent
+            .send_json(create_request(&self.parent, &options)?)
+    }
+
+    /// Update a deployment, creating a new release.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config, deployment::DeploymentUpdate};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let deployment = replicate.deployments.update(
+    ///     "acme",
+    ///     "image-upscaler",
+    ///     DeploymentUpdate {
+    ///         max_instances: Some(5),
+    ///         ..Default::default()
+    ///     },
+    /// )?;
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn update(
+        &self,
+        deployment_owner: &str,
+        deployment_name: &str,
+        options: DeploymentUpdate,
+    ) -> Result<GetDeployment, ReplicateError> {
+        self.parent.send_json(update_request(
+            &self.parent,
+            deployment_owner,
+            deployment_name,
+            &options,
+        )?)
+    }
+
+    /// Delete a deployment. Only deployments that have been offline and unused for some time can be deleted.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// replicate.deployments.delete("acme", "image-upscaler")?;
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn delete(
+        &self,
+        deployment_owner: &str,
+        deployment_name: &str,
+    ) -> Result<(), ReplicateError> {
+        self.parent.send(delete_request(
+            &self.parent,
+            deployment_owner,
+            deployment_name,
+        ))?;
+
+        Ok(())
+    }
+}
+
+/// Used to run predictions on a deployment, with the [Deployment Prediction Endpoint](https://replicate.com/docs/reference/http#deployments.predictions.create).
+#[derive(Clone, Debug)]
+pub struct DeploymentPredictions {
+    /// Holds a reference to a Configuration struct, which contains the base url,  auth token among other settings.
+    pub parent: crate::config::Config,
+}
+
+impl DeploymentPredictions {
+    /// Create a new DeploymentPredictions struct.
+    pub fn new(rep: crate::config::Config) -> Self {
+        Self { parent: rep }
+    }
+
+    /// Create a new prediction running the current release of a deployment.
+    /// Returns a [`PredictionClient`], to reload, cancel or wait for the prediction like any other.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let mut inputs = std::collections::HashMap::new();
+    /// inputs.insert("image", "https://example.com/wombat.png");
+    ///
+    /// let prediction = replicate
+    ///     .deployments
+    ///     .predictions
+    ///     .create("acme", "image-upscaler", inputs)?;
+    ///
+    /// println!("Result : {:?}", prediction.wait()?);
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn create<K: serde::Serialize, V: serde::ser::Serialize>(
+        &self,
+        deployment_owner: &str,
+        deployment_name: &str,
+        inputs: HashMap<K, V>,
+    ) -> Result<PredictionClient, ReplicateError> {
+        self.create_with_options(
+            deployment_owner,
+            deployment_name,
+            inputs,
+            PredictionOptions::default(),
+        )
+    }
+
+    /// Create a new prediction running the current release of a deployment, with options, e.g. to stream its output.
+    /// See [`Prediction::create_with_options`](crate::prediction::Prediction::create_with_options).
+    pub fn create_with_options<K: serde::Serialize, V: serde::ser::Serialize>(
+        &self,
+        deployment_owner: &str,
+        deployment_name: &str,
+        inputs: HashMap<K, V>,
+        options: PredictionOptions,
+    ) -> Result<PredictionClient, ReplicateError> {
+        let request = create_prediction_request(
+            &self.parent,
+            deployment_owner,
+            deployment_name,
+            inputs,
+            options,
+        )?;
+
+        PredictionClient::send_create(self.parent.clone(), request)
+    }
+}
+
+/// Path of a deployment.
+fn deployment_path(deployment_owner: &str, deployment_name: &str) -> String {
+    format!("/deployments/{}/{}", deployment_owner, deployment_name)
+}
+
+/// Request getting a deployment, shared with the [async client](crate::nonblocking::deployment).
+pub(crate) fn get_request(
+    config: &Config,
+    deployment_owner: &str,
+    deployment_name: &str,
+) -> Request {
+    config.request(
+        Method::GET,
+        &deployment_path(deployment_owner, deployment_name),
+    )
+}
+
+/// Request listing the deployments, shared with the [async client](crate::nonblocking::deployment).
+pub(crate) fn list_request(config: &Config) -> Request {
+    config.request(Method::GET, "/deployments")
+}
+
+/// Request creating a deployment, shared with the [async client](crate::nonblocking::deployment).
+pub(crate) fn create_request(
+    config: &Config,
+    options: &DeploymentOptions,
+) -> Result<Request, ReplicateError> {
+    config.request(Method::POST, "/deployments").json(options)
+}
+
+/// Request updating a deployment, shared with the [async client](crate::nonblocking::deployment).
+pub(crate) fn update_request(
+    config: &Config,
+    deployment_owner: &str,
+    deployment_name: &str,
+    options: &DeploymentUpdate,
+) -> Result<Request, ReplicateError> {
+    config
+        .request(
+            Method::PATCH,
+            &deployment_path(deployment_owner, deployment_name),
+        )
+        .json(options)
+}
+
+/// Request deleting a deployment, shared with the [async client](crate::nonblocking::deployment).
+pub(crate) fn delete_request(
+    config: &Config,
+    deployment_owner: &str,
+    deployment_name: &str,
+) -> Request {
+    config.request(
+        Method::DELETE,
+        &deployment_path(deployment_owner, deployment_name),
+    )
+}
+
+/// Request creating a prediction on a deployment, which runs the version of its current release, shared with the [async client](crate::nonblocking::deployment).
+pub(crate) fn create_prediction_request<K: serde::Serialize, V: serde::ser::Serialize>(
+    config: &Config,
+    deployment_owner: &str,
+    deployment_name: &str,
+    inputs: HashMap<K, V>,
+    options: PredictionOptions,
+) -> Result<Request, ReplicateError> {
+    let path = format!(
+        "{}/predictions",
+        deployment_path(deployment_owner, deployment_name)
+    );
+
+    config
+        .request(Method::POST, &path)
+        .json(&PredictionPayload::new(String::new(), inputs, options))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{config::Config, Replicate};
+
+    use super::*;
+    use httpmock::{
+        Method::{GET, PATCH, POST},
+        MockServer,
+    };
+    use serde_json::json;
+
+    fn deployment() -> serde_json::Value {
+        json!({
+            "owner": "acme",
+            "name": "image-upscaler",
+            "current_release": {
+                "number": 1,
+                "model": "nightmareai/real-esrgan",
+                "version": "42fed1c4974146d4d2414e2be2c5277c7fcf05fcc3a73abf41610695738c1d7b",
+                "created_at": "2022-01-01T00:00:00.000000Z",
+                "created_by": {
+                    "type": "organization",
+                    "username": "acme",
+                    "name": "Acme Corp, Inc.",
+                    "github_url": "https://github.com/acme",
+                },
+                "configuration": {
+                    "hardware": "gpu-t4",
+                    "min_instances": 1,
+                    "max_instances": 5,
+                },
+            },
+        })
+    }
+
+    #[test]
+    fn test_get_update() -> Result<(), ReplicateError> {
+        let server = MockServer::start();
+
+        let get_mock = server.mock(|when, then| {
+            when.method(GET).path("/deployments/acme/image-upscaler");
+            then.status(200).json_body_obj(&deployment());
+        });
+        let update_mock = server.mock(|when, then| {
+            when.method(PATCH)
+                .path("/deployments/acme/image-upscaler")
+                .json_body_obj(&json!({"max_instances": 5}));
+            then.status(200).json_body_obj(&deployment());
+        });
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: server.base_url(),
+            ..Config::default()
+        };
+        let replicate = Replicate::new(config);
+
+        let result = replicate.deployments.get("acme", "image-upscaler")?;
+        let release = result.current_release.unwrap();
+        assert_eq!(release.model, "nightmareai/real-esrgan");
+        assert_eq!(release.configuration.hardware, "gpu-t4");
+        assert_eq!(release.configuration.min_instances, 1);
+
+        replicate.deployments.update(
+            "acme",
+            "image-upscaler",
+            DeploymentUpdate {
+                max_instances: Some(5),
+                ..Default::default()
+            },
+        )?;
+
+        // Ensure the mocks were called as expected
+        get_mock.assert();
+        update_mock.assert();
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_prediction() -> Result<(), ReplicateError> {
+        let server = MockServer::start();
+
+        let post_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/deployments/acme/image-upscaler/predictions")
+                .json_body_obj(&json!({"input": {"text": "Alice"}}));
+            then.status(201).json_body_obj(&json!({
+                "id": "ufawqhfynnddngldkgtslldrkq",
+                "version": "42fed1c4974146d4d2414e2be2c5277c7fcf05fcc3a73abf41610695738c1d7b",
+                "urls": {
+                    "get": "https://api.replicate.com/v1/predictions/ufawqhfynnddngldkgtslldrkq",
+                    "cancel": "https://api.replicate.com/v1/predictions/ufawqhfynnddngldkgtslldrkq/cancel",
+                },
+                "created_at": "2022-04-26T22:13:06.224088Z",
+                "status": "starting",
+                "input": {"text": "Alice"},
+                "output": None::<String>,
+                "error": None::<String>,
+                "logs": None::<String>,
+            }));
+        });
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: server.base_url(),
+            ..Config::default()
+        };
+        let replicate = Replicate::new(config);
+
+        let mut input = HashMap::new();
+        input.insert("text", "Alice");
+
+        let prediction =
+            replicate
+                .deployments
+                .predictions
+                .create("acme", "image-upscaler", input)?;
+        assert_eq!(prediction.id, "ufawqhfynnddngldkgtslldrkq");
+
+        // Ensure the mocks were called as expected
+        post_mock.assert();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,757 @@
+//! Used to download the files in the output of a prediction, e.g. the images or audio generated by a model.
+//!
+//! The files are streamed to disk, named after the last segment of their url. They are first written to a `.part` file,
+//! renamed once their size was checked against the `Content-Length` of the response. The url and size of a download in progress
+//! are recorded in a `.source` file next to it, removed once the download completes, so that an interrupted download is only
+//! resumed with a `Range` request when it comes from the same url. Otherwise, the file is downloaded again.
+//! Outputs inlined as `data:` URIs are decoded instead of downloaded.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use replicate_rust::{Replicate, config::Config, download::DownloadOptions};
+//!
+//! let config = Config::default();
+//! let replicate = Replicate::new(config);
+//!
+//! let prediction = replicate.predictions.get("rrr4z55ocneqzikepnug6xezpe")?;
+//!
+//! let paths = replicate.predictions.download_outputs(
+//!     &prediction,
+//!     "outputs",
+//!     &DownloadOptions {
+//!         concurrency: 8,
+//!         ..Default::default()
+//!     },
+//! )?;
+//! println!("Downloaded : {:?}", paths);
+//!
+//! # Ok::<(), replicate_rust::errors::ReplicateError>(())
+//! ```
+
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+use base64::Engine;
+use reqwest::{
+    header::{HeaderMap, HeaderValue, CONTENT_LENGTH, CONTENT_RANGE, RANGE, USER_AGENT},
+    Method, StatusCode,
+};
+use serde_json::Value;
+
+use crate::{
+    api_definitions::GetPrediction, config::Config, errors::ReplicateError, transport::Request,
+};
+
+/// Options for downloading the files in the output of a prediction.
+#[derive(Clone, Debug)]
+pub struct DownloadOptions {
+    /// Maximum number of files downloaded at the same time. Defaults to 4.
+    pub concurrency: usize,
+
+    /// Resume the downloads interrupted by a previous call, when they come from the same url. Defaults to `true`.
+    /// Otherwise, every file is downloaded from the start.
+    pub resume: bool,
+}
+
+impl Default for DownloadOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            resume: true,
+        }
+    }
+}
+
+/// A file in the output of a prediction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutputFile {
+    /// Url of the file, either an `http(s)` url or a `data:` URI.
+    pub url: String,
+
+    /// Name of the file when downloaded to a directory, derived from the url.
+    pub file_name: String,
+}
+
+impl GetPrediction {
+    /// Files in the output of the prediction, in order: every `http(s)` url or `data:` URI, even when nested in lists or objects.
+    ///
+    /// File names are taken from the last segment of the urls, made unique by prefixing duplicates with their position.
+    pub fn output_files(&self) -> Vec<OutputFile> {
+        let mut urls = Vec::new();
+        if let Some(output) = &self.output {
+            collect_urls(output, &mut urls);
+        }
+
+        let mut files: Vec<OutputFile> = Vec::new();
+        for (index, url) in urls.into_iter().enumerate() {
+            let mut file_name = file_name(&url, index);
+            if files.iter().any(|file| file.file_name == file_name) {
+                file_name = format!("{}-{}", index, file_name);
+            }
+
+            files.push(OutputFile { url, file_name });
+        }
+
+        files
+    }
+}
+
+/// Collect the file urls in an output.
+fn collect_urls(value: &Value, urls: &mut Vec<String>) {
+    match value {
+        Value::String(value)
+            if value.starts_with("https://")
+                || value.starts_with("http://")
+                || value.starts_with("data:") =>
+        {
+            urls.push(value.clone())
+        }
+        Value::Array(values) => values.iter().for_each(|value| collect_urls(value, urls)),
+        Value::Object(object) => object.values().for_each(|value| collect_urls(value, urls)),
+        _ => {}
+    }
+}
+
+/// Name of the file of an url: the last segment of its path, or `output-{index}` with an extension guessed from the MIME type of `data:` URIs.
+fn file_name(url: &str, index: usize) -> String {
+    if let Some((mime_type, _)) = url
+        .strip_prefix("data:")
+        .and_then(|data| data.split_once(','))
+    {
+        let mime_type = mime_type.split(';').next().unwrap_or_default();
+        return format!("output-{}.{}", index, extension(mime_type));
+    }
+
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| {
+            url.path_segments()?
+                .next_back()
+                .filter(|name| !name.is_empty() && *name != "." && *name != "..")
+                .map(String::from)
+        })
+        .unwrap_or_else(|| format!("output-{}", index))
+}
+
+/// Extension of the files of a MIME type.
+pub(crate) fn extension(mime_type: &str) -> &'static str {
+    match mime_type {
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        "image/webp" => "webp",
+        "image/gif" => "gif",
+        "image/svg+xml" => "svg",
+        "audio/wav" | "audio/x-wav" => "wav",
+        "audio/mpeg" => "mp3",
+        "audio/ogg" => "ogg",
+        "audio/flac" => "flac",
+        "video/mp4" => "mp4",
+        "video/webm" => "webm",
+        "text/plain" => "txt",
+        "application/json" => "json",
+        "application/zip" => "zip",
+        _ => "bin",
+    }
+}
+
+/// Decode the data of a `data:` URI.
+pub(crate) fn decode_data_uri(uri: &str) -> Result<Vec<u8>, ReplicateError> {
+    let invalid = || ReplicateError::DownloadError(format!("invalid data URI {:.64}", uri));
+
+    let (metadata, data) = uri
+        .strip_prefix("data:")
+        .and_then(|data| data.split_once(','))
+        .ok_or_else(invalid)?;
+
+    if metadata.ends_with(";base64") {
+        return base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .map_err(|_| invalid());
+    }
+
+    // Percent encoded data.
+    let mut bytes = Vec::with_capacity(data.len());
+    let mut chars = data.bytes();
+    while let Some(byte) = chars.next() {
+        match byte {
+            b'%' => {
+                let hex = [
+                    chars.next().ok_or_else(invalid)?,
+                    chars.next().ok_or_else(invalid)?,
+                ];
+                let hex = std::str::from_utf8(&hex).map_err(|_| invalid())?;
+                bytes.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+            }
+            byte => bytes.push(byte),
+        }
+    }
+
+    Ok(bytes)
+}
+
+/// Request to download a file. The API token is not sent, as the files are served by another host.
+pub(crate) fn file_request(config: &Config, url: &str, offset: u64) -> Request {
+    let mut request = Request::new(Method::GET, url);
+
+    if let Ok(value) = HeaderValue::from_str(&config.user_agent) {
+        request.headers.insert(USER_AGENT, value);
+    }
+    if offset > 0 {
+        if let Ok(value) = HeaderValue::from_str(&format!("bytes={}-", offset)) {
+            request.headers.insert(RANGE, value);
+        }
+    }
+
+    request
+}
+
+/// Total size of the file, from the `Content-Range` header of a partial response, or else from the `Content-Length` header.
+pub(crate) fn expected_size(headers: &HeaderMap, start: u64) -> Option<u64> {
+    let header = |name| headers.get(name)?.to_str().ok();
+
+    header(CONTENT_RANGE)
+        .and_then(|range| range.rsplit_once('/')?.1.parse().ok())
+        .or_else(|| Some(start + header(CONTENT_LENGTH)?.parse::<u64>().ok()?))
+}
+
+/// Check the size of a downloaded file.
+pub(crate) fn check_size(
+    url: &str,
+    expected: Option<u64>,
+    size: u64,
+) -> Result<(), ReplicateError> {
+    match expected {
+        Some(expected) if expected != size => Err(ReplicateError::DownloadError(format!(
+            "{} is {} bytes long, but {} bytes were received",
+            url, expected, size
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Path of the partial download of a file.
+pub(crate) fn part_path(path: &Path) -> PathBuf {
+    let mut part = path.as_os_str().to_owned();
+    part.push(".part");
+    PathBuf::from(part)
+}
+
+/// Path of the record of the source of a download, see [`SourceRecord`].
+fn source_path(path: &Path) -> PathBuf {
+    let mut source = path.as_os_str().to_owned();
+    source.push(".source");
+    PathBuf::from(source)
+}
+
+/// Url and total size of a download in progress, written next to the file so that it is only resumed for the same url.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct SourceRecord {
+    url: String,
+    size: Option<u64>,
+}
+
+impl SourceRecord {
+    /// Parse a record, written as the url and the size on two lines.
+    fn parse(contents: &str) -> Option<Self> {
+        let mut lines = contents.lines();
+        let url = lines.next()?.to_string();
+        let size = lines.next().and_then(|size| size.parse().ok());
+
+        Some(Self { url, size })
+    }
+
+    /// Size of the downloaded file or partial download, if it comes from the url.
+    fn size_for(contents: Option<String>, url: &str) -> Option<u64> {
+        Self::parse(&contents?)
+            .filter(|record| record.url == url)?
+            .size
+    }
+}
+
+impl std::fmt::Display for SourceRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.size {
+            Some(size) => writeln!(f, "{}\n{}", self.url, size),
+            None => writeln!(f, "{}", self.url),
+        }
+    }
+}
+
+/// Download a file to a directory, resuming a partial download of the same url if enabled.
+fn download_file(
+    config: &Config,
+    file: &OutputFile,
+    dir: &Path,
+    resume: bool,
+) -> Result<PathBuf, ReplicateError> {
+    let path = dir.join(&file.file_name);
+    if file.url.starts_with("data:") {
+        fs::write(&path, decode_data_uri(&file.url)?)?;
+        return Ok(path);
+    }
+
+    let part = part_path(&path);
+    let source = source_path(&path);
+
+    // Only the data known to come from the same url is reused.
+    let recorded_size = match resume {
+        true => SourceRecord::size_for(fs::read_to_string(&source).ok(), &file.url),
+        false => None,
+    };
+    let offset = match recorded_size {
+        Some(size) => fs::metadata(&part)
+            .map(|metadata| metadata.len())
+            .ok()
+            .filter(|offset| *offset <= size)
+            .unwrap_or(0),
+        None => 0,
+    };
+
+    let mut response = match config.send_streaming(file_request(config, &file.url, offset)) {
+        // The partial download may already hold the whole file.
+        Err(error) if offset > 0 && error.status() == Some(StatusCode::RANGE_NOT_SATISFIABLE) => {
+            if Some(offset) == recorded_size {
+                fs::rename(&part, &path)?;
+                fs::remove_file(&source)?;
+                return Ok(path);
+            }
+            config.send_streaming(file_request(config, &file.url, 0))?
+        }
+        response => response?,
+    };
+
+    let start = match response.status {
+        StatusCode::PARTIAL_CONTENT => offset,
+        _ => 0,
+    };
+    let record = SourceRecord {
+        url: file.url.clone(),
+        size: expected_size(&response.headers, start),
+    };
+    fs::write(&source, record.to_string())?;
+    let output = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(start > 0)
+        .truncate(start == 0)
+        .open(&part)?;
+
+    let mut writer = BufWriter::new(output);
+    let written = io::copy(&mut response.body, &mut writer)?;
+    writer.flush()?;
+
+    check_size(&file.url, record.size, start + written)?;
+    fs::rename(&part, &path)?;
+    fs::remove_file(&source)?;
+
+    Ok(path)
+}
+
+/// Download files to a directory, with at most `options.concurrency` downloads at the same time.
+pub(crate) fn download_files(
+    config: &Config,
+    files: &[OutputFile],
+    dir: &Path,
+    options: &DownloadOptions,
+) -> Result<Vec<PathBuf>, ReplicateError> {
+    fs::create_dir_all(dir)?;
+
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::new());
+
+    std::thread::scope(|scope| {
+        for _ in 0..options.concurrency.clamp(1, files.len().max(1)) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(file) = files.get(index) else {
+                    break;
+                };
+
+                let result = download_file(config, file, dir, options.resume);
+                if let Ok(mut results) = results.lock() {
+                    results.push((index, result));
+                }
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap_or_default();
+    results.sort_by_key(|(index, _)| *index);
+
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Download a single file into a writer, returning the number of bytes written.
+pub(crate) fn download_to_writer(
+    config: &Config,
+    url: &str,
+    writer: &mut impl Write,
+) -> Result<u64, ReplicateError> {
+    if url.starts_with("data:") {
+        let data = decode_data_uri(url)?;
+        writer.write_all(&data)?;
+        return Ok(data.len() as u64);
+    }
+
+    let mut response = config.send_streaming(file_request(config, url, 0))?;
+    let written = io::copy(&mut response.body, writer)?;
+    check_size(url, expected_size(&response.headers, 0), written)?;
+
+    Ok(written)
+}
+
+/// Async counterpart of [`download_file`].
+#[cfg(feature = "async")]
+async fn async_download_file(
+    config: &Config,
+    file: &OutputFile,
+    dir: &Path,
+    resume: bool,
+) -> Result<PathBuf, ReplicateError> {
+    use tokio::{fs, io::AsyncWriteExt};
+
+    let path = dir.join(&file.file_name);
+    if file.url.starts_with("data:") {
+        fs::write(&path, decode_data_uri(&file.url)?).await?;
+        return Ok(path);
+    }
+
+    let part = part_path(&path);
+    let source = source_path(&path);
+
+    // Only the data known to come from the same url is reused.
+    let recorded_size = match resume {
+        true => SourceRecord::size_for(fs::read_to_string(&source).await.ok(), &file.url),
+        false => None,
+    };
+    let offset = match recorded_size {
+        Some(size) => fs::metadata(&part)
+            .await
+            .map(|metadata| metadata.len())
+            .ok()
+            .filter(|offset| *offset <= size)
+            .unwrap_or(0),
+        None => 0,
+    };
+
+    let mut response = match config
+        .async_send_streaming(file_request(config, &file.url, offset))
+        .await
+    {
+        // The partial download may already hold the whole file.
+        Err(error) if offset > 0 && error.status() == Some(StatusCode::RANGE_NOT_SATISFIABLE) => {
+            if Some(offset) == recorded_size {
+                fs::rename(&part, &path).await?;
+                fs::remove_file(&source).await?;
+                return Ok(path);
+            }
+            config
+                .async_send_streaming(file_request(config, &file.url, 0))
+                .await?
+        }
+        response => response?,
+    };
+
+    let start = match response.status {
+        StatusCode::PARTIAL_CONTENT => offset,
+        _ => 0,
+    };
+    let record = SourceRecord {
+        url: file.url.clone(),
+        size: expected_size(&response.headers, start),
+    };
+    fs::write(&source, record.to_string()).await?;
+    let output = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(start > 0)
+        .truncate(start == 0)
+        .open(&part)
+        .await?;
+
+    let mut writer = tokio::io::BufWriter::new(output);
+    let written = async_copy(&mut response.body, &mut writer).await?;
+    writer.flush().await?;
+
+    check_size(&file.url, record.size, start + written)?;
+    fs::rename(&part, &path).await?;
+    fs::remove_file(&source).await?;
+
+    Ok(path)
+}
+
+/// Write a streamed body into a writer, returning the number of bytes written. Async counterpart of [`io::copy`].
+#[cfg(feature = "async")]
+pub(crate) async fn async_copy(
+    body: &mut crate::transport::ByteStream,
+    writer: &mut (impl tokio::io::AsyncWrite + Unpin),
+) -> Result<u64, ReplicateError> {
+    use futures_util::StreamExt;
+    use tokio::io::AsyncWriteExt;
+
+    let mut written = 0;
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk?;
+        writer.write_all(&chunk).await?;
+        written += chunk.len() as u64;
+    }
+
+    Ok(written)
+}
+
+/// Async counterpart of [`download_files`].
+#[cfg(feature = "async")]
+pub(crate) async fn async_download_files(
+    config: &Config,
+    files: &[OutputFile],
+    dir: &Path,
+    options: &DownloadOptions,
+) -> Result<Vec<PathBuf>, ReplicateError> {
+    use futures_util::StreamExt;
+
+    tokio::fs::create_dir_all(dir).await?;
+
+    futures_util::stream::iter(files)
+        .map(|file| async_download_file(config, file, dir, options.resume))
+        .buffered(options.concurrency.max(1))
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect()
+}
+
+/// Async counterpart of [`download_to_writer`].
+#[cfg(feature = "async")]
+pub(crate) async fn async_download_to_writer(
+    config: &Config,
+    url: &str,
+    writer: &mut (impl tokio::io::AsyncWrite + Unpin),
+) -> Result<u64, ReplicateError> {
+    use tokio::io::AsyncWriteExt;
+
+    if url.starts_with("data:") {
+        let data = decode_data_uri(url)?;
+        writer.write_all(&data).await?;
+        return Ok(data.len() as u64);
+    }
+
+    let mut response = config
+        .async_send_streaming(file_request(config, url, 0))
+        .await?;
+
+    let written = async_copy(&mut response.body, writer).await?;
+    check_size(url, expected_size(&response.headers, 0), written)?;
+
+    Ok(written)
+}
+
+/// The single file in the output of a prediction.
+pub(crate) fn single_file(prediction: &GetPrediction) -> Result<OutputFile, ReplicateError> {
+    let mut files = prediction.output_files();
+
+    match files.len() {
+        1 => Ok(files.remove(0)),
+        count => Err(ReplicateError::UnexpectedOutput(format!(
+            "expected a single file in the output of prediction {}, found {}",
+            prediction.id, count
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Replicate;
+    use httpmock::{Method::GET, MockServer};
+    use serde_json::json;
+
+    fn prediction(output: Value) -> GetPrediction {
+        serde_json::from_value(json!({
+            "id": "rrr4z55ocneqzikepnug6xezpe",
+            "version": "be04660a5b93ef2aff61e3668dedb4cbeb14941e62a3fd5998364a32d613e35e",
+            "urls": {
+                "get": "https://api.replicate.com/v1/predictions/rrr4z55ocneqzikepnug6xezpe",
+                "cancel": "https://api.replicate.com/v1/predictions/rrr4z55ocneqzikepnug6xezpe/cancel",
+            },
+            "created_at": "2022-09-13T22:54:18.578761Z",
+            "started_at": "2022-09-13T22:54:19.438525Z",
+            "completed_at": "2022-09-13T22:54:23.236610Z",
+            "source": "api",
+            "status": "succeeded",
+            "input": {},
+            "output": output,
+            "error": None::<String>,
+            "logs": None::<String>,
+            "metrics": None::<Value>,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_output_files() {
+        let files = prediction(json!([
+            "https://replicate.delivery/pbxt/a/out-0.png",
+            "https://replicate.delivery/pbxt/b/out-0.png",
+            {"audio": "data:audio/wav;base64,UklGRg=="},
+            "not a file",
+        ]))
+        .output_files();
+
+        let names: Vec<_> = files.iter().map(|file| file.file_name.as_str()).collect();
+        assert_eq!(names, vec!["out-0.png", "1-out-0.png", "output-2.wav"]);
+    }
+
+    #[test]
+    fn test_data_uri() -> Result<(), ReplicateError> {
+        assert_eq!(
+            decode_data_uri("data:text/plain;base64,aGVsbG8=")?,
+            b"hello"
+        );
+        assert_eq!(decode_data_uri("data:,hello%20Alice")?, b"hello Alice");
+        assert!(decode_data_uri("data:text/plain;base64,!!").is_err());
+
+        Ok(())
+    }
+
+    fn has_range(request: &httpmock::prelude::HttpMockRequest) -> bool {
+        request
+            .headers
+            .iter()
+            .flatten()
+            .any(|(name, _)| name.eq_ignore_ascii_case("range"))
+    }
+
+    #[test]
+    fn test_download() -> Result<(), ReplicateError> {
+        let server = MockServer::start();
+        let dir = std::env::temp_dir().join(format!("replicate-download-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir)?;
+
+        // A previous download of the same url was interrupted after 5 bytes.
+        fs::write(dir.join("out-0.png.part"), b"hello")?;
+        let record = SourceRecord {
+            url: server.url("/files/out-0.png"),
+            size: Some(11),
+        };
+        fs::write(dir.join("out-0.png.source"), record.to_string())?;
+
+        // Files of another prediction, with the same names, are not reused.
+        fs::write(dir.join("out-1.png"), b"hello Eve")?;
+        fs::write(dir.join("out-1.png.part"), b"hello")?;
+
+        let resume_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/files/out-0.png")
+                .header("range", "bytes=5-");
+            then.status(206)
+                .header("content-range", "bytes 5-10/11")
+                .body(" Alice");
+        });
+        let full_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/files/out-1.png")
+                .matches(|request| !has_range(request));
+            then.status(200).body("hello Bob");
+        });
+        let changed_range_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/files/changed.png")
+                .header("range", "bytes=5-");
+            then.status(416);
+        });
+        let changed_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/files/changed.png")
+                .matches(|request| !has_range(request));
+            then.status(200).body("hi");
+        });
+        let short_mock = server.mock(|when, then| {
+            when.method(GET).path("/files/short.png");
+            then.status(206)
+                .header("content-range", "bytes 0-2/100")
+                .body("abc");
+        });
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: server.base_url(),
+            ..Config::default()
+        };
+        let replicate = Replicate::new(config);
+
+        let output = prediction(json!([
+            server.url("/files/out-0.png"),
+            server.url("/files/out-1.png"),
+            "data:text/plain;base64,aGVsbG8=",
+        ]));
+        let paths =
+            replicate
+                .predictions
+                .download_outputs(&output, &dir, &DownloadOptions::default())?;
+
+        assert_eq!(
+            paths,
+            vec![
+                dir.join("out-0.png"),
+                dir.join("out-1.png"),
+                dir.join("output-2.txt")
+            ]
+        );
+        assert_eq!(fs::read(dir.join("out-0.png"))?, b"hello Alice");
+        assert_eq!(fs::read(dir.join("out-1.png"))?, b"hello Bob");
+        assert_eq!(fs::read(dir.join("output-2.txt"))?, b"hello");
+        assert!(!dir.join("out-0.png.part").exists());
+
+        // No state is left next to the downloaded files.
+        assert!(!dir.join("out-0.png.source").exists());
+        assert!(!dir.join("out-1.png.source").exists());
+        resume_mock.assert_hits(1);
+        full_mock.assert_hits(1);
+
+        // A partial download rejected by the server, without reaching the recorded size, is downloaded again.
+        fs::write(dir.join("changed.png.part"), b"hello")?;
+        let record = SourceRecord {
+            url: server.url("/files/changed.png"),
+            size: Some(20),
+        };
+        fs::write(dir.join("changed.png.source"), record.to_string())?;
+        let output = prediction(json!(server.url("/files/changed.png")));
+        replicate
+            .predictions
+            .download_outputs(&output, &dir, &DownloadOptions::default())?;
+        assert_eq!(fs::read(dir.join("changed.png"))?, b"hi");
+        assert!(!dir.join("changed.png.source").exists());
+        changed_range_mock.assert_hits(1);
+        changed_mock.assert_hits(1);
+
+        // Truncated downloads fail.
+        let output = prediction(json!(server.url("/files/short.png")));
+        assert!(matches!(
+            replicate
+                .predictions
+                .download_outputs(&output, &dir, &DownloadOptions::default()),
+            Err(ReplicateError::DownloadError(_))
+        ));
+        short_mock.assert_hits(1);
+
+        let mut buffer = Vec::new();
+        let output = prediction(json!(server.url("/files/out-1.png")));
+        replicate
+            .predictions
+            .download_output_to(&output, &mut buffer)?;
+        assert_eq!(buffer, b"hello Bob");
+
+        fs::remove_dir_all(&dir)?;
+
+        Ok(())
+    }
+}
@@ -1,7 +1,17 @@
 //! Custom errors for the crate.
 
+use std::{fmt, time::Duration};
+
+use reqwest::{header::HeaderMap, Method, StatusCode};
+use serde::Deserialize;
 use thiserror::Error;
 
+use crate::{
+    api_definitions::PredictionStatus,
+    schema::InputErrors,
+    transport::{Request, Response},
+};
+
 /// Errors related to sending requests to the API.
 #[derive(Error, Debug)]
 pub enum ReplicateError {
@@ -9,15 +19,325 @@ pub enum ReplicateError {
     #[error("failed to send the api request: {0}")]
     ReqwestError(#[from] reqwest::Error),
 
-    /// Error occues when the api returns a non 200 response.
-    #[error("Received a non 200 response from the api: {0}")]
-    ResponseError(String),
+    /// Error occues when the api returns a non 2xx response.
+    #[error("Received a non 2xx response from the api: {0}")]
+    ResponseError(Box<ApiError>),
+
+    /// Error occues when reading a response or a file results in an error.
+    #[error("failed to read the data: {0}")]
+    IoError(#[from] std::io::Error),
 
     /// Error occues when parsing the api response into a struct results in an error.
     #[error("failed to parse the api response : {0}")]
     SerdeError(#[from] serde_json::Error),
 
+    /// Error occues when no API token is set in the config.
+    #[error("No API token provided. Set the REPLICATE_API_TOKEN environment variable or create a client with `Config::builder().auth(\"REPLICATE_API_TOKEN\")`. You can find your API token on https://replicate.com/account")]
+    MissingCredentials,
+
+    /// Error occues when the API rejects the API token.
+    #[error("Invalid API token: {0}")]
+    InvalidCredentials(Box<ApiError>),
+
+    /// Error occues when a prediction is still running after the polling policy gave up waiting for it.
+    #[error("prediction {0} did not complete before the polling policy gave up")]
+    WaitTimeout(String),
+
+    /// Error occues when streaming the output of a prediction that was not created with streaming enabled.
+    #[error("prediction {0} was not created with streaming enabled")]
+    StreamingNotEnabled(String),
+
+    /// Error occues when a webhook is not signed by Replicate, or was sent too long ago.
+    #[error("failed to verify the webhook: {0}")]
+    InvalidWebhook(String),
+
+    /// Error occues when a prediction failed or was canceled, while its output was expected.
+    #[error("prediction {id} {status:?}: {}", error.as_deref().unwrap_or("no error message"))]
+    PredictionFailed {
+        /// Id of the prediction.
+        id: String,
+        /// Status of the prediction, either failed or canceled.
+        status: PredictionStatus,
+        /// Error message of the prediction, if any.
+        error: Option<String>,
+    },
+
+    /// Error occues when the output of a prediction does not have the expected shape.
+    #[error("unexpected prediction output: {0}")]
+    UnexpectedOutput(String),
+
+    /// Error occues when a file in the output of a prediction cannot be downloaded, e.g. when fewer bytes than announced were received.
+    #[error("failed to download the file: {0}")]
+    DownloadError(String),
+
+    /// Error occues when the OpenAPI schema of a model version cannot be parsed.
+    #[error("invalid model version schema: {0}")]
+    InvalidSchema(String),
+
+    /// Error occues when the inputs of a prediction do not match the schema of the model version, listing every invalid input.
+    #[error("invalid inputs: {0}")]
+    InvalidInput(InputErrors),
+
+    /// Error occues when the cursor of a page does not point to the API, so that the API token is never sent to another host.
+    #[error("invalid page cursor, not an url of the API: {0}")]
+    InvalidCursor(String),
+
+    /// Error occues when the content type of an uploaded file is not a valid MIME type, e.g. when it contains line breaks.
+    #[error("invalid content type, not a MIME type: {0:?}")]
+    InvalidContentType(String),
+
+    /// Error occues when a timestamp of an api response is not a valid RFC 3339 timestamp.
+    #[error("invalid timestamp, not RFC 3339: {0:?}")]
+    InvalidTimestamp(String),
+
     /// Invalid version string provided.
     #[error("Invalid version string: {0}")]
     InvalidVersionString(String),
 }
+
+impl ReplicateError {
+    /// The API error, if the API returned a non 2xx response.
+    pub fn api_error(&self) -> Option<&ApiError> {
+        match self {
+            Self::ResponseError(error) | Self::InvalidCredentials(error) => Some(error),
+            _ => None,
+        }
+    }
+
+    /// HTTP status of the response, if the API returned a non 2xx response.
+    pub fn status(&self) -> Option<StatusCode> {
+        self.api_error().map(|error| error.status)
+    }
+
+    /// Whether the error is transient and the request may be sent again, i.e. whether the client would retry it:
+    /// a retryable response (see [`ApiError::is_retryable`]), or a connection error, when the request never reached the API.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::ResponseError(error) => error.is_retryable(),
+            Self::ReqwestError(error) => error.is_connect(),
+            _ => false,
+        }
+    }
+
+    /// Whether the API answered with a `404 Not Found` response.
+    pub fn is_not_found(&self) -> bool {
+        self.api_error().is_some_and(ApiError::is_not_found)
+    }
+
+    /// Whether the API rejected the API token, with a `401 Unauthorized` or `403 Forbidden` response.
+    pub fn is_auth_error(&self) -> bool {
+        self.api_error().is_some_and(ApiError::is_auth_error)
+    }
+
+    /// Whether the API throttled the request, with a `429 Too Many Requests` response.
+    pub fn is_rate_limited(&self) -> bool {
+        self.api_error().is_some_and(ApiError::is_rate_limited)
+    }
+
+    /// Whether the API rejected the request body, with a `422 Unprocessable Entity` response.
+    pub fn is_validation_error(&self) -> bool {
+        self.api_error().is_some_and(ApiError::is_validation_error)
+    }
+}
+
+/// Problem details returned by the API along with an error, see [RFC 7807](https://www.rfc-editor.org/rfc/rfc7807).
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct ProblemDetails {
+    /// Short summary of the problem, e.g. `Input validation failed`.
+    pub title: Option<String>,
+
+    /// Explanation specific to this occurrence of the problem.
+    pub detail: Option<String>,
+
+    /// HTTP status code of the problem.
+    pub status: Option<u16>,
+}
+
+/// Rate limit information sent by the API in the response headers.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Maximum number of requests allowed in the current window.
+    pub limit: Option<u64>,
+
+    /// Number of requests left in the current window.
+    pub remaining: Option<u64>,
+
+    /// Number of seconds until the current window resets.
+    pub reset: Option<u64>,
+
+    /// Delay to wait before sending the request again, from the `Retry-After` header.
+    pub retry_after: Option<Duration>,
+}
+
+impl RateLimit {
+    /// Read the rate limit headers, either with or without the `x-` prefix.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let number = |name: &str| {
+            headers
+                .get(name)
+                .or_else(|| headers.get(format!("x-{}", name)))
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse().ok())
+        };
+
+        Self {
+            limit: number("ratelimit-limit"),
+            remaining: number("ratelimit-remaining"),
+            reset: number("ratelimit-reset"),
+            retry_after: crate::retry::retry_after(headers),
+        }
+    }
+}
+
+/// Non 2xx response returned by the API.
+#[derive(Clone, Debug)]
+pub struct ApiError {
+    /// HTTP status of the response.
+    pub status: StatusCode,
+
+    /// HTTP method of the request.
+    pub method: Method,
+
+    /// Url of the request.
+    pub url: String,
+
+    /// Problem details parsed from the response body, if it contained any.
+    pub problem: Option<ProblemDetails>,
+
+    /// Rate limit information from the response headers.
+    pub rate_limit: RateLimit,
+
+    /// Raw body of the response.
+    pub body: String,
+}
+
+impl ApiError {
+    /// Create a new ApiError from the response to a request.
+    pub fn new(request: &Request, response: &Response) -> Self {
+        let problem = response
+            .json::<ProblemDetails>()
+            .ok()
+            .filter(|problem| problem.title.is_some() || problem.detail.is_some());
+
+        Self {
+            status: response.status,
+            method: request.method.clone(),
+            url: request.url.clone(),
+            problem,
+            rate_limit: RateLimit::from_headers(&response.headers),
+            body: response.text(),
+        }
+    }
+
+    /// Title of the problem, if the API returned one.
+    pub fn title(&self) -> Option<&str> {
+        self.problem.as_ref()?.title.as_deref()
+    }
+
+    /// Detail of the problem, if the API returned one.
+    pub fn detail(&self) -> Option<&str> {
+        self.problem.as_ref()?.detail.as_deref()
+    }
+
+    /// Whether the request may succeed if sent again: a `429 Too Many Requests` response,
+    /// or a `5xx` response except `501 Not Implemented` to a request with an idempotent method, so that nothing is created twice.
+    pub fn is_retryable(&self) -> bool {
+        self.is_rate_limited()
+            || (self.status.is_server_error()
+                && self.status != StatusCode::NOT_IMPLEMENTED
+                && crate::retry::is_idempotent(&self.method))
+    }
+
+    /// Whether the response is a `404 Not Found`.
+    pub fn is_not_found(&self) -> bool {
+        self.status == StatusCode::NOT_FOUND
+    }
+
+    /// Whether the response is a `401 Unauthorized` or `403 Forbidden`.
+    pub fn is_auth_error(&self) -> bool {
+        self.status == StatusCode::UNAUTHORIZED || self.status == StatusCode::FORBIDDEN
+    }
+
+    /// Whether the response is a `429 Too Many Requests`.
+    pub fn is_rate_limited(&self) -> bool {
+        self.status == StatusCode::TOO_MANY_REQUESTS
+    }
+
+    /// Whether the response is a `422 Unprocessable Entity`.
+    pub fn is_validation_error(&self) -> bool {
+        self.status == StatusCode::UNPROCESSABLE_ENTITY
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} returned {}", self.method, self.url, self.status)?;
+
+        match (self.title(), self.detail()) {
+            (Some(title), Some(detail)) => write!(f, ": {}: {}", title, detail),
+            (Some(message), None) | (None, Some(message)) => write!(f, ": {}", message),
+            (None, None) if !self.body.is_empty() => write!(f, ": {}", self.body),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_problem_details() {
+        let mut response = Response::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            r#"{"title": "Input validation failed", "detail": "- input.prompt: Invalid type. Expected: string, given: integer", "status": 422}"#,
+        );
+        response
+            .headers
+            .insert("ratelimit-remaining", HeaderValue::from_static("0"));
+        response
+            .headers
+            .insert("x-ratelimit-limit", HeaderValue::from_static("600"));
+        response
+            .headers
+            .insert("retry-after", HeaderValue::from_static("2"));
+
+        let request = Request::new(Method::POST, "https://api.replicate.com/v1/predictions");
+        let error = ApiError::new(&request, &response);
+
+        assert_eq!(error.title(), Some("Input validation failed"));
+        assert_eq!(error.problem.as_ref().unwrap().status, Some(422));
+        assert_eq!(error.rate_limit.limit, Some(600));
+        assert_eq!(error.rate_limit.remaining, Some(0));
+        assert_eq!(error.rate_limit.retry_after, Some(Duration::from_secs(2)));
+        assert_eq!(
+            error.to_string(),
+            "POST https://api.replicate.com/v1/predictions returned 422 Unprocessable Entity: Input validation failed: - input.prompt: Invalid type. Expected: string, given: integer"
+        );
+
+        let error = ReplicateError::ResponseError(Box::new(error));
+        assert!(error.is_validation_error());
+        assert!(!error.is_retryable());
+        assert!(!error.is_not_found());
+    }
+
+    #[test]
+    fn test_plain_body() {
+        let response = Response::new(StatusCode::SERVICE_UNAVAILABLE, "upstream timeout");
+        let request = Request::new(Method::GET, "https://api.replicate.com/v1/models");
+        let error = ApiError::new(&request, &response);
+
+        assert_eq!(error.problem, None);
+        assert_eq!(
+            error.to_string(),
+            "GET https://api.replicate.com/v1/models returned 503 Service Unavailable: upstream timeout"
+        );
+
+        let error = ReplicateError::ResponseError(Box::new(error));
+        assert!(error.is_retryable());
+        assert_eq!(error.status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+    }
+}
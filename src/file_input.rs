@@ -0,0 +1,325 @@
+//! Used to pass local files as inputs of a prediction.
+//!
+//! A [`FileInput`] reads a file from a path, bytes or a reader. Files up to [`DATA_URI_MAX_SIZE`] bytes are sent inline
+//! as `data:` URIs, larger ones are uploaded through the [Files API](crate::files)
+//! and replaced by the url of the upload. The MIME type is detected from the file name, or else from the first bytes of the file.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use replicate_rust::{Replicate, config::Config, file_input::FileInput};
+//!
+//! let config = Config::default();
+//! let replicate = Replicate::new(config);
+//!
+//! let mut inputs = std::collections::HashMap::new();
+//! inputs.insert("scale", 4);
+//!
+//! let mut files = std::collections::HashMap::new();
+//! files.insert("image", FileInput::path("wombat.png"));
+//!
+//! let prediction = replicate.predictions.create_with_files(
+//!     "nightmareai/real-esrgan:42fed1c4974146d4d2414e2be2c5277c7fcf05fcc3a73abf41610695738c1d7b",
+//!     inputs,
+//!     files,
+//! )?;
+//! # Ok::<(), replicate_rust::errors::ReplicateError>(())
+//! ```
+
+use std::{
+    fmt,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use base64::Engine;
+
+use crate::{api_definitions::FileObject, config::Config, errors::ReplicateError, files};
+
+/// Largest file sent inline as a `data:` URI, in bytes. Larger files are uploaded through the Files API.
+pub const DATA_URI_MAX_SIZE: usize = 1024 * 1024;
+
+/// Where the content of a [`FileInput`] is read from.
+enum Source {
+    Path(PathBuf),
+    Bytes(Vec<u8>),
+    Reader(Box<dyn Read + Send>),
+}
+
+/// A local file passed as an input of a prediction, see [`Prediction::create_with_files`](crate::prediction::Prediction::create_with_files).
+pub struct FileInput {
+    source: Source,
+    file_name: Option<String>,
+    content_type: Option<String>,
+}
+
+impl fmt::Debug for FileInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let source = match &self.source {
+            Source::Path(path) => format!("Path({:?})", path),
+            Source::Bytes(bytes) => format!("Bytes({} bytes)", bytes.len()),
+            Source::Reader(_) => String::from("Reader"),
+        };
+
+        f.debug_struct("FileInput")
+            .field("source", &source)
+            .field("file_name", &self.file_name)
+            .field("content_type", &self.content_type)
+            .finish()
+    }
+}
+
+impl FileInput {
+    /// A file read from a path. Its name is the last component of the path.
+    pub fn path(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+
+        Self {
+            file_name: path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned()),
+            source: Source::Path(path.to_path_buf()),
+            content_type: None,
+        }
+    }
+
+    /// A file holding the given bytes.
+    pub fn bytes(bytes: impl Into<Vec<u8>>) -> Self {
+        Self {
+            source: Source::Bytes(bytes.into()),
+            file_name: None,
+            content_type: None,
+        }
+    }
+
+    /// A file read from a reader, e.g. the body of a response. The reader is read to the end when the prediction is created,
+    /// on the blocking thread pool of the runtime with the [async client](crate::nonblocking), so it may block.
+    pub fn reader(reader: impl Read + Send + 'static) -> Self {
+        Self {
+            source: Source::Reader(Box::new(reader)),
+            file_name: None,
+            content_type: None,
+        }
+    }
+
+    /// Set the name of the file, used to detect its MIME type and to name the upload.
+    pub fn file_name(mut self, file_name: impl Into<String>) -> Self {
+        self.file_name = Some(file_name.into());
+        self
+    }
+
+    /// Set the MIME type of the file, instead of detecting it.
+    pub fn content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_type = Some(content_type.into());
+        self
+    }
+
+    /// Read the content of the file.
+    pub(crate) fn read(self) -> Result<LoadedFile, ReplicateError> {
+        let bytes = match self.source {
+            Source::Path(path) => std::fs::read(path)?,
+            Source::Bytes(bytes) => bytes,
+            Source::Reader(mut reader) => {
+                let mut bytes = Vec::new();
+                reader.read_to_end(&mut bytes)?;
+                bytes
+            }
+        };
+
+        Ok(LoadedFile::new(self.file_name, self.content_type, bytes))
+    }
+
+    /// Async counterpart of [`read`](Self::read), reading paths and readers without blocking the async runtime.
+    /// Readers are read on the blocking thread pool of the runtime.
+    #[cfg(feature = "async")]
+    pub(crate) async fn async_read(self) -> Result<LoadedFile, ReplicateError> {
+        let bytes = match self.source {
+            Source::Path(path) => tokio::fs::read(path).await?,
+            Source::Bytes(bytes) => bytes,
+            Source::Reader(mut reader) => {
+                let read = tokio::task::spawn_blocking(move || {
+                    let mut bytes = Vec::new();
+                    reader.read_to_end(&mut bytes).map(|_| bytes)
+                });
+                read.await.map_err(std::io::Error::other)??
+            }
+        };
+
+        Ok(LoadedFile::new(self.file_name, self.content_type, bytes))
+    }
+
+    /// Turn the file into an input value: a `data:` URI for small files, or the url of the upload for large ones.
+    pub(crate) fn resolve(self, config: &Config) -> Result<String, ReplicateError> {
+        let file = self.read()?;
+
+        match file.bytes.len() <= DATA_URI_MAX_SIZE {
+            true => Ok(file.data_uri()),
+            false => {
+                let upload: FileObject =
+                    config.send_json(files::create_request(config, &file, None)?)?;
+                Ok(upload.urls.get)
+            }
+        }
+    }
+
+    /// Async counterpart of [`resolve`](Self::resolve).
+    #[cfg(feature = "async")]
+    pub(crate) async fn async_resolve(self, config: &Config) -> Result<String, ReplicateError> {
+        let file = self.async_read().await?;
+
+        match file.bytes.len() <= DATA_URI_MAX_SIZE {
+            true => Ok(file.data_uri()),
+            false => {
+                let upload: FileObject = config
+                    .async_send_json(files::create_request(config, &file, None)?)
+                    .await?;
+                Ok(upload.urls.get)
+            }
+        }
+    }
+}
+
+/// Content of a [`FileInput`], along with its name and MIME type.
+pub(crate) struct LoadedFile {
+    pub(crate) file_name: String,
+    pub(crate) content_type: String,
+    pub(crate) bytes: Vec<u8>,
+}
+
+impl LoadedFile {
+    fn new(file_name: Option<String>, content_type: Option<String>, bytes: Vec<u8>) -> Self {
+        let content_type = content_type
+            .or_else(|| file_name.as_deref().and_then(mime_type_from_name))
+            .unwrap_or_else(|| String::from(mime_type_from_bytes(&bytes)));
+
+        Self {
+            file_name: file_name.unwrap_or_else(|| String::from("file")),
+            content_type,
+            bytes,
+        }
+    }
+
+    /// Encode the file as a `data:` URI.
+    fn data_uri(&self) -> String {
+        format!(
+            "data:{};base64,{}",
+            self.content_type,
+            base64::engine::general_purpose::STANDARD.encode(&self.bytes)
+        )
+    }
+}
+
+/// MIME type of a file, from the extension of its name.
+pub(crate) fn mime_type_from_name(file_name: &str) -> Option<String> {
+    let (_, extension) = file_name.rsplit_once('.')?;
+
+    let mime_type = match extension.to_ascii_lowercase().as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "webp" => "image/webp",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "wav" => "audio/wav",
+        "mp3" => "audio/mpeg",
+        "ogg" => "audio/ogg",
+        "flac" => "audio/flac",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "txt" => "text/plain",
+        "json" => "application/json",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "tar" => "application/x-tar",
+        _ => return None,
+    };
+
+    Some(String::from(mime_type))
+}
+
+/// MIME type of a file, from its first bytes. Defaults to `application/octet-stream`.
+pub(crate) fn mime_type_from_bytes(bytes: &[u8]) -> &'static str {
+    match bytes {
+        [0x89, b'P', b'N', b'G', ..] => "image/png",
+        [0xFF, 0xD8, 0xFF, ..] => "image/jpeg",
+        [b'G', b'I', b'F', b'8', ..] => "image/gif",
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => "image/webp",
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'A', b'V', b'E', ..] => "audio/wav",
+        [b'I', b'D', b'3', ..] | [0xFF, 0xFB, ..] => "audio/mpeg",
+        [b'O', b'g', b'g', b'S', ..] => "audio/ogg",
+        [b'f', b'L', b'a', b'C', ..] => "audio/flac",
+        [_, _, _, _, b'f', b't', b'y', b'p', ..] => "video/mp4",
+        [b'%', b'P', b'D', b'F', ..] => "application/pdf",
+        [b'P', b'K', 0x03, 0x04, ..] => "application/zip",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::{Method::POST, MockServer};
+    use serde_json::json;
+
+    #[test]
+    fn test_mime_type() {
+        let png = FileInput::bytes(b"\x89PNG\r\n\x1a\n".to_vec())
+            .read()
+            .unwrap();
+        assert_eq!(png.content_type, "image/png");
+
+        let named = FileInput::bytes(b"RIFF".to_vec())
+            .file_name("speech.WAV")
+            .read()
+            .unwrap();
+        assert_eq!(named.content_type, "audio/wav");
+
+        let unknown = FileInput::reader(&b"hello"[..]).read().unwrap();
+        assert_eq!(unknown.content_type, "application/octet-stream");
+        assert_eq!(
+            unknown.data_uri(),
+            "data:application/octet-stream;base64,aGVsbG8="
+        );
+    }
+
+    #[test]
+    fn test_resolve() -> Result<(), ReplicateError> {
+        let server = MockServer::start();
+
+        let upload_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/files")
+                .header_exists("content-type")
+                .body_contains("name=\"content\"; filename=\"large.png\"")
+                .body_contains("Content-Type: image/png");
+            then.status(201).json_body_obj(&json!({
+                "id": "cneqzikepnug6xezperrr4z55o",
+                "name": "large.png",
+                "content_type": "image/png",
+                "size": DATA_URI_MAX_SIZE + 1,
+                "etag": None::<String>,
+                "checksums": {"sha256": None::<String>, "md5": None::<String>},
+                "created_at": "2024-07-18T14:41:17.578761Z",
+                "expires_at": None::<String>,
+                "urls": {"get": "https://api.replicate.com/v1/files/cneqzikepnug6xezperrr4z55o"},
+            }));
+        });
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: server.base_url(),
+            ..Config::default()
+        };
+
+        let small = FileInput::bytes(b"hello".to_vec()).content_type("text/plain");
+        assert_eq!(small.resolve(&config)?, "data:text/plain;base64,aGVsbG8=");
+
+        let large = FileInput::bytes(vec![0; DATA_URI_MAX_SIZE + 1]).file_name("large.png");
+        assert_eq!(
+            large.resolve(&config)?,
+            "https://api.replicate.com/v1/files/cneqzikepnug6xezperrr4z55o"
+        );
+        upload_mock.assert();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,541 @@
+//! Used to interact with the [File Endpoints](https://replicate.com/docs/reference/http#files.create).
+//!
+//! Files uploaded once can be passed as inputs of many predictions by their url, instead of sending them inline every time.
+//! They expire after a while, see [`FileObject::expires_at`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use replicate_rust::{Replicate, config::Config, file_input::FileInput};
+//!
+//! let config = Config::default();
+//! let replicate = Replicate::new(config);
+//!
+//! let file = replicate.files.create(FileInput::path("wombat.png"))?;
+//!
+//! // Pass the url of the file as an input of the predictions.
+//! let mut inputs = std::collections::HashMap::new();
+//! inputs.insert("image", file.urls.get.clone());
+//!
+//! # Ok::<(), replicate_rust::errors::ReplicateError>(())
+//! ```
+
+use std::io::Write;
+
+use reqwest::{
+    header::{HeaderValue, CONTENT_TYPE},
+    Method,
+};
+use serde::Serialize;
+
+use crate::{
+    api_definitions::{FileObject, ListFiles},
+    config::Config,
+    download,
+    errors::ReplicateError,
+    file_input::{FileInput, LoadedFile},
+    pagination::Paginator,
+    transport::Request,
+};
+
+/// Used to interact with the [File Endpoints](https://replicate.com/docs/reference/http#files.create).
+#[derive(Clone, Debug)]
+pub struct Files {
+    /// Holds a reference to a Config struct, which contains the base url,  auth token among other settings.
+    pub parent: Config,
+}
+
+impl Files {
+    /// Create a new Files struct.
+    pub fn new(rep: Config) -> Self {
+        Self { parent: rep }
+    }
+
+    /// Return a copy of this endpoint using the given policy to retry its requests, e.g. to override the client policy for a single call.
+    pub fn with_retry_policy(&self, policy: crate::retry::RetryPolicy) -> Self {
+        let mut config = self.parent.clone();
+        config.retry_policy = policy;
+        Self::new(config)
+    }
+
+    /// Upload a file.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config, file_input::FileInput};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let file = replicate.files.create(FileInput::path("wombat.png"))?;
+    /// println!("File : {:?}", file.urls.get);
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn create(&self, file: FileInput) -> Result<FileObject, ReplicateError> {
+        let request = create_request(&self.parent, &file.read()?, None)?;
+
+        self.parent.send_json(request)
+    }
+
+    /// Upload a file along with metadata, returned as is by the API in [`FileObject::metadata`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config, file_input::FileInput};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let file = replicate.files.create_with_metadata(
+    ///     FileInput::path("wombat.png"),
+    ///     &serde_json::json!({"subject": "wombat"}),
+    /// )?;
+    /// println!("File : {:?}", file.metadata);
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn create_with_metadata<M: Serialize + ?Sized>(
+        &self,
+        file: FileInput,
+        metadata: &M,
+    ) -> Result<FileObject, ReplicateError> {
+        let metadata = serde_json::to_string(metadata)?;
+        let request = create_request(&self.parent, &file.read()?, Some(&metadata))?;
+
+        self.parent.send_json(request)
+    }
+
+    /// List the files uploaded by the user, most recent first.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let files = replicate.files.list()?;
+    /// println!("Files : {:?}", files);
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn list(&self) -> Result<ListFiles, ReplicateError> {
+        self.parent.send_json(list_request(&self.parent))
+    }
+
+    /// Iterate over all the files, following the pages lazily. See [`Paginator`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// for file in replicate.files.list_all() {
+    ///     println!("File : {}", file?.name);
+    /// }
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn list_all(&self) -> Paginator<FileObject> {
+        Paginator::new(self.parent.clone(), list_request(&self.parent).url)
+    }
+
+    /// Get a file by id.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let file = replicate.files.get("cneqzikepnug6xezperrr4z55o")?;
+    /// println!("Checksums : {:?}", file.checksums);
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn get(&self, id: &str) -> Result<FileObject, ReplicateError> {
+        self.parent.send_json(get_request(&self.parent, id))
+    }
+
+    /// Download the content of a file into a writer, returning the number of bytes written.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let mut file = std::fs::File::create("wombat.png")?;
+    /// replicate.files.download("cneqzikepnug6xezperrr4z55o", &mut file)?;
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn download(&self, id: &str, writer: &mut impl Write) -> Result<u64, ReplicateError> {
+        let request = download_request(&self.parent, id);
+        let url = request.url.clone();
+
+        let mut response = self.parent.send_streaming(request)?;
+        let written = std::io::copy(&mut response.body, writer)?;
+        download::check_size(&url, download::expected_size(&response.headers, 0), written)?;
+
+        Ok(written)
+    }
+
+    /// Delete a file.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// replicate.files.delete("cneqzikepnug6xezperrr4z55o")?;
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn delete(&self, id: &str) -> Result<(), ReplicateError> {
+        self.parent.send(delete_request(&self.parent, id))?;
+
+        Ok(())
+    }
+}
+
+/// Whether the content type is a MIME type, `{type}/{subtype}` with optional parameters, and can be sent in a header as is.
+fn is_mime_type(content_type: &str) -> bool {
+    let is_token = |token: &str| {
+        !token.is_empty()
+            && token
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c))
+    };
+
+    let essence = content_type.split(';').next().unwrap_or_default();
+    let valid_essence = match essence.trim().split_once('/') {
+        Some((main_type, subtype)) => is_token(main_type) && is_token(subtype),
+        None => false,
+    };
+
+    valid_essence
+        && content_type
+            .chars()
+            .all(|c| c.is_ascii() && (c == '\t' || !c.is_ascii_control()))
+}
+
+/// Request uploading a file, as a `multipart/form-data` body with the `content` and `metadata` fields expected by the API.
+///
+/// Fails with [`ReplicateError::InvalidContentType`] when the content type of the file is not a MIME type, as it is written as is in the body.
+pub(crate) fn create_request(
+    config: &Config,
+    file: &LoadedFile,
+    metadata: Option<&str>,
+) -> Result<Request, ReplicateError> {
+    if !is_mime_type(&file.content_type) {
+        return Err(ReplicateError::InvalidContentType(
+            file.content_type.clone(),
+        ));
+    }
+
+    let mut form = Multipart::new(file.bytes.len());
+    form.part(
+        "content",
+        Some(&file.file_name),
+        &file.content_type,
+        &file.bytes,
+    );
+    if let Some(metadata) = metadata {
+        form.part("metadata", None, "application/json", metadata.as_bytes());
+    }
+
+    let mut request = config.request(Method::POST, "/files");
+    if let Ok(value) = HeaderValue::from_str(&form.content_type()) {
+        request.headers.insert(CONTENT_TYPE, value);
+    }
+
+    Ok(request.body(form.finish()))
+}
+
+/// Encoder of a `multipart/form-data` body, see [RFC 7578](https://www.rfc-editor.org/rfc/rfc7578).
+/// The transport sends bodies as bytes, so the whole form is written in memory.
+struct Multipart {
+    boundary: String,
+    body: Vec<u8>,
+}
+
+impl Multipart {
+    /// Create an empty form, with room for the given number of bytes of content.
+    fn new(capacity: usize) -> Self {
+        Self {
+            boundary: format!("----replicate-rust-{:016x}", rand::random::<u64>()),
+            body: Vec::with_capacity(capacity + 512),
+        }
+    }
+
+    /// Quote the value of a parameter of the `Content-Disposition` header, escaping the characters
+    /// that would end it the way browsers do: `"` as `%22`, and line breaks as `%0D` and `%0A`.
+    fn quote(value: &str) -> String {
+        let mut quoted = String::with_capacity(value.len() + 2);
+        quoted.push('"');
+        for c in value.chars() {
+            match c {
+                '"' => quoted.push_str("%22"),
+                '\r' => quoted.push_str("%0D"),
+                '\n' => quoted.push_str("%0A"),
+                c => quoted.push(c),
+            }
+        }
+        quoted.push('"');
+        quoted
+    }
+
+    /// Add a field to the form, as a file when it has a file name. The content type must be a valid header value.
+    fn part(&mut self, name: &str, file_name: Option<&str>, content_type: &str, data: &[u8]) {
+        let mut disposition = format!("form-data; name={}", Self::quote(name));
+        if let Some(file_name) = file_name {
+            disposition.push_str(&format!("; filename={}", Self::quote(file_name)));
+        }
+
+        self.body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: {}\r\nContent-Type: {}\r\n\r\n",
+                self.boundary, disposition, content_type
+            )
+            .as_bytes(),
+        );
+        self.body.extend_from_slice(data);
+        self.body.extend_from_slice(b"\r\n");
+    }
+
+    /// Value of the `Content-Type` header of the form.
+    fn content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", self.boundary)
+    }
+
+    /// Close the form and return its body.
+    fn finish(mut self) -> Vec<u8> {
+        self.body
+            .extend_from_slice(format!("--{}--\r\n", self.boundary).as_bytes());
+        self.body
+    }
+}
+
+/// Request listing the uploaded files, shared with the [async client](crate::nonblocking::files).
+pub(crate) fn list_request(config: &Config) -> Request {
+    config.request(Method::GET, "/files")
+}
+
+/// Request getting an uploaded file, shared with the [async client](crate::nonblocking::files).
+pub(crate) fn get_request(config: &Config, id: &str) -> Request {
+    config.request(Method::GET, &format!("/files/{}", id))
+}
+
+/// Request downloading the content of an uploaded file, shared with the [async client](crate::nonblocking::files).
+pub(crate) fn download_request(config: &Config, id: &str) -> Request {
+    config.request(Method::GET, &format!("/files/{}/download", id))
+}
+
+/// Request deleting an uploaded file, shared with the [async client](crate::nonblocking::files).
+pub(crate) fn delete_request(config: &Config, id: &str) -> Request {
+    config.request(Method::DELETE, &format!("/files/{}", id))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{config::Config, Replicate};
+
+    use super::*;
+    use httpmock::{
+        Method::{GET, POST},
+        MockServer,
+    };
+    use serde_json::json;
+
+    fn file_object() -> serde_json::Value {
+        json!({
+            "id": "cneqzikepnug6xezperrr4z55o",
+            "name": "wombat.png",
+            "content_type": "image/png",
+            "size": 8,
+            "etag": "f211779ff7502705bbf42e9874a17ab3",
+            "checksums": {
+                "sha256": "cd47e3d9d6b4d4f5d6d4c3ab8b9ad9f1a6b8c7e1d2f3a4b5c6d7e8f9a0b1c2d3",
+                "md5": "f211779ff7502705bbf42e9874a17ab3",
+            },
+            "metadata": {"subject": "wombat"},
+            "created_at": "2024-07-18T14:41:17.578761Z",
+            "expires_at": "2024-07-19T14:41:17.578761Z",
+            "urls": {"get": "https://api.replicate.com/v1/files/cneqzikepnug6xezperrr4z55o"},
+        })
+    }
+
+    #[test]
+    fn test_create() -> Result<(), ReplicateError> {
+        let server = MockServer::start();
+
+        let post_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/files")
+                .header_exists("content-type")
+                .body_contains("name=\"content\"; filename=\"wombat.png\"")
+                .body_contains("Content-Type: image/png")
+                .body_contains("name=\"metadata\"")
+                .body_contains(r#"{"subject":"wombat"}"#);
+            then.status(201).json_body_obj(&file_object());
+        });
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: server.base_url(),
+            ..Config::default()
+        };
+        let replicate = Replicate::new(config);
+
+        let file = replicate.files.create_with_metadata(
+            FileInput::bytes(b"\x89PNG\r\n\x1a\n".to_vec()).file_name("wombat.png"),
+            &json!({"subject": "wombat"}),
+        )?;
+
+        assert_eq!(file.id, "cneqzikepnug6xezperrr4z55o");
+        assert_eq!(file.size, 8);
+        assert_eq!(
+            file.checksums.md5.as_deref(),
+            Some("f211779ff7502705bbf42e9874a17ab3")
+        );
+        assert!(file.expires_at.is_some());
+
+        // Ensure the mocks were called as expected
+        post_mock.assert();
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_multipart() {
+        let mut form = Multipart::new(0);
+        form.boundary = String::from("boundary");
+        form.part("content", Some("a \"b\"\r\n.txt"), "text/plain", b"hello");
+        form.part("metadata", None, "application/json", b"{}");
+
+        assert_eq!(
+            form.content_type(),
+            "multipart/form-data; boundary=boundary"
+        );
+        assert_eq!(
+            String::from_utf8(form.finish()).unwrap(),
+            "--boundary\r\n\
+             Content-Disposition: form-data; name=\"content\"; filename=\"a %22b%22%0D%0A.txt\"\r\n\
+             Content-Type: text/plain\r\n\r\n\
+             hello\r\n\
+             --boundary\r\n\
+             Content-Disposition: form-data; name=\"metadata\"\r\n\
+             Content-Type: application/json\r\n\r\n\
+             {}\r\n\
+             --boundary--\r\n"
+        );
+    }
+
+    #[test]
+    fn test_invalid_content_type() -> Result<(), ReplicateError> {
+        let server = MockServer::start();
+
+        let post_mock = server.mock(|when, then| {
+            when.method(POST).path("/files");
+            then.status(201).json_body_obj(&file_object());
+        });
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: server.base_url(),
+            ..Config::default()
+        };
+        let replicate = Replicate::new(config);
+
+        for content_type in [
+            "text/plain\r\nContent-Disposition: form-data; name=\"metadata\"",
+            "text",
+            "text/",
+            "text/plain; charset=\u{e9}",
+        ] {
+            let result = replicate
+                .files
+                .create(FileInput::bytes(b"hello".to_vec()).content_type(content_type));
+            assert!(
+                matches!(result, Err(ReplicateError::InvalidContentType(_))),
+                "{:?} should be rejected",
+                content_type
+            );
+        }
+
+        replicate.files.create(
+            FileInput::bytes(b"hello".to_vec()).content_type("text/plain; charset=utf-8"),
+        )?;
+
+        // Ensure the mocks were called as expected
+        post_mock.assert_hits(1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_get_download() -> Result<(), ReplicateError> {
+        let server = MockServer::start();
+
+        let list_mock = server.mock(|when, then| {
+            when.method(GET).path("/files");
+            then.status(200).json_body_obj(&json!({
+                "previous": None::<String>,
+                "next": None::<String>,
+                "results": [file_object()],
+            }));
+        });
+        let get_mock = server.mock(|when, then| {
+            when.method(GET).path("/files/cneqzikepnug6xezperrr4z55o");
+            then.status(200).json_body_obj(&file_object());
+        });
+        let download_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/files/cneqzikepnug6xezperrr4z55o/download");
+            then.status(200).body("\x7fPNG\r\n\x1a\n");
+        });
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: server.base_url(),
+            ..Config::default()
+        };
+        let replicate = Replicate::new(config);
+
+        let files = replicate.files.list()?;
+        assert_eq!(files.results.len(), 1);
+        assert_eq!(files.results[0].name, "wombat.png");
+
+        let file = replicate.files.get("cneqzikepnug6xezperrr4z55o")?;
+        assert_eq!(file.content_type, "image/png");
+        assert_eq!(file.metadata, json!({"subject": "wombat"}));
+
+        let mut content = Vec::new();
+        let written = replicate
+            .files
+            .download("cneqzikepnug6xezperrr4z55o", &mut content)?;
+        assert_eq!(written, file.size);
+
+        // Ensure the mocks were called as expected
+        list_mock.assert();
+        get_mock.assert();
+        download_mock.assert();
+
+        Ok(())
+    }
+}
@@ -17,7 +17,7 @@
 //!
 //! Here's an example using `replicate_rust` to run a model:
 //!
-//! ```rust
+//! ```rust,no_run
 //! use replicate_rust::{config::Config, Replicate, errors::ReplicateError};
 //!
 //! fn main() -> Result<(), ReplicateError> {
@@ -41,7 +41,7 @@
 //!
 //!    // Print the result.
 //!    println!("{:?}", result.output);
-//!    // Some(Array [String("https://pbxt.replicate.delivery/QLDGe2rXuIQ9ByMViQEXrYCkKfDi9I3YWAzPwWsDZWMXeN7iA/out-0.png")])```
+//!    // Some(Array [String("https://pbxt.replicate.delivery/QLDGe2rXuIQ9ByMViQEXrYCkKfDi9I3YWAzPwWsDZWMXeN7iA/out-0.png")])
 //!
 //!    Ok(())
 //! }
@@ -51,10 +51,95 @@
 //!
 //! See the [reference docs](https://docs.rs/replicate-rust/) for detailed API documentation.
 //!
+//! ## Async
+//!
+//! Enable the `async` feature to get an async client in the `nonblocking` module, which exposes the same endpoints as the blocking client:
+//!
+//! ```toml
+//! [dependencies]
+//! replicate-rust = { version = "0.0.5", features = ["async"] }
+//! ```
+//!
+//! ```rust,ignore
+//! use replicate_rust::{config::Config, nonblocking::Replicate};
+//!
+//! let replicate = Replicate::new(Config::default());
+//! let prediction = replicate.predictions.create(version, inputs).await?;
+//! let result = prediction.wait().await?;
+//! ```
+//!
+//! ## Webhook server
+//!
+//! Enable the `webhook-server` feature to receive the webhooks sent by Replicate with a small HTTP server, in the `webhooks::server` module:
+//!
+//! ```toml
+//! [dependencies]
+//! replicate-rust = { version = "0.0.5", features = ["webhook-server"] }
+//! ```
+//!
+//! ```rust,ignore
+//! use replicate_rust::webhooks::{server::WebhookServer, WebhookVerifier};
+//!
+//! let server = WebhookServer::bind("0.0.0.0:8080", WebhookVerifier::new(&secret.key)?)?;
+//! server.on(WebhookEvents::Completed, |event| println!("{:?}", event.prediction.output));
+//! ```
+//!
+//! ## Timestamps
+//!
+//! The timestamps of the API responses, e.g. `created_at`, are RFC 3339 strings. Enable the `chrono` feature to also parse them into `chrono::DateTime<Utc>`,
+//! with accessors such as `created_at_parsed()`. Predictions and trainings then also have `queue_duration()`, `run_duration()` and `total_duration()` helpers:
+//!
+//! ```toml
+//! [dependencies]
+//! replicate-rust = { version = "0.0.5", features = ["chrono"] }
+//! ```
+//!
+//! ```rust,ignore
+//! let prediction = replicate.predictions.get("rrr4z55ocneqzikepnug6xezpe")?;
+//! println!("Ran for {:?}", prediction.run_duration()?);
+//! ```
+//!
+//! ## Typed bindings
+//!
+//! The `codegen` module generates typed inputs, output and a `run` function for a model version, from its schema saved as a JSON file.
+//! Call it from a build script, and include the generated module:
+//!
+//! ```rust,ignore
+//! // build.rs
+//! replicate_rust::codegen::Bindings::from_file(
+//!     "stability-ai/stable-diffusion:27b93a2413e7f36cd83da926f3656280b2931564ff050bf9575f1fdf9bcd7478",
+//!     "schemas/stable-diffusion.json",
+//! )?
+//! .write(format!("{}/stable_diffusion.rs", std::env::var("OUT_DIR")?))?;
+//! ```
+//!
+//! ```rust,ignore
+//! include!(concat!(env!("OUT_DIR"), "/stable_diffusion.rs"));
+//!
+//! let images = stable_diffusion::run(&replicate, &stable_diffusion::Input {
+//!     prompt: String::from("a 19th century portrait of a wombat gentleman"),
+//!     ..Default::default()
+//! })?;
+//! ```
+//!
 //! ## Examples
 //!
+//! - Run the latest version of an official model, by name:
+//!   ```rust,no_run
+//!   # use replicate_rust::{Replicate, config::Config};
+//!   # let replicate = Replicate::new(Config::default());
+//!   let mut inputs = std::collections::HashMap::new();
+//!   inputs.insert("prompt", "Tell me a story about a wombat");
+//!
+//!   let result = replicate.run("meta/meta-llama-3-8b-instruct", inputs)?;
+//!   println!("Output : {:?}", result.output);
+//!   # Ok::<(), replicate_rust::errors::ReplicateError>(())
+//!   ```
+//!
 //! - Run a model in the background:
-//!     ```rust
+//!     ```rust,no_run
+//!     # use replicate_rust::{Replicate, config::Config};
+//!     # let replicate = Replicate::new(Config::default());
 //!     // Construct the inputs.
 //!     let mut inputs = std::collections::HashMap::new();
 //!     inputs.insert("prompt", "a 19th century portrait of a wombat gentleman");
@@ -84,11 +169,14 @@
 //!     // 'succeeded'
 //!
 //!     println!("{:?}", prediction.output);
-// !    // Some(Array [String("https://pbxt.replicate.delivery/QLDGe2rXuIQ9ByMViQEXrYCkKfDi9I3YWAzPwWsDZWMXeN7iA/out-0.png")])
+//!     // Some(Array [String("https://pbxt.replicate.delivery/QLDGe2rXuIQ9ByMViQEXrYCkKfDi9I3YWAzPwWsDZWMXeN7iA/out-0.png")])
+//!     # Ok::<(), replicate_rust::errors::ReplicateError>(())
 //!     ```
 //!
 //! - Cancel a prediction:
-//!   ```rust
+//!   ```rust,no_run
+//!   # use replicate_rust::{Replicate, config::Config};
+//!   # let replicate = Replicate::new(Config::default());
 //!   // Construct the inputs.
 //!   let mut inputs = std::collections::HashMap::new();
 //!   inputs.insert("prompt", "a 19th century portrait of a wombat gentleman");
@@ -107,50 +195,192 @@
 //!
 //!   println!("{:?}", prediction.status);
 //!   // 'cancelled'
+//!   # Ok::<(), replicate_rust::errors::ReplicateError>(())
 //!   ```
 //!
 //! - List predictions:
-//!   ```rust
+//!   ```rust,no_run
+//!   # use replicate_rust::{Replicate, config::Config};
+//!   # let replicate = Replicate::new(Config::default());
 //!   let predictions = replicate.predictions.list()?;
 //!   println!("{:?}", predictions);
 //!   // ListPredictions { ... }
+//!   # Ok::<(), replicate_rust::errors::ReplicateError>(())
 //!   ```
 //!
 //! - Get model Information:
-//!   ```rust
+//!   ```rust,no_run
+//!   # use replicate_rust::{Replicate, config::Config};
+//!   # let replicate = Replicate::new(Config::default());
 //!   let model = replicate.models.get("replicate", "hello-world")?;
 //!   println!("{:?}", model);
 //!   // GetModel { ... }
+//!   # Ok::<(), replicate_rust::errors::ReplicateError>(())
 //!   ```
 //!
 //! - Get Versions List:
-//!   ```rust
+//!   ```rust,no_run
+//!   # use replicate_rust::{Replicate, config::Config};
+//!   # let replicate = Replicate::new(Config::default());
 //!   let versions = replicate.models.versions.list("replicate", "hello-world")?;
 //!   println!("{:?}", versions);
 //!   // ListModelVersions { ... }
+//!   # Ok::<(), replicate_rust::errors::ReplicateError>(())
 //!   ```
 //!
 //! - Get Model Version Information:
-//!   ```rust
+//!   ```rust,no_run
+//!   # use replicate_rust::{Replicate, config::Config};
+//!   # let replicate = Replicate::new(Config::default());
 //!   let model = replicate.models.versions.get("kvfrans",
 //!   "clipdraw",
 //!   "5797a99edc939ea0e9242d5e8c9cb3bc7d125b1eac21bda852e5cb79ede2cd9b",)?;
 //!   println!("{:?}", model);
 //!   // GetModelVersion { ... }
+//!   # Ok::<(), replicate_rust::errors::ReplicateError>(())
 //!   ```
 //!
 //! - Get Collection Information:
-//!   ```rust
+//!   ```rust,no_run
+//!   # use replicate_rust::{Replicate, config::Config};
+//!   # let replicate = Replicate::new(Config::default());
 //!   let collection = replicate.collections.get("audio-generation")?;
 //!   println!("{:?}", collection);
-//!   // GetCollectionModels { ... }//!   ```
-//!    ```
+//!   // GetCollectionModels { ... }
+//!   # Ok::<(), replicate_rust::errors::ReplicateError>(())
+//!   ```
 //!
 //! - Get Collection Lists:
-//!   ```rust
+//!   ```rust,no_run
+//!   # use replicate_rust::{Replicate, config::Config};
+//!   # let replicate = Replicate::new(Config::default());
 //!   let collections = replicate.collections.list()?;
 //!   println!("{:?}", collections);
 //!   // ListCollectionModels { ... }
+//!   # Ok::<(), replicate_rust::errors::ReplicateError>(())
+//!   ```
+//!
+//! - Iterate over all the predictions, fetching the pages lazily:
+//!   ```rust,no_run
+//!   # use replicate_rust::{Replicate, config::Config};
+//!   # let replicate = Replicate::new(Config::default());
+//!   for prediction in replicate.predictions.list_all().page_size(100).take(250) {
+//!       println!("{}", prediction?.id);
+//!   }
+//!   # Ok::<(), replicate_rust::errors::ReplicateError>(())
+//!   ```
+//!
+//! - Run a model on a local file, sent inline or uploaded depending on its size:
+//!   ```rust,no_run
+//!   # use replicate_rust::{Replicate, config::Config};
+//!   # let replicate = Replicate::new(Config::default());
+//!   use replicate_rust::file_input::FileInput;
+//!
+//!   let mut inputs = std::collections::HashMap::new();
+//!   inputs.insert("scale", 4);
+//!
+//!   let mut files = std::collections::HashMap::new();
+//!   files.insert("image", FileInput::path("wombat.png"));
+//!
+//!   let version = "nightmareai/real-esrgan:42fed1c4974146d4d2414e2be2c5277c7fcf05fcc3a73abf41610695738c1d7b";
+//!   let prediction = replicate.predictions.create_with_files(version, inputs, files)?;
+//!   # Ok::<(), replicate_rust::errors::ReplicateError>(())
+//!   ```
+//!
+//! - Upload a file once, to pass its url to many predictions:
+//!   ```rust,no_run
+//!   # use replicate_rust::{Replicate, config::Config};
+//!   # let replicate = Replicate::new(Config::default());
+//!   use replicate_rust::file_input::FileInput;
+//!
+//!   let file = replicate.files.create(FileInput::path("wombat.png"))?;
+//!   println!("{} expires at {:?}", file.urls.get, file.expires_at);
+//!
+//!   for file in replicate.files.list_all() {
+//!       replicate.files.delete(&file?.id)?;
+//!   }
+//!   # Ok::<(), replicate_rust::errors::ReplicateError>(())
+//!   ```
+//!
+//! - Search models, most recently updated first:
+//!   ```rust,no_run
+//!   # use replicate_rust::{Replicate, config::Config};
+//!   # let replicate = Replicate::new(Config::default());
+//!   use replicate_rust::model::{ModelSortField, SearchOptions, SortDirection};
+//!
+//!   let options = SearchOptions::new()
+//!       .sort_by(ModelSortField::LatestVersionCreatedAt)
+//!       .sort_direction(SortDirection::Desc)
+//!       .limit(20);
+//!
+//!   for model in replicate.models.search_with_options("upscale", &options) {
+//!       let model = model?;
+//!       println!("{}/{}", model.owner, model.name);
+//!   }
+//!   # Ok::<(), replicate_rust::errors::ReplicateError>(())
+//!   ```
+//!
+//! - Create a model, as destination of a fine-tune:
+//!   ```rust,no_run
+//!   # use replicate_rust::{Replicate, config::Config};
+//!   # let replicate = Replicate::new(Config::default());
+//!   use replicate_rust::{api_definitions::ModelVisibility, model::ModelOptions};
+//!
+//!   let options = ModelOptions::new("acme", "wombat-upscaler", ModelVisibility::Private, "gpu-t4");
+//!   let model = replicate.models.create(options)?;
+//!   println!("{}", model.url);
+//!   // https://replicate.com/acme/wombat-upscaler
+//!   # Ok::<(), replicate_rust::errors::ReplicateError>(())
+//!   ```
+//!
+//! - Run a prediction on a deployment, with dedicated hardware:
+//!   ```rust,no_run
+//!   # use replicate_rust::{Replicate, config::Config};
+//!   # let replicate = Replicate::new(Config::default());
+//!   # let mut inputs = std::collections::HashMap::new();
+//!   # inputs.insert("image", "https://example.com/wombat.png");
+//!   let prediction = replicate
+//!       .deployments
+//!       .predictions
+//!       .create("acme", "image-upscaler", inputs)?;
+//!
+//!   let result = prediction.wait()?;
+//!   println!("Result : {:?}", result.output);
+//!   # Ok::<(), replicate_rust::errors::ReplicateError>(())
+//!   ```
+//!
+//! - Download the files generated by a model, resuming interrupted downloads:
+//!   ```rust,no_run
+//!   # use replicate_rust::{Replicate, config::Config};
+//!   # let replicate = Replicate::new(Config::default());
+//!   # let mut inputs = std::collections::HashMap::new();
+//!   # inputs.insert("prompt", "a 19th century portrait of a wombat gentleman");
+//!   # let version = "stability-ai/stable-diffusion:27b93a2413e7f36cd83da926f3656280b2931564ff050bf9575f1fdf9bcd7478";
+//!   use replicate_rust::download::DownloadOptions;
+//!
+//!   let prediction = replicate.predictions.create(version, inputs)?;
+//!
+//!   let paths = prediction.download_outputs("outputs", &DownloadOptions::default())?;
+//!   println!("{:?}", paths);
+//!   // ["outputs/out-0.png"]
+//!   # Ok::<(), replicate_rust::errors::ReplicateError>(())
+//!   ```
+//!
+//! - Verify a webhook:
+//!   ```rust,no_run
+//!   # use replicate_rust::{Replicate, config::Config};
+//!   # let replicate = Replicate::new(Config::default());
+//!   # let (headers, body) = (reqwest::header::HeaderMap::new(), b"");
+//!   use replicate_rust::webhooks::WebhookVerifier;
+//!
+//!   let secret = replicate.webhooks.default_secret()?;
+//!   let verifier = WebhookVerifier::new(&secret.key)?;
+//!
+//!   // With the headers and raw body of the request received by your server.
+//!   let prediction = verifier.parse_prediction(&headers, body)?;
+//!   println!("{:?}", prediction.status);
+//!   // succeeded
+//!   # Ok::<(), replicate_rust::errors::ReplicateError>(())
 //!   ```
 //!
 #![warn(missing_docs)]
@@ -158,31 +388,54 @@
 
 use std::collections::HashMap;
 
-use api_definitions::GetPrediction;
+use api_definitions::{GetAccount, GetPrediction};
 use collection::Collection;
 use config::Config;
+use deployment::Deployment;
 use errors::ReplicateError;
+use files::Files;
 use model::Model;
 use prediction::Prediction;
+use reqwest::Method;
 use training::Training;
+use transport::Request;
+use webhooks::Webhooks;
 
+pub mod codegen;
 pub mod collection;
 pub mod config;
+pub mod deployment;
+pub mod files;
 pub mod model;
 pub mod prediction;
 pub mod training;
 pub mod version;
+pub mod webhooks;
 
 pub mod api_definitions;
+pub mod download;
 pub mod errors;
+pub mod file_input;
+pub mod model_ref;
+pub mod output;
+pub mod pagination;
 pub mod prediction_client;
 pub mod retry;
+pub mod schema;
+pub mod stream;
+pub mod transport;
+
+#[cfg(feature = "async")]
+pub mod nonblocking;
 
 /// Rust Client for interacting with the [Replicate API](https://replicate.com/docs/api/). Currently supports the following endpoints:
 /// * [Predictions](https://replicate.com/docs/reference/http#predictions.create)
 /// * [Models](https://replicate.com/docs/reference/http#models.get)
 /// * [Trainings](https://replicate.com/docs/reference/http#trainings.create)
 /// * [Collections](https://replicate.com/docs/reference/http#collections.get)
+/// * [Deployments](https://replicate.com/docs/reference/http#deployments.get)
+/// * [Files](https://replicate.com/docs/reference/http#files.create)
+/// * [Webhooks](https://replicate.com/docs/reference/http#webhooks.default.secret.get)
 #[derive(Clone, Debug)]
 pub struct Replicate {
     /// Holds a reference to a Config struct.
@@ -199,44 +452,121 @@ pub struct Replicate {
 
     /// Holds a reference to a Collection struct. Use to get and list model collections present in Replicate.
     pub collections: Collection,
+
+    /// Holds a reference to a Deployment struct. Use to manage deployments and run predictions on them.
+    pub deployments: Deployment,
+
+    /// Holds a reference to a Files struct. Use to upload files and manage the uploaded files.
+    pub files: Files,
+
+    /// Holds a reference to a Webhooks struct. Use to get the secret signing the webhooks.
+    pub webhooks: Webhooks,
 }
 
 /// Rust Client for interacting with the [Replicate API](https://replicate.com/docs/api/).
 impl Replicate {
     /// Create a new Replicate client.
     ///
+    /// The transport is built once from the config (unless one was injected with [`ConfigBuilder::transport`](config::ConfigBuilder::transport)) and shared by all the endpoints.
+    ///
+    /// # Panics
+    /// Panics if no API token is set, or if the HTTP client cannot be built from the config, e.g. if the TLS backend cannot be initialized.
+    /// Use [`try_new`](Self::try_new) to handle these errors instead.
+    ///
     /// # Example
-    /// ```
+    /// ```no_run
     /// use replicate_rust::{Replicate, config::Config};
     ///
     /// let config = Config::default();
     /// let replicate = Replicate::new(config);
     /// ```
     pub fn new(config: Config) -> Self {
+        match Self::try_new(config) {
+            Ok(replicate) => replicate,
+            Err(error) => panic!("failed to create the Replicate client: {}", error),
+        }
+    }
+
+    /// Create a new Replicate client, returning an error instead of panicking.
+    ///
+    /// Returns a [`ReplicateError::MissingCredentials`] error if no API token is set.
+    /// The token itself is not checked against the API, use [`validate_token`](Self::validate_token) for that.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::try_new(config)?;
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn try_new(mut config: Config) -> Result<Self, ReplicateError> {
         // Check if auth is set.
-        config.check_auth();
+        config.check_auth()?;
+
+        // Build the transport shared by all the endpoints.
+        config.transport = Some(config.transport()?);
 
-        // TODO : Maybe reference instead of clone
         let predictions = Prediction::new(config.clone());
         let models = Model::new(config.clone());
         let trainings = Training::new(config.clone());
         let collections = Collection::new(config.clone());
+        let deployments = Deployment::new(config.clone());
+        let files = Files::new(config.clone());
+        let webhooks = Webhooks::new(config.clone());
 
-        Self {
+        Ok(Self {
             config,
             predictions,
             models,
             trainings,
             collections,
-        }
+            deployments,
+            files,
+            webhooks,
+        })
+    }
+
+    /// Get the account the API token belongs to.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let account = replicate.account()?;
+    /// println!("Username : {}", account.username);
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn account(&self) -> Result<GetAccount, ReplicateError> {
+        self.config.send_json(account_request(&self.config))
+    }
+
+    /// Check the API token against the API, returning the account it belongs to.
+    ///
+    /// Returns a [`ReplicateError::InvalidCredentials`] error if the API rejects the token.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::try_new(config)?;
+    /// replicate.validate_token()?;
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn validate_token(&self) -> Result<GetAccount, ReplicateError> {
+        check_credentials(self.account())
     }
 
     /// Run a model with the given inputs in a blocking manner.
     /// # Arguments
-    /// * `version` - The version of the model to run.
+    /// * `version` - The model to run, as `{owner}/{name}:{version_id}`, `{owner}/{name}` or a version id. See [`model_ref::ModelRef`].
     /// * `inputs` - The inputs to the model in the form of a HashMap.
     /// # Example
-    /// ```
+    /// ```no_run
     /// use replicate_rust::{Replicate, config::Config};
     ///
     /// let config = Config::default();
@@ -265,6 +595,23 @@ impl Replicate {
     }
 }
 
+/// Request getting the account the API token belongs to, shared with the [async client](crate::nonblocking).
+pub(crate) fn account_request(config: &Config) -> Request {
+    config.request(Method::GET, "/account")
+}
+
+/// Turn the rejection of the API token by the account endpoint into a [`ReplicateError::InvalidCredentials`] error.
+pub(crate) fn check_credentials(
+    result: Result<GetAccount, ReplicateError>,
+) -> Result<GetAccount, ReplicateError> {
+    match result {
+        Err(ReplicateError::ResponseError(error)) if error.is_auth_error() => {
+            Err(ReplicateError::InvalidCredentials(error))
+        }
+        result => result,
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -347,4 +694,63 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_try_new_missing_credentials() {
+        let config = Config {
+            auth: String::new(),
+            ..Config::default()
+        };
+
+        let result = Replicate::try_new(config);
+        assert!(matches!(result, Err(ReplicateError::MissingCredentials)));
+    }
+
+    #[test]
+    fn test_validate_token() -> Result<(), ReplicateError> {
+        let server = MockServer::start();
+
+        let valid_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/account")
+                .header("authorization", "Token valid");
+            then.status(200).json_body_obj(&json!({
+                "type": "organization",
+                "username": "replicate",
+                "name": "Replicate",
+                "github_url": "https://github.com/replicate",
+            }));
+        });
+        let invalid_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/account")
+                .header("authorization", "Token invalid");
+            then.status(401).json_body_obj(&json!({
+                "title": "Unauthenticated",
+                "detail": "You did not pass a valid authentication token",
+                "status": 401,
+            }));
+        });
+
+        let config = Config::builder()
+            .auth("valid")
+            .base_url(server.base_url())
+            .build();
+        let account = Replicate::try_new(config)?.validate_token()?;
+        assert_eq!(account.username, "replicate");
+        assert_eq!(account.account_type, "organization");
+
+        let config = Config::builder()
+            .auth("invalid")
+            .base_url(server.base_url())
+            .build();
+        let error = Replicate::try_new(config)?.validate_token().unwrap_err();
+        assert!(matches!(error, ReplicateError::InvalidCredentials(_)));
+        assert!(error.is_auth_error());
+
+        valid_mock.assert();
+        invalid_mock.assert();
+
+        Ok(())
+    }
 }
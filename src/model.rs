@@ -3,12 +3,17 @@
 //! The model module contains all the functionality for interacting with the model endpoints of the Replicate API.
 //! Currently supports the following endpoint:
 //! * [Get Model](https://replicate.com/docs/reference/http#models.get)
+//! * [List Models](https://replicate.com/docs/reference/http#models.list)
+//! * [Search Models](https://replicate.com/docs/reference/http#models.search)
+//! * [Create Model](https://replicate.com/docs/reference/http#models.create)
+//! * [Update Model](https://replicate.com/docs/reference/http#models.update)
+//! * [Delete Model](https://replicate.com/docs/reference/http#models.delete)
 //! * [Get Model Versions](https://replicate.com/docs/reference/http#models.versions.get)
 //! * [List Model Versions](https://replicate.com/docs/reference/http#models.versions.list)
 //! * [Delete Model Version](https://replicate.com/docs/reference/http#models.versions.delete)
 //!
 //! # Example
-//! ```
+//! ```no_run
 //! use replicate_rust::{Replicate, config::Config};
 //!
 //! let config = Config::default();
@@ -19,7 +24,215 @@
 //! # Ok::<(), replicate_rust::errors::ReplicateError>(())
 //! ```
 
-use crate::{api_definitions::GetModel, errors::ReplicateError, version::Version};
+use reqwest::Method;
+use serde::Serialize;
+
+use crate::{
+    api_definitions::{GetModel, ListModels, ModelVisibility},
+    config::Config,
+    errors::ReplicateError,
+    pagination::Paginator,
+    transport::Request,
+    version::Version,
+};
+
+/// Contains all the options for creating a model.
+///
+/// To use a local image as cover, upload it first with [`crate::files::Files::create`] and pass its url.
+#[derive(Clone, Debug, Serialize)]
+pub struct ModelOptions {
+    /// Owner of the model, the user or one of its organizations.
+    pub owner: String,
+
+    /// Name of the model, unique for the owner.
+    pub name: String,
+
+    /// Whether the model can be seen and run by anyone, or only by the owner.
+    pub visibility: ModelVisibility,
+
+    /// SKU of the hardware running the model, e.g. `gpu-t4`.
+    pub hardware: String,
+
+    /// Description of the model.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Url of the source code of the model.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub github_url: Option<String>,
+
+    /// Url of the paper describing the model.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paper_url: Option<String>,
+
+    /// Url of the license of the model.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license_url: Option<String>,
+
+    /// Url of the image shown as cover of the model.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cover_image_url: Option<String>,
+}
+
+impl ModelOptions {
+    /// Create the options of a model, without description nor urls.
+    pub fn new(owner: &str, name: &str, visibility: ModelVisibility, hardware: &str) -> Self {
+        Self {
+            owner: owner.to_string(),
+            name: name.to_string(),
+            visibility,
+            hardware: hardware.to_string(),
+            description: None,
+            github_url: None,
+            paper_url: None,
+            license_url: None,
+            cover_image_url: None,
+        }
+    }
+}
+
+/// Contains the options for updating a model. Fields left to `None` are not changed.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ModelUpdate {
+    /// Description of the model.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// README of the model, in markdown.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub readme: Option<String>,
+
+    /// Url of the source code of the model.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub github_url: Option<String>,
+
+    /// Url of the paper describing the model.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paper_url: Option<String>,
+
+    /// Url of the license of the model.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license_url: Option<String>,
+
+    /// Url of the weights of the model.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weights_url: Option<String>,
+
+    /// Url of the image shown as cover of the model.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cover_image_url: Option<String>,
+}
+
+/// Field to sort the models found by a search.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ModelSortField {
+    /// Creation time of the model.
+    ModelCreatedAt,
+
+    /// Creation time of the latest version of the model.
+    LatestVersionCreatedAt,
+}
+
+impl ModelSortField {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::ModelCreatedAt => "model_created_at",
+            Self::LatestVersionCreatedAt => "latest_version_created_at",
+        }
+    }
+}
+
+/// Direction of the sort of the models found by a search.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SortDirection {
+    /// Oldest first.
+    Asc,
+
+    /// Most recent first.
+    Desc,
+}
+
+impl SortDirection {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Asc => "asc",
+            Self::Desc => "desc",
+        }
+    }
+}
+
+/// Options of a model search, to sort and limit the models found.
+///
+/// # Example
+///
+/// ```
+/// use replicate_rust::model::{ModelSortField, SearchOptions, SortDirection};
+///
+/// let options = SearchOptions::new()
+///     .sort_by(ModelSortField::LatestVersionCreatedAt)
+///     .sort_direction(SortDirection::Desc)
+///     .limit(50);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SearchOptions {
+    /// Field to sort the models by. Sorted by relevance when `None`.
+    pub sort_by: Option<ModelSortField>,
+
+    /// Direction of the sort.
+    pub sort_direction: Option<SortDirection>,
+
+    /// Maximum number of models returned.
+    pub limit: Option<usize>,
+}
+
+impl SearchOptions {
+    /// Create options returning every model found, sorted by relevance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sort the models by this field.
+    pub fn sort_by(mut self, field: ModelSortField) -> Self {
+        self.sort_by = Some(field);
+        self
+    }
+
+    /// Sort the models in this direction.
+    pub fn sort_direction(mut self, direction: SortDirection) -> Self {
+        self.sort_direction = Some(direction);
+        self
+    }
+
+    /// Return at most this number of models. The pages past them are not fetched.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Url of the first page of models found, with the sort options.
+    pub(crate) fn url(&self, config: &crate::config::Config) -> String {
+        let url = config.url("/models");
+        let query = [
+            ("sort_by", self.sort_by.map(|field| field.as_str())),
+            (
+                "sort_direction",
+                self.sort_direction.map(|direction| direction.as_str()),
+            ),
+        ];
+
+        match reqwest::Url::parse(&url) {
+            Ok(mut parsed) => {
+                for (name, value) in query {
+                    if let Some(value) = value {
+                        parsed.query_pairs_mut().append_pair(name, value);
+                    }
+                }
+                parsed.into()
+            }
+            Err(_) => url,
+        }
+    }
+}
 
 // #[derive(Clone)]
 /// Used to interact with the [Model Endpoints](https://replicate.com/docs/reference/http#models.get).
@@ -35,6 +248,11 @@ pub struct Model {
 /// Model struct contains all the functionality for interacting with the model endpoints of the Replicate API.
 /// Currently supports the following endpoint:
 /// * [Get Model](https://replicate.com/docs/reference/http#models.get)
+/// * [List Models](https://replicate.com/docs/reference/http#models.list)
+/// * [Search Models](https://replicate.com/docs/reference/http#models.search)
+/// * [Create Model](https://replicate.com/docs/reference/http#models.create)
+/// * [Update Model](https://replicate.com/docs/reference/http#models.update)
+/// * [Delete Model](https://replicate.com/docs/reference/http#models.delete)
 /// * [Get Model Versions](https://replicate.com/docs/reference/http#models.versions.get)
 /// * [List Model Versions](https://replicate.com/docs/reference/http#models.versions.list)
 /// * [Delete Model Version](https://replicate.com/docs/reference/http#models.versions.delete)
@@ -52,13 +270,20 @@ impl Model {
         }
     }
 
+    /// Return a copy of this endpoint using the given policy to retry its requests, e.g. to override the client policy for a single call.
+    pub fn with_retry_policy(&self, policy: crate::retry::RetryPolicy) -> Self {
+        let mut config = self.parent.clone();
+        config.retry_policy = policy;
+        Self::new(config)
+    }
+
     /// Get the details of a model.
     /// # Arguments
     /// * `model_owner` - The owner of the model.
     /// * `model_name` - The name of the model.
     ///
     /// # Example
-    /// ```
+    /// ```no_run
     /// use replicate_rust::{Replicate, config::Config};
     ///
     /// let config = Config::default();
@@ -70,35 +295,250 @@ impl Model {
     /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
     /// ```
     pub fn get(&self, model_owner: &str, model_name: &str) -> Result<GetModel, ReplicateError> {
-        let client = reqwest::blocking::Client::new();
-
-        let response = client
-            .get(format!(
-                "{}/models/{}/{}",
-                self.parent.base_url, model_owner, model_name
-            ))
-            .header("Authorization", format!("Token {}", self.parent.auth))
-            .header("User-Agent", &self.parent.user_agent)
-            .send()?;
-
-        if !response.status().is_success() {
-            return Err(ReplicateError::ResponseError(response.text()?));
+        self.parent
+            .send_json(get_request(&self.parent, model_owner, model_name))
+    }
+
+    /// List the public models, along with the models of the user.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let models = replicate.models.list()?;
+    /// println!("Models : {:?}", models);
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn list(&self) -> Result<ListModels, ReplicateError> {
+        self.parent.send_json(list_request(&self.parent))
+    }
+
+    /// Iterate over all the models, following the pages lazily. See [`Paginator`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// for model in replicate.models.list_all().take(100) {
+    ///     let model = model?;
+    ///     println!("Model : {}/{}", model.owner, model.name);
+    /// }
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn list_all(&self) -> Paginator<GetModel> {
+        Paginator::new(self.parent.clone(), list_request(&self.parent).url)
+    }
+
+    /// Search the public models, following the pages of results lazily. See [`Paginator`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// for model in replicate.models.search("upscale").take(10) {
+    ///     let model = model?;
+    ///     println!("Model : {}/{}", model.owner, model.name);
+    /// }
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn search(&self, query: &str) -> Paginator<GetModel> {
+        self.search_with_options(query, &SearchOptions::default())
+    }
+
+    /// Search the public models, sorting and limiting the models found.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use replicate_rust::{
+    ///     config::Config,
+    ///     model::{ModelSortField, SearchOptions, SortDirection},
+    ///     Replicate,
+    /// };
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let options = SearchOptions::new()
+    ///     .sort_by(ModelSortField::LatestVersionCreatedAt)
+    ///     .sort_direction(SortDirection::Desc)
+    ///     .limit(100);
+    ///
+    /// let models = replicate
+    ///     .models
+    ///     .search_with_options("upscale", &options)
+    ///     .collect::<Result<Vec<_>, _>>()?;
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn search_with_options(&self, query: &str, options: &SearchOptions) -> Paginator<GetModel> {
+        let paginator =
+            Paginator::new(self.parent.clone(), options.url(&self.parent)).with_search(query);
+
+        match options.limit {
+            Some(limit) => paginator.limit(limit),
+            None => paginator,
         }
+    }
 
-        let response_string = response.text()?;
-        let response_struct: GetModel = serde_json::from_str(&response_string)?;
+    /// Create a model. Its versions are then pushed with Cog, or created by a training.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use replicate_rust::{
+    ///     api_definitions::ModelVisibility, config::Config, model::ModelOptions, Replicate,
+    /// };
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let mut options = ModelOptions::new("acme", "wombat-upscaler", ModelVisibility::Private, "gpu-t4");
+    /// options.description = Some(String::from("Upscale portraits of wombats"));
+    ///
+    /// let model = replicate.models.create(options)?;
+    /// println!("Model : {}", model.url);
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn create(&self, options: ModelOptions) -> Result<GetModel, ReplicateError> {
+        self.parent
+            .send_json(create_request(&self.parent, &options)?)
+    }
 
-        Ok(response_struct)
+    /// Update the details of a model.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config, model::ModelUpdate};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let model = replicate.models.update(
+    ///     "acme",
+    ///     "wombat-upscaler",
+    ///     ModelUpdate {
+    ///         license_url: Some(String::from("https://opensource.org/license/mit")),
+    ///         ..ModelUpdate::default()
+    ///     },
+    /// )?;
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn update(
+        &self,
+        model_owner: &str,
+        model_name: &str,
+        options: ModelUpdate,
+    ) -> Result<GetModel, ReplicateError> {
+        self.parent.send_json(update_request(
+            &self.parent,
+            model_owner,
+            model_name,
+            &options,
+        )?)
     }
+
+    /// Delete a model. Only private models without versions can be deleted, see [`Version::delete`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// replicate.models.delete("acme", "wombat-upscaler")?;
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn delete(&self, model_owner: &str, model_name: &str) -> Result<(), ReplicateError> {
+        self.parent
+            .send(delete_request(&self.parent, model_owner, model_name))?;
+
+        Ok(())
+    }
+}
+
+/// Path of a model.
+fn model_path(model_owner: &str, model_name: &str) -> String {
+    format!("/models/{}/{}", model_owner, model_name)
+}
+
+/// Request getting a model, shared with the [async client](crate::nonblocking::model).
+pub(crate) fn get_request(config: &Config, model_owner: &str, model_name: &str) -> Request {
+    config.request(Method::GET, &model_path(model_owner, model_name))
+}
+
+/// Request listing the public models, shared with the [async client](crate::nonblocking::model).
+pub(crate) fn list_request(config: &Config) -> Request {
+    config.request(Method::GET, "/models")
+}
+
+/// Request creating a model, shared with the [async client](crate::nonblocking::model).
+pub(crate) fn create_request(
+    config: &Config,
+    options: &ModelOptions,
+) -> Result<Request, ReplicateError> {
+    config.request(Method::POST, "/models").json(options)
+}
+
+/// Request updating a model, shared with the [async client](crate::nonblocking::model).
+pub(crate) fn update_request(
+    config: &Config,
+    model_owner: &str,
+    model_name: &str,
+    options: &ModelUpdate,
+) -> Result<Request, ReplicateError> {
+    config
+        .request(Method::PATCH, &model_path(model_owner, model_name))
+        .json(options)
+}
+
+/// Request deleting a model, shared with the [async client](crate::nonblocking::model).
+pub(crate) fn delete_request(config: &Config, model_owner: &str, model_name: &str) -> Request {
+    config.request(Method::DELETE, &model_path(model_owner, model_name))
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{config::Config, errors::ReplicateError, Replicate};
 
-    use httpmock::{Method::GET, MockServer};
+    use super::*;
+    use httpmock::{
+        Method::{DELETE, GET, PATCH, POST},
+        MockServer,
+    };
     use serde_json::json;
 
+    fn model_json(description: Option<&str>) -> serde_json::Value {
+        json!({
+            "url": "https://replicate.com/acme/wombat-upscaler",
+            "owner": "acme",
+            "name": "wombat-upscaler",
+            "description": description,
+            "visibility": "private",
+            "github_url": None::<String>,
+            "paper_url": None::<String>,
+            "license_url": None::<String>,
+            "run_count": 0,
+            "cover_image_url": None::<String>,
+            "default_example": None::<String>,
+            "latest_version": None::<String>
+        })
+    }
+
     #[test]
     fn test_get() -> Result<(), ReplicateError> {
         let server = MockServer::start();
@@ -136,6 +576,136 @@ mod tests {
         // Ensure the mocks were called as expected
         get_mock.assert();
 
+        Ok(())
+    }
+    #[test]
+    fn test_create_update_delete() -> Result<(), ReplicateError> {
+        let server = MockServer::start();
+
+        let create_mock = server.mock(|when, then| {
+            when.method(POST).path("/models").json_body_obj(&json!({
+                "owner": "acme",
+                "name": "wombat-upscaler",
+                "visibility": "private",
+                "hardware": "gpu-t4",
+            }));
+            then.status(201).json_body_obj(&model_json(None));
+        });
+        let update_mock = server.mock(|when, then| {
+            when.method(PATCH)
+                .path("/models/acme/wombat-upscaler")
+                .json_body_obj(&json!({"description": "Upscale portraits of wombats"}));
+            then.status(200)
+                .json_body_obj(&model_json(Some("Upscale portraits of wombats")));
+        });
+        let delete_version_mock = server.mock(|when, then| {
+            when.method(DELETE)
+                .path("/models/acme/wombat-upscaler/versions/5797a99edc939ea0e9242d5e8c9cb3bc7d125b1eac21bda852e5cb79ede2cd9b");
+            then.status(202);
+        });
+        let delete_mock = server.mock(|when, then| {
+            when.method(DELETE).path("/models/acme/wombat-upscaler");
+            then.status(204);
+        });
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: server.base_url(),
+            ..Config::default()
+        };
+        let replicate = Replicate::new(config);
+
+        let model = replicate.models.create(ModelOptions::new(
+            "acme",
+            "wombat-upscaler",
+            ModelVisibility::Private,
+            "gpu-t4",
+        ))?;
+        assert_eq!(model.visibility, ModelVisibility::Private);
+        assert_eq!(model.description, None);
+
+        let model = replicate.models.update(
+            "acme",
+            "wombat-upscaler",
+            ModelUpdate {
+                description: Some(String::from("Upscale portraits of wombats")),
+                ..ModelUpdate::default()
+            },
+        )?;
+        assert_eq!(
+            model.description.as_deref(),
+            Some("Upscale portraits of wombats")
+        );
+
+        replicate.models.versions.delete(
+            "acme",
+            "wombat-upscaler",
+            "5797a99edc939ea0e9242d5e8c9cb3bc7d125b1eac21bda852e5cb79ede2cd9b",
+        )?;
+        replicate.models.delete("acme", "wombat-upscaler")?;
+
+        // Ensure the mocks were called as expected
+        create_mock.assert();
+        update_mock.assert();
+        delete_version_mock.assert();
+        delete_mock.assert();
+
+        Ok(())
+    }
+    #[test]
+    fn test_search() -> Result<(), ReplicateError> {
+        let server = MockServer::start();
+
+        let next = format!(
+            "{}/models?sort_by=model_created_at&sort_direction=desc&cursor=cD0yMDIz",
+            server.base_url()
+        );
+        let second_mock = server.mock(|when, then| {
+            when.path("/models")
+                .query_param("cursor", "cD0yMDIz")
+                .header("content-type", "text/plain")
+                .body("wombat")
+                .matches(|request| request.method == "QUERY");
+            then.status(200).json_body_obj(&json!({
+                "next": None::<String>,
+                "previous": None::<String>,
+                "results": [model_json(None), model_json(None)],
+            }));
+        });
+        let first_mock = server.mock(|when, then| {
+            when.path("/models")
+                .query_param("sort_by", "model_created_at")
+                .query_param("sort_direction", "desc")
+                .body("wombat")
+                .matches(|request| request.method == "QUERY");
+            then.status(200).json_body_obj(&json!({
+                "next": next,
+                "previous": None::<String>,
+                "results": [model_json(None), model_json(None)],
+            }));
+        });
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: server.base_url(),
+            ..Config::default()
+        };
+        let replicate = Replicate::new(config);
+
+        let options = SearchOptions::new()
+            .sort_by(ModelSortField::ModelCreatedAt)
+            .sort_direction(SortDirection::Desc)
+            .limit(3);
+        let models = replicate
+            .models
+            .search_with_options("wombat", &options)
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(models.len(), 3);
+
+        // Ensure the mocks were called as expected
+        first_mock.assert();
+        second_mock.assert();
+
         Ok(())
     }
 }
@@ -0,0 +1,220 @@
+//! Used to reference the model to run, by name, by version or by version id alone.
+//!
+//! Every function running a model, e.g. [`Replicate::run`](crate::Replicate::run) or [`Prediction::create`](crate::prediction::Prediction::create),
+//! parses its model string into a [`ModelRef`], which decides the endpoint creating the prediction:
+//! * `{owner}/{name}` runs the latest version of an official model, with `POST /models/{owner}/{name}/predictions`.
+//! * `{owner}/{name}:{version_id}` and `{version_id}` run the given version, with `POST /predictions`.
+//!   A version id alone must be the 64 hexadecimal characters of a version hash, so that a misspelled model is not sent as a version.
+//!
+//! # Example
+//!
+//! ```
+//! use replicate_rust::model_ref::ModelRef;
+//!
+//! let model: ModelRef = "meta/meta-llama-3-8b-instruct".parse()?;
+//! assert_eq!(model.owner(), Some("meta"));
+//! assert_eq!(model.version_id(), None);
+//!
+//! let model: ModelRef = "replicate/hello-world:5c7d5dc6dd8bf75c1acaa8565735e7986bc5b66206b55cca93cb72c9bf15ccaa".parse()?;
+//! assert_eq!(model.name(), Some("hello-world"));
+//! assert_eq!(
+//!     model.version_id(),
+//!     Some("5c7d5dc6dd8bf75c1acaa8565735e7986bc5b66206b55cca93cb72c9bf15ccaa")
+//! );
+//! # Ok::<(), replicate_rust::errors::ReplicateError>(())
+//! ```
+
+use std::{fmt, str::FromStr};
+
+use crate::errors::ReplicateError;
+
+/// Reference to the model to run, parsed from `{owner}/{name}`, `{owner}/{name}:{version_id}` or `{version_id}`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ModelRef {
+    /// A model by name, running its latest version. Only official models can be run this way.
+    Model {
+        /// Owner of the model.
+        owner: String,
+        /// Name of the model.
+        name: String,
+    },
+
+    /// A version of a model.
+    Version {
+        /// Owner of the model.
+        owner: String,
+        /// Name of the model.
+        name: String,
+        /// Id of the version.
+        version_id: String,
+    },
+
+    /// A version id alone, without its model.
+    VersionId(String),
+}
+
+impl ModelRef {
+    /// Owner of the model, unless only the version id is known.
+    pub fn owner(&self) -> Option<&str> {
+        match self {
+            Self::Model { owner, .. } | Self::Version { owner, .. } => Some(owner),
+            Self::VersionId(_) => None,
+        }
+    }
+
+    /// Name of the model, unless only the version id is known.
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Self::Model { name, .. } | Self::Version { name, .. } => Some(name),
+            Self::VersionId(_) => None,
+        }
+    }
+
+    /// Id of the version, unless the model is referenced by name.
+    pub fn version_id(&self) -> Option<&str> {
+        match self {
+            Self::Model { .. } => None,
+            Self::Version { version_id, .. } | Self::VersionId(version_id) => Some(version_id),
+        }
+    }
+
+    /// Path of the endpoint creating the predictions of the model.
+    pub(crate) fn predictions_path(&self) -> String {
+        match self {
+            Self::Model { owner, name } => format!("/models/{}/{}/predictions", owner, name),
+            Self::Version { .. } | Self::VersionId(_) => String::from("/predictions"),
+        }
+    }
+}
+
+/// Whether the part of a model reference is not empty, and only made of the characters allowed by the API.
+fn is_valid_part(part: &str) -> bool {
+    !part.is_empty()
+        && part
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Whether the string looks like a version hash, i.e. 64 hexadecimal characters.
+fn is_version_hash(version_id: &str) -> bool {
+    version_id.len() == 64 && version_id.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+impl FromStr for ModelRef {
+    type Err = ReplicateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ReplicateError::InvalidVersionString(s.to_string());
+
+        let (model, version_id) = match s.split_once(':') {
+            Some((model, version_id)) => (model, Some(version_id)),
+            None => (s, None),
+        };
+
+        if let Some(version_id) = version_id {
+            if !is_valid_part(version_id) {
+                return Err(invalid());
+            }
+        }
+
+        let (owner, name) = match model.split_once('/') {
+            Some(parts) => parts,
+            // Without a slash, the string is a version id alone.
+            None if version_id.is_none() && is_version_hash(model) => {
+                return Ok(Self::VersionId(model.to_string()))
+            }
+            None => return Err(invalid()),
+        };
+        if !is_valid_part(owner) || !is_valid_part(name) {
+            return Err(invalid());
+        }
+
+        let (owner, name) = (owner.to_string(), name.to_string());
+        Ok(match version_id {
+            Some(version_id) => Self::Version {
+                owner,
+                name,
+                version_id: version_id.to_string(),
+            },
+            None => Self::Model { owner, name },
+        })
+    }
+}
+
+impl fmt::Display for ModelRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Model { owner, name } => write!(f, "{}/{}", owner, name),
+            Self::Version {
+                owner,
+                name,
+                version_id,
+            } => write!(f, "{}/{}:{}", owner, name, version_id),
+            Self::VersionId(version_id) => write!(f, "{}", version_id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() -> Result<(), ReplicateError> {
+        assert_eq!(
+            "meta/meta-llama-3-8b-instruct".parse::<ModelRef>()?,
+            ModelRef::Model {
+                owner: String::from("meta"),
+                name: String::from("meta-llama-3-8b-instruct"),
+            }
+        );
+        assert_eq!(
+            "replicate/hello-world:5c7d5dc6".parse::<ModelRef>()?,
+            ModelRef::Version {
+                owner: String::from("replicate"),
+                name: String::from("hello-world"),
+                version_id: String::from("5c7d5dc6"),
+            }
+        );
+        assert_eq!(
+            "5c7d5dc6dd8bf75c1acaa8565735e7986bc5b66206b55cca93cb72c9bf15ccaa"
+                .parse::<ModelRef>()?,
+            ModelRef::VersionId(String::from(
+                "5c7d5dc6dd8bf75c1acaa8565735e7986bc5b66206b55cca93cb72c9bf15ccaa"
+            ))
+        );
+
+        for model in [
+            "replicate/hello-world:5c7d5dc6",
+            "meta/meta-llama-3-8b-instruct",
+            "5c7d5dc6dd8bf75c1acaa8565735e7986bc5b66206b55cca93cb72c9bf15ccaa",
+        ] {
+            assert_eq!(model.parse::<ModelRef>()?.to_string(), model);
+        }
+
+        for invalid in [
+            "",
+            "replicate/",
+            "/hello-world",
+            "replicate/hello-world:",
+            "hello-world:5c7d5dc6",
+            "replicate/hello/world",
+            "replicate/hello world",
+            // Version ids alone must be version hashes.
+            "hello-world",
+            "5c7d5dc6",
+            "5c7d5dc6dd8bf75c1acaa8565735e7986bc5b66206b55cca93cb72c9bf15ccaz",
+        ] {
+            assert!(
+                matches!(
+                    invalid.parse::<ModelRef>(),
+                    Err(ReplicateError::InvalidVersionString(_))
+                ),
+                "{:?} should be invalid",
+                invalid
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,312 @@
+//! Async client for the Replicate API. Requires the `async` feature.
+//!
+//! Mirrors the blocking [`Replicate`](crate::Replicate) client, but every method returns a future and can be awaited
+//! from an async runtime such as [tokio](https://tokio.rs).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use replicate_rust::{config::Config, nonblocking::Replicate};
+//!
+//! # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+//! let config = Config::default();
+//! let replicate = Replicate::new(config);
+//!
+//! // Construct the inputs.
+//! let mut inputs = std::collections::HashMap::new();
+//! inputs.insert("prompt", "a  19th century portrait of a wombat gentleman");
+//!
+//! let version = "stability-ai/stable-diffusion:27b93a2413e7f36cd83da926f3656280b2931564ff050bf9575f1fdf9bcd7478";
+//!
+//! // Run the model.
+//! let result = replicate.run(version, inputs).await?;
+//!
+//! // Print the result.
+//! println!("{:?}", result.output);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+
+use crate::{
+    account_request,
+    api_definitions::{GetAccount, GetPrediction},
+    check_credentials,
+    config::Config,
+    errors::ReplicateError,
+};
+
+use self::{
+    collection::Collection, deployment::Deployment, files::Files, model::Model,
+    prediction::Prediction, training::Training, webhooks::Webhooks,
+};
+
+pub mod collection;
+pub mod deployment;
+pub mod files;
+pub mod model;
+pub mod pagination;
+pub mod prediction;
+pub mod prediction_client;
+pub mod stream;
+pub mod training;
+pub mod version;
+pub mod webhooks;
+
+/// Async Rust Client for interacting with the [Replicate API](https://replicate.com/docs/api/). Supports the same endpoints as the blocking [`Replicate`](crate::Replicate) client.
+#[derive(Clone, Debug)]
+pub struct Replicate {
+    /// Holds a reference to a Config struct.
+    config: Config,
+
+    /// Holds a reference to a Prediction struct. Use to run inference given model inputs and version.
+    pub predictions: Prediction,
+
+    /// Holds a reference to a Model struct. Use to get information about a model.
+    pub models: Model,
+
+    /// Holds a reference to a Training struct. Use to create a new training run.
+    pub trainings: Training,
+
+    /// Holds a reference to a Collection struct. Use to get and list model collections present in Replicate.
+    pub collections: Collection,
+
+    /// Holds a reference to a Deployment struct. Use to manage deployments and run predictions on them.
+    pub deployments: Deployment,
+
+    /// Holds a reference to a Files struct. Use to upload files and manage the uploaded files.
+    pub files: Files,
+
+    /// Holds a reference to a Webhooks struct. Use to get the secret signing the webhooks.
+    pub webhooks: Webhooks,
+}
+
+impl Replicate {
+    /// Create a new async Replicate client.
+    ///
+    /// The transport is built once from the config (unless one was injected with [`ConfigBuilder::async_transport`](crate::config::ConfigBuilder::async_transport)) and shared by all the endpoints.
+    ///
+    /// # Panics
+    /// Panics if no API token is set, or if the HTTP client cannot be built from the config, e.g. if the TLS backend cannot be initialized.
+    /// Use [`try_new`](Self::try_new) to handle these errors instead.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use replicate_rust::{config::Config, nonblocking::Replicate};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    /// ```
+    pub fn new(config: Config) -> Self {
+        match Self::try_new(config) {
+            Ok(replicate) => replicate,
+            Err(error) => panic!("failed to create the Replicate client: {}", error),
+        }
+    }
+
+    /// Create a new async Replicate client, returning an error instead of panicking.
+    ///
+    /// Returns a [`ReplicateError::MissingCredentials`] error if no API token is set.
+    /// The token itself is not checked against the API, use [`validate_token`](Self::validate_token) for that.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use replicate_rust::{config::Config, nonblocking::Replicate};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::try_new(config)?;
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn try_new(mut config: Config) -> Result<Self, ReplicateError> {
+        // Check if auth is set.
+        config.check_auth()?;
+
+        // Build the transport shared by all the endpoints.
+        config.async_transport = Some(config.async_transport()?);
+
+        let predictions = Prediction::new(config.clone());
+        let models = Model::new(config.clone());
+        let trainings = Training::new(config.clone());
+        let collections = Collection::new(config.clone());
+        let deployments = Deployment::new(config.clone());
+        let files = Files::new(config.clone());
+        let webhooks = Webhooks::new(config.clone());
+
+        Ok(Self {
+            config,
+            predictions,
+            models,
+            trainings,
+            collections,
+            deployments,
+            files,
+            webhooks,
+        })
+    }
+
+    /// Get the account the API token belongs to.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use replicate_rust::{config::Config, nonblocking::Replicate};
+    ///
+    /// # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let account = replicate.account().await?;
+    /// println!("Username : {}", account.username);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn account(&self) -> Result<GetAccount, ReplicateError> {
+        self.config
+            .async_send_json(account_request(&self.config))
+            .await
+    }
+
+    /// Check the API token against the API, returning the account it belongs to.
+    ///
+    /// Returns a [`ReplicateError::InvalidCredentials`] error if the API rejects the token.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use replicate_rust::{config::Config, nonblocking::Replicate};
+    ///
+    /// # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+    /// let config = Config::default();
+    /// let replicate = Replicate::try_new(config)?;
+    /// replicate.validate_token().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn validate_token(&self) -> Result<GetAccount, ReplicateError> {
+        check_credentials(self.account().await)
+    }
+
+    /// Run a model with the given inputs and wait for it to complete.
+    /// # Arguments
+    /// * `version` - The model to run, as `{owner}/{name}:{version_id}`, `{owner}/{name}` or a version id. See [`model_ref::ModelRef`](crate::model_ref::ModelRef).
+    /// * `inputs` - The inputs to the model in the form of a HashMap.
+    /// # Example
+    /// ```no_run
+    /// use replicate_rust::{config::Config, nonblocking::Replicate};
+    ///
+    /// # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// // Construct the inputs.
+    /// let mut inputs = std::collections::HashMap::new();
+    /// inputs.insert("prompt", "a  19th century portrait of a wombat gentleman");
+    ///
+    /// let version = "stability-ai/stable-diffusion:27b93a2413e7f36cd83da926f3656280b2931564ff050bf9575f1fdf9bcd7478";
+    ///
+    /// // Run the model.
+    /// let result = replicate.run(version, inputs).await?;
+    ///
+    /// println!("Output : {:?}", result.output);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn run<K: serde::Serialize, V: serde::Serialize>(
+        &self,
+        version: &str,
+        inputs: HashMap<K, V>,
+    ) -> Result<GetPrediction, ReplicateError> {
+        let prediction = Prediction::new(self.config.clone())
+            .create(version, inputs)
+            .await?;
+
+        prediction.wait().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use httpmock::{
+        Method::{GET, POST},
+        MockServer,
+    };
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_run() -> Result<(), ReplicateError> {
+        let server = MockServer::start_async().await;
+
+        // Mock the POST response
+        let post_mock = server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .path("/predictions")
+                    .json_body_obj(&json!({
+                        "version": "v1",
+                        "input": {"text": "world"}
+                    }));
+                then.status(200).json_body_obj(&json!({
+                    "id": "p1",
+                    "version": "v1",
+                    "urls": {
+                        "get": format!("{}/predictions/p1", server.base_url()),
+                        "cancel": format!("{}/predictions/p1", server.base_url()),
+                    },
+                    "created_at": "2022-04-26T20:00:40.658234Z",
+                    "completed_at": "2022-04-26T20:02:27.648305Z",
+                    "source": "api",
+                    "status": "processing",
+                    "input": {"text": "world"},
+                    "output": None::<String>,
+                    "error": None::<String>,
+                    "logs": None::<String>,
+                }));
+            })
+            .await;
+
+        // Mock the GET response
+        let get_mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/predictions/p1");
+                then.status(200).json_body_obj(&json!({
+                    "id": "p1",
+                    "version": "v1",
+                    "urls": {
+                        "get": format!("{}/predictions/p1", server.base_url()),
+                        "cancel": format!("{}/predictions/p1", server.base_url()),
+                    },
+                    "created_at": "2022-04-26T20:00:40.658234Z",
+                    "completed_at": "2022-04-26T20:02:27.648305Z",
+                    "source": "api",
+                    "status": "succeeded",
+                    "input": {"text": "world"},
+                    "output": "hello world",
+                    "error": None::<String>,
+                    "logs": "",
+                }));
+            })
+            .await;
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: server.base_url(),
+            ..Config::default()
+        };
+        let replicate = Replicate::new(config);
+
+        let mut inputs = std::collections::HashMap::new();
+        inputs.insert("text", "world");
+
+        let result = replicate.run("test/model:v1", inputs).await?;
+
+        // Assert that the returned value is correct
+        assert_eq!(result.output, Some(serde_json::to_value("hello world")?));
+
+        // Ensure the mocks were called as expected
+        post_mock.assert_async().await;
+        get_mock.assert_async().await;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+//! Async counterpart of [`crate::collection`]. Used to interact with the [Collection Endpoints](https://replicate.com/docs/reference/http#collections.get).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use replicate_rust::{config::Config, nonblocking::Replicate};
+//!
+//! # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+//! let config = Config::default();
+//! let replicate = Replicate::new(config);
+//!
+//! let collections = replicate.collections.get("audio-generation").await?;
+//! println!("Collection : {:?}", collections);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    api_definitions::{GetCollectionModels, ListCollectionModels, ListCollectionModelsItem},
+    collection::{get_request, list_request},
+    errors::ReplicateError,
+};
+
+use super::pagination::Paginator;
+
+/// Used to interact with the [Collection Endpoints](https://replicate.com/docs/reference/http#collections.get).
+#[derive(Clone, Debug)]
+pub struct Collection {
+    /// Holds a reference to a Config struct, which contains the base url,  auth token among other settings.
+    pub parent: crate::config::Config,
+}
+
+impl Collection {
+    /// Create a new Collection struct.
+    pub fn new(rep: crate::config::Config) -> Self {
+        Self { parent: rep }
+    }
+
+    /// Return a copy of this endpoint using the given policy to retry its requests, e.g. to override the client policy for a single call.
+    pub fn with_retry_policy(&self, policy: crate::retry::RetryPolicy) -> Self {
+        let mut config = self.parent.clone();
+        config.retry_policy = policy;
+        Self::new(config)
+    }
+
+    /// Get a collection by slug.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{config::Config, nonblocking::Replicate};
+    ///
+    /// # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let collections = replicate.collections.get("audio-generation").await?;
+    /// println!("Collections : {:?}", collections);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get(&self, collection_slug: &str) -> Result<GetCollectionModels, ReplicateError> {
+        self.parent
+            .async_send_json(get_request(&self.parent, collection_slug))
+            .await
+    }
+
+    /// List all collections present in Replicate.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{config::Config, nonblocking::Replicate};
+    ///
+    /// # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let collections = replicate.collections.list().await?;
+    /// println!("Collections : {:?}", collections);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list(&self) -> Result<ListCollectionModels, ReplicateError> {
+        self.parent
+            .async_send_json(list_request(&self.parent))
+            .await
+    }
+
+    /// Iterate over all the collections, following the pages lazily. See [`Paginator`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use futures_util::TryStreamExt;
+    /// use replicate_rust::{config::Config, nonblocking::Replicate};
+    ///
+    /// # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let collections: Vec<_> = replicate.collections.list_all().try_collect().await?;
+    /// println!("Collections : {:?}", collections);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn list_all(&self) -> Paginator<ListCollectionModelsItem> {
+        Paginator::new(self.parent.clone(), list_request(&self.parent).url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{config::Config, errors::ReplicateError, nonblocking::Replicate};
+
+    use httpmock::{Method::GET, MockServer};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_list() -> Result<(), ReplicateError> {
+        let server = MockServer::start_async().await;
+
+        let get_mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/collections");
+                then.status(200).json_body_obj(&json!({
+                    "results": [
+                        {
+                            "name": "Super resolution",
+                            "slug": "super-resolution",
+                            "description": "Upscaling models that create high-quality images from low-quality images.",
+                        },
+                    ],
+                    "next": None::<String>,
+                    "previous": None::<String>,
+                }));
+            })
+            .await;
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: server.base_url(),
+            ..Config::default()
+        };
+        let replicate = Replicate::new(config);
+
+        let result = replicate.collections.list().await?;
+
+        // Assert that the returned value is correct
+        assert_eq!(result.results.len(), 1);
+
+        // Ensure the mocks were called as expected
+        get_mock.assert_async().await;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,245 @@
+//! Async counterpart of [`crate::deployment`]. Used to interact with the [Deployment Endpoints](https://replicate.com/docs/reference/http#deployments.get).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use replicate_rust::{config::Config, nonblocking::Replicate};
+//!
+//! # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+//! let config = Config::default();
+//! let replicate = Replicate::new(config);
+//!
+//! // Construct the inputs.
+//! let mut inputs = std::collections::HashMap::new();
+//! inputs.insert("prompt", "a  19th century portrait of a wombat gentleman");
+//!
+//! // Run the model of the deployment.
+//! let result = replicate
+//!     .deployments
+//!     .predictions
+//!     .create("acme", "image-upscaler", inputs)
+//!     .await?
+//!     .wait()
+//!     .await?;
+//!
+//! println!("Result : {:?}", result.output);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+
+use crate::{
+    api_definitions::{GetDeployment, ListDeployments},
+    deployment::{
+        create_prediction_request, create_request, delete_request, get_request, list_request,
+        update_request, DeploymentOptions, DeploymentUpdate,
+    },
+    errors::ReplicateError,
+    prediction::PredictionOptions,
+};
+
+use super::{pagination::Paginator, prediction_client::PredictionClient};
+
+/// Used to interact with the [Deployment Endpoints](https://replicate.com/docs/reference/http#deployments.get).
+#[derive(Clone, Debug)]
+pub struct Deployment {
+    /// Holds a reference to a Configuration struct, which contains the base url,  auth token among other settings.
+    pub parent: crate::config::Config,
+
+    /// Holds a reference to a DeploymentPredictions struct. Use to run predictions on a deployment.
+    pub predictions: DeploymentPredictions,
+}
+
+impl Deployment {
+    /// Create a new Deployment struct.
+    pub fn new(rep: crate::config::Config) -> Self {
+        let predictions = DeploymentPredictions::new(rep.clone());
+        Self {
+            parent: rep,
+            predictions,
+        }
+    }
+
+    /// Return a copy of this endpoint using the given policy to retry its requests, e.g. to override the client policy for a single call.
+    pub fn with_retry_policy(&self, policy: crate::retry::RetryPolicy) -> Self {
+        let mut config = self.parent.clone();
+        config.retry_policy = policy;
+        Self::new(config)
+    }
+
+    /// Get a deployment, along with its current release.
+    pub async fn get(
+        &self,
+        deployment_owner: &str,
+        deployment_name: &str,
+    ) -> Result<GetDeployment, ReplicateError> {
+        self.parent
+            .async_send_json(get_request(&self.parent, deployment_owner, deployment_name))
+            .await
+    }
+
+    /// List the deployments of the user.
+    pub async fn list(&self) -> Result<ListDeployments, ReplicateError> {
+        self.parent
+            .async_send_json(list_request(&self.parent))
+            .await
+    }
+
+    /// Stream all the deployments, following the pages lazily. See [`Paginator`].
+    pub fn list_all(&self) -> Paginator<GetDeployment> {
+        Paginator::new(self.parent.clone(), list_request(&self.parent).url)
+    }
+
+    /// Create a deployment, owned by the user.
+    pub async fn create(
+        &self,
+        options: DeploymentOptions,
+    ) -> Result<GetDeployment, ReplicateError> {
+        self.parent
+            .async_send_json(create_request(&self.parent, &options)?)
+            .await
+    }
+
+    /// Update a deployment, creating a new release.
+    pub async fn update(
+        &self,
+        deployment_owner: &str,
+        deployment_name: &str,
+        options: DeploymentUpdate,
+    ) -> Result<GetDeployment, ReplicateError> {
+        self.parent
+            .async_send_json(update_request(
+                &self.parent,
+                deployment_owner,
+                deployment_name,
+                &options,
+            )?)
+            .await
+    }
+
+    /// Delete a deployment. Only deployments that have been offline and unused for some time can be deleted.
+    pub async fn delete(
+        &self,
+        deployment_owner: &str,
+        deployment_name: &str,
+    ) -> Result<(), ReplicateError> {
+        self.parent
+            .async_send(delete_request(
+                &self.parent,
+                deployment_owner,
+                deployment_name,
+            ))
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Used to run predictions on a deployment, with the [Deployment Prediction Endpoint](https://replicate.com/docs/reference/http#deployments.predictions.create).
+#[derive(Clone, Debug)]
+pub struct DeploymentPredictions {
+    /// Holds a reference to a Configuration struct, which contains the base url,  auth token among other settings.
+    pub parent: crate::config::Config,
+}
+
+impl DeploymentPredictions {
+    /// Create a new DeploymentPredictions struct.
+    pub fn new(rep: crate::config::Config) -> Self {
+        Self { parent: rep }
+    }
+
+    /// Create a new prediction running the current release of a deployment.
+    /// Returns a [`PredictionClient`], to reload, cancel or wait for the prediction like any other.
+    pub async fn create<K: serde::Serialize, V: serde::ser::Serialize>(
+        &self,
+        deployment_owner: &str,
+        deployment_name: &str,
+        inputs: HashMap<K, V>,
+    ) -> Result<PredictionClient, ReplicateError> {
+        self.create_with_options(
+            deployment_owner,
+            deployment_name,
+            inputs,
+            PredictionOptions::default(),
+        )
+        .await
+    }
+
+    /// Create a new prediction running the current release of a deployment, with options, e.g. to stream its output.
+    pub async fn create_with_options<K: serde::Serialize, V: serde::ser::Serialize>(
+        &self,
+        deployment_owner: &str,
+        deployment_name: &str,
+        inputs: HashMap<K, V>,
+        options: PredictionOptions,
+    ) -> Result<PredictionClient, ReplicateError> {
+        let request = create_prediction_request(
+            &self.parent,
+            deployment_owner,
+            deployment_name,
+            inputs,
+            options,
+        )?;
+
+        PredictionClient::send_create(self.parent.clone(), request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{config::Config, nonblocking::Replicate};
+
+    use super::*;
+    use httpmock::{Method::POST, MockServer};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_create_prediction() -> Result<(), ReplicateError> {
+        let server = MockServer::start_async().await;
+
+        let post_mock = server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .path("/deployments/acme/image-upscaler/predictions")
+                    .json_body_obj(&json!({"input": {"text": "Alice"}}));
+                then.status(201).json_body_obj(&json!({
+                    "id": "ufawqhfynnddngldkgtslldrkq",
+                    "version": "42fed1c4974146d4d2414e2be2c5277c7fcf05fcc3a73abf41610695738c1d7b",
+                    "urls": {
+                        "get": "https://api.replicate.com/v1/predictions/ufawqhfynnddngldkgtslldrkq",
+                        "cancel": "https://api.replicate.com/v1/predictions/ufawqhfynnddngldkgtslldrkq/cancel",
+                    },
+                    "created_at": "2022-04-26T22:13:06.224088Z",
+                    "status": "starting",
+                    "input": {"text": "Alice"},
+                    "output": None::<String>,
+                    "error": None::<String>,
+                    "logs": None::<String>,
+                }));
+            })
+            .await;
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: server.base_url(),
+            ..Config::default()
+        };
+        let replicate = Replicate::new(config);
+
+        let mut input = HashMap::new();
+        input.insert("text", "Alice");
+
+        let prediction = replicate
+            .deployments
+            .predictions
+            .create("acme", "image-upscaler", input)
+            .await?;
+        assert_eq!(prediction.id, "ufawqhfynnddngldkgtslldrkq");
+
+        // Ensure the mocks were called as expected
+        post_mock.assert_async().await;
+
+        Ok(())
+    }
+}
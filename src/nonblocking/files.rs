@@ -0,0 +1,213 @@
+//! Async counterpart of [`crate::files`]. Used to interact with the [File Endpoints](https://replicate.com/docs/reference/http#files.create).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use replicate_rust::{config::Config, file_input::FileInput, nonblocking::Replicate};
+//!
+//! # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+//! let config = Config::default();
+//! let replicate = Replicate::new(config);
+//!
+//! let file = replicate.files.create(FileInput::path("wombat.png")).await?;
+//! println!("File : {:?}", file.urls.get);
+//! # Ok(())
+//! # }
+//! ```
+
+use serde::Serialize;
+use tokio::io::AsyncWrite;
+
+use crate::{
+    api_definitions::{FileObject, ListFiles},
+    download,
+    errors::ReplicateError,
+    file_input::FileInput,
+    files::{create_request, delete_request, download_request, get_request, list_request},
+};
+
+use super::pagination::Paginator;
+
+/// Used to interact with the [File Endpoints](https://replicate.com/docs/reference/http#files.create).
+#[derive(Clone, Debug)]
+pub struct Files {
+    /// Holds a reference to a Config struct, which contains the base url,  auth token among other settings.
+    pub parent: crate::config::Config,
+}
+
+impl Files {
+    /// Create a new Files struct.
+    pub fn new(rep: crate::config::Config) -> Self {
+        Self { parent: rep }
+    }
+
+    /// Return a copy of this endpoint using the given policy to retry its requests, e.g. to override the client policy for a single call.
+    pub fn with_retry_policy(&self, policy: crate::retry::RetryPolicy) -> Self {
+        let mut config = self.parent.clone();
+        config.retry_policy = policy;
+        Self::new(config)
+    }
+
+    /// Upload a file.
+    pub async fn create(&self, file: FileInput) -> Result<FileObject, ReplicateError> {
+        let request = create_request(&self.parent, &file.async_read().await?, None)?;
+
+        self.parent.async_send_json(request).await
+    }
+
+    /// Upload a file along with metadata, returned as is by the API in [`FileObject::metadata`].
+    pub async fn create_with_metadata<M: Serialize + ?Sized>(
+        &self,
+        file: FileInput,
+        metadata: &M,
+    ) -> Result<FileObject, ReplicateError> {
+        let metadata = serde_json::to_string(metadata)?;
+        let request = create_request(&self.parent, &file.async_read().await?, Some(&metadata))?;
+
+        self.parent.async_send_json(request).await
+    }
+
+    /// List the files uploaded by the user, most recent first.
+    pub async fn list(&self) -> Result<ListFiles, ReplicateError> {
+        self.parent
+            .async_send_json(list_request(&self.parent))
+            .await
+    }
+
+    /// Stream all the files, following the pages lazily. See [`Paginator`].
+    pub fn list_all(&self) -> Paginator<FileObject> {
+        Paginator::new(self.parent.clone(), list_request(&self.parent).url)
+    }
+
+    /// Get a file by id.
+    pub async fn get(&self, id: &str) -> Result<FileObject, ReplicateError> {
+        self.parent
+            .async_send_json(get_request(&self.parent, id))
+            .await
+    }
+
+    /// Download the content of a file into a writer, returning the number of bytes written.
+    pub async fn download(
+        &self,
+        id: &str,
+        writer: &mut (impl AsyncWrite + Unpin),
+    ) -> Result<u64, ReplicateError> {
+        let request = download_request(&self.parent, id);
+        let url = request.url.clone();
+
+        let mut response = self.parent.async_send_streaming(request).await?;
+        let written = download::async_copy(&mut response.body, writer).await?;
+        download::check_size(&url, download::expected_size(&response.headers, 0), written)?;
+
+        Ok(written)
+    }
+
+    /// Delete a file.
+    pub async fn delete(&self, id: &str) -> Result<(), ReplicateError> {
+        self.parent
+            .async_send(delete_request(&self.parent, id))
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{config::Config, nonblocking::Replicate};
+
+    use super::*;
+    use crate::file_input::FileInput;
+    use httpmock::{
+        Method::{DELETE, GET, POST},
+        MockServer,
+    };
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_create_from_reader() -> Result<(), ReplicateError> {
+        let server = MockServer::start_async().await;
+
+        let post_mock = server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .path("/files")
+                    .body_contains("name=\"content\"; filename=\"hello.txt\"")
+                    .body_contains("hello Alice");
+                then.status(201).json_body_obj(&json!({
+                    "id": "cneqzikepnug6xezperrr4z55o",
+                    "name": "hello.txt",
+                    "content_type": "text/plain",
+                    "size": 11,
+                    "etag": None::<String>,
+                    "checksums": {"sha256": None::<String>, "md5": None::<String>},
+                    "created_at": "2024-07-18T14:41:17.578761Z",
+                    "expires_at": None::<String>,
+                    "urls": {"get": "https://api.replicate.com/v1/files/cneqzikepnug6xezperrr4z55o"},
+                }));
+            })
+            .await;
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: server.base_url(),
+            ..Config::default()
+        };
+        let replicate = Replicate::new(config);
+
+        // The reader is read on the blocking thread pool.
+        let file = replicate
+            .files
+            .create(FileInput::reader(&b"hello Alice"[..]).file_name("hello.txt"))
+            .await?;
+        assert_eq!(file.size, 11);
+
+        // Ensure the mocks were called as expected
+        post_mock.assert_async().await;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_download_and_delete() -> Result<(), ReplicateError> {
+        let server = MockServer::start_async().await;
+
+        let download_mock = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/files/cneqzikepnug6xezperrr4z55o/download");
+                then.status(200).body("hello Alice");
+            })
+            .await;
+        let delete_mock = server
+            .mock_async(|when, then| {
+                when.method(DELETE)
+                    .path("/files/cneqzikepnug6xezperrr4z55o");
+                then.status(204);
+            })
+            .await;
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: server.base_url(),
+            ..Config::default()
+        };
+        let replicate = Replicate::new(config);
+
+        let mut content = Vec::new();
+        let written = replicate
+            .files
+            .download("cneqzikepnug6xezperrr4z55o", &mut content)
+            .await?;
+        assert_eq!(written, 11);
+        assert_eq!(content, b"hello Alice");
+
+        replicate.files.delete("cneqzikepnug6xezperrr4z55o").await?;
+
+        // Ensure the mocks were called as expected
+        download_mock.assert_async().await;
+        delete_mock.assert_async().await;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,239 @@
+//! Async counterpart of [`crate::model`]. Used to interact with the [Model Endpoints](https://replicate.com/docs/reference/http#models.get).
+//!
+//! # Example
+//! ```no_run
+//! use replicate_rust::{config::Config, nonblocking::Replicate};
+//!
+//! # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+//! let config = Config::default();
+//! let replicate = Replicate::new(config);
+//!
+//! let model = replicate.models.get("replicate", "hello-world").await?;
+//! println!("Model : {:?}", model);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    api_definitions::{GetModel, ListModels},
+    errors::ReplicateError,
+    model::{
+        create_request, delete_request, get_request, list_request, update_request, ModelOptions,
+        ModelUpdate, SearchOptions,
+    },
+};
+
+use super::{pagination::Paginator, version::Version};
+
+/// Used to interact with the [Model Endpoints](https://replicate.com/docs/reference/http#models.get).
+#[derive(Clone, Debug)]
+pub struct Model {
+    /// Holds a reference to a Configuration struct, which contains the base url,  auth token among other settings.
+    pub parent: crate::config::Config,
+
+    /// Holds a reference to a Version struct, which contains the functionality for interacting with the version endpoints of the Replicate API.
+    pub versions: Version,
+}
+
+impl Model {
+    /// Create a new Model struct.
+    /// # Arguments
+    /// * `rep` - The config (`crate::config::Config`) to use for authentication and communication.
+    ///
+    pub fn new(rep: crate::config::Config) -> Self {
+        let versions = Version::new(rep.clone());
+        Self {
+            parent: rep,
+            versions,
+        }
+    }
+
+    /// Return a copy of this endpoint using the given policy to retry its requests, e.g. to override the client policy for a single call.
+    pub fn with_retry_policy(&self, policy: crate::retry::RetryPolicy) -> Self {
+        let mut config = self.parent.clone();
+        config.retry_policy = policy;
+        Self::new(config)
+    }
+
+    /// Get the details of a model.
+    /// # Arguments
+    /// * `model_owner` - The owner of the model.
+    /// * `model_name` - The name of the model.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use replicate_rust::{config::Config, nonblocking::Replicate};
+    ///
+    /// # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let model = replicate.models.get("replicate", "hello-world").await?;
+    /// println!("Model : {:?}", model);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get(
+        &self,
+        model_owner: &str,
+        model_name: &str,
+    ) -> Result<GetModel, ReplicateError> {
+        self.parent
+            .async_send_json(get_request(&self.parent, model_owner, model_name))
+            .await
+    }
+
+    /// List the public models, along with the models of the user.
+    pub async fn list(&self) -> Result<ListModels, ReplicateError> {
+        self.parent
+            .async_send_json(list_request(&self.parent))
+            .await
+    }
+
+    /// Stream all the models, following the pages lazily. See [`Paginator`].
+    pub fn list_all(&self) -> Paginator<GetModel> {
+        Paginator::new(self.parent.clone(), list_request(&self.parent).url)
+    }
+
+    /// Search the public models, streaming the pages of results lazily. See [`Paginator`].
+    pub fn search(&self, query: &str) -> Paginator<GetModel> {
+        self.search_with_options(query, &SearchOptions::default())
+    }
+
+    /// Search the public models, sorting and limiting the models found. See [`crate::model::Model::search_with_options`].
+    pub fn search_with_options(&self, query: &str, options: &SearchOptions) -> Paginator<GetModel> {
+        let paginator =
+            Paginator::new(self.parent.clone(), options.url(&self.parent)).with_search(query);
+
+        match options.limit {
+            Some(limit) => paginator.limit(limit),
+            None => paginator,
+        }
+    }
+
+    /// Create a model. See [`crate::model::Model::create`].
+    pub async fn create(&self, options: ModelOptions) -> Result<GetModel, ReplicateError> {
+        self.parent
+            .async_send_json(create_request(&self.parent, &options)?)
+            .await
+    }
+
+    /// Update the details of a model.
+    pub async fn update(
+        &self,
+        model_owner: &str,
+        model_name: &str,
+        options: ModelUpdate,
+    ) -> Result<GetModel, ReplicateError> {
+        self.parent
+            .async_send_json(update_request(
+                &self.parent,
+                model_owner,
+                model_name,
+                &options,
+            )?)
+            .await
+    }
+
+    /// Delete a model. Only private models without versions can be deleted, see [`Version::delete`].
+    pub async fn delete(&self, model_owner: &str, model_name: &str) -> Result<(), ReplicateError> {
+        self.parent
+            .async_send(delete_request(&self.parent, model_owner, model_name))
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{config::Config, errors::ReplicateError, nonblocking::Replicate};
+
+    use httpmock::{
+        Method::{DELETE, GET},
+        MockServer,
+    };
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_get() -> Result<(), ReplicateError> {
+        let server = MockServer::start_async().await;
+
+        let get_mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/models/replicate/hello-world");
+                then.status(200).json_body_obj(&json!({
+                    "url": "https://replicate.com/replicate/hello-world",
+                    "owner": "replicate",
+                    "name": "hello-world",
+                    "description": "A tiny model that says hello",
+                    "visibility": "public",
+                    "github_url": "https://github.com/replicate/cog-examples",
+                    "paper_url": None::<String>,
+                    "license_url": None::<String>,
+                    "run_count": 12345,
+                    "cover_image_url": "",
+                    "default_example": {},
+                    "latest_version": {}
+                }));
+            })
+            .await;
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: server.base_url(),
+            ..Config::default()
+        };
+        let replicate = Replicate::new(config);
+
+        let result = replicate.models.get("replicate", "hello-world").await?;
+        assert_eq!(result.name, "hello-world");
+
+        // Ensure the mocks were called as expected
+        get_mock.assert_async().await;
+
+        Ok(())
+    }
+    #[tokio::test]
+    async fn test_delete() -> Result<(), ReplicateError> {
+        let server = MockServer::start_async().await;
+
+        let delete_version_mock = server
+            .mock_async(|when, then| {
+                when.method(DELETE)
+                    .path("/models/acme/wombat-upscaler/versions/5797a99edc939ea0e9242d5e8c9cb3bc7d125b1eac21bda852e5cb79ede2cd9b");
+                then.status(202);
+            })
+            .await;
+        let delete_mock = server
+            .mock_async(|when, then| {
+                when.method(DELETE).path("/models/acme/wombat-upscaler");
+                then.status(204);
+            })
+            .await;
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: server.base_url(),
+            ..Config::default()
+        };
+        let replicate = Replicate::new(config);
+
+        replicate
+            .models
+            .versions
+            .delete(
+                "acme",
+                "wombat-upscaler",
+                "5797a99edc939ea0e9242d5e8c9cb3bc7d125b1eac21bda852e5cb79ede2cd9b",
+            )
+            .await?;
+        replicate.models.delete("acme", "wombat-upscaler").await?;
+
+        // Ensure the mocks were called as expected
+        delete_version_mock.assert_async().await;
+        delete_mock.assert_async().await;
+
+        Ok(())
+    }
+}
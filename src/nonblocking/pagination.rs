@@ -0,0 +1,237 @@
+//! Async counterpart of [`crate::pagination`]. Used to go through the pages of the list endpoints, following their `next` cursors.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use futures_util::StreamExt;
+//! use replicate_rust::{config::Config, nonblocking::Replicate};
+//!
+//! # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+//! let config = Config::default();
+//! let replicate = Replicate::new(config);
+//!
+//! // Only fetches the pages needed for the first 250 predictions.
+//! let mut predictions = replicate.predictions.list_all().page_size(100).take(250);
+//! while let Some(prediction) = predictions.next().await {
+//!     println!("Prediction : {}", prediction?.id);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_util::{FutureExt, Stream};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    config::Config,
+    errors::ReplicateError,
+    pagination::{Page, Pages, ResultFilter},
+    transport::BoxFuture,
+};
+
+/// Async stream of the results of every page of a list endpoint. Created with the `list_all` methods.
+///
+/// The next page is only fetched once all the results of the current one have been returned.
+/// The stream stops after the first error, and can be resumed from [`next_cursor`](Self::next_cursor).
+pub struct Paginator<T> {
+    pages: Pages<T>,
+    pending: Option<BoxFuture<'static, Result<Page<T>, ReplicateError>>>,
+}
+
+// The pending future is boxed, and the results are never pinned.
+impl<T> Unpin for Paginator<T> {}
+
+impl<T> std::fmt::Debug for Paginator<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.pages.fmt(f)
+    }
+}
+
+impl<T: DeserializeOwned + Send + 'static> Paginator<T> {
+    /// Create a new paginator starting at the given url.
+    pub(crate) fn new(config: Config, url: String) -> Self {
+        Self {
+            pages: Pages::new(config, url),
+            pending: None,
+        }
+    }
+
+    /// Only return the results matching the predicate.
+    pub(crate) fn with_filter(mut self, filter: ResultFilter<T>) -> Self {
+        self.pages.filter = Some(filter);
+        self
+    }
+
+    /// Send the query along with the request of every page, for the search endpoint.
+    pub(crate) fn with_search(mut self, query: &str) -> Self {
+        self.pages.search = Some(query.to_string());
+        self
+    }
+
+    /// Stop after the given number of results, without fetching the pages past them.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.pages.remaining = Some(limit);
+        self
+    }
+
+    /// Ask the API for pages of the given size. This is only a hint, the API may return pages of a different size.
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.pages.page_size = Some(page_size);
+        self
+    }
+
+    /// Resume from a cursor returned by [`next_cursor`](Self::next_cursor).
+    ///
+    /// Fetching the page fails with [`ReplicateError::InvalidCursor`] if the cursor is not an url of the API.
+    pub fn cursor(mut self, cursor: impl Into<String>) -> Self {
+        self.pages.set_cursor(cursor.into());
+        self.pending = None;
+        self
+    }
+
+    /// Cursor of the next page to fetch, or `None` once the last page was fetched.
+    ///
+    /// Results of the current page that were not returned yet are not included when resuming from this cursor.
+    pub fn next_cursor(&self) -> Option<&str> {
+        self.pages.next_cursor()
+    }
+
+    /// Fetch the next page, or `None` once the last page was fetched.
+    ///
+    /// Results of the current page that were not returned by the stream yet are dropped.
+    /// When the results are filtered on the client, the page only contains the matching results, and may be empty.
+    pub async fn next_page(&mut self) -> Result<Option<Page<T>>, ReplicateError> {
+        let page = match self.pending.take() {
+            Some(pending) => pending.await?,
+            None => match self.fetch() {
+                Some(pending) => pending.await?,
+                None => return Ok(None),
+            },
+        };
+
+        Ok(Some(self.pages.receive(page)))
+    }
+
+    /// Start fetching the next page, if any.
+    fn fetch(&mut self) -> Option<BoxFuture<'static, Result<Page<T>, ReplicateError>>> {
+        let config = self.pages.config.clone();
+        let request = self.pages.next_request().transpose()?;
+
+        Some(async move { config.async_send_json(request?).await }.boxed())
+    }
+}
+
+impl<T: DeserializeOwned + Send + 'static> Stream for Paginator<T> {
+    type Item = Result<T, ReplicateError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(result) = self.pages.next_result() {
+                return Poll::Ready(Some(Ok(result)));
+            }
+
+            if self.pages.is_over() {
+                return Poll::Ready(None);
+            }
+
+            if self.pending.is_none() {
+                self.pending = self.fetch();
+            }
+
+            let Some(pending) = self.pending.as_mut() else {
+                return Poll::Ready(None);
+            };
+
+            match pending.poll_unpin(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(result) => {
+                    self.pending = None;
+                    match result {
+                        Ok(page) => {
+                            let page = self.pages.receive(page);
+                            self.pages.buffer(page.results);
+                        }
+                        Err(error) => {
+                            self.pages.failed = true;
+                            return Poll::Ready(Some(Err(error)));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nonblocking::Replicate;
+    use futures_util::{StreamExt, TryStreamExt};
+    use httpmock::{Method::GET, MockServer};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_paginate() -> Result<(), ReplicateError> {
+        let server = MockServer::start_async().await;
+
+        let second_mock = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/collections")
+                    .query_param("cursor", "2");
+                then.status(200).json_body_obj(&json!({
+                    "previous": None::<String>,
+                    "next": None::<String>,
+                    "results": [{"name": "c", "slug": "c", "description": ""}],
+                }));
+            })
+            .await;
+        let first_mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/collections");
+                then.status(200).json_body_obj(&json!({
+                    "previous": None::<String>,
+                    "next": format!("{}/collections?cursor=2", server.base_url()),
+                    "results": [
+                        {"name": "a", "slug": "a", "description": ""},
+                        {"name": "b", "slug": "b", "description": ""},
+                    ],
+                }));
+            })
+            .await;
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: server.base_url(),
+            ..Config::default()
+        };
+        let replicate = Replicate::new(config);
+
+        let first_two: Vec<_> = replicate
+            .collections
+            .list_all()
+            .take(2)
+            .try_collect()
+            .await?;
+        assert_eq!(first_two.len(), 2);
+        second_mock.assert_hits_async(0).await;
+
+        let slugs: Vec<_> = replicate
+            .collections
+            .list_all()
+            .map_ok(|collection| collection.slug)
+            .try_collect()
+            .await?;
+        assert_eq!(slugs, vec!["a", "b", "c"]);
+
+        first_mock.assert_hits_async(2).await;
+        second_mock.assert_hits_async(1).await;
+
+        Ok(())
+    }
+}
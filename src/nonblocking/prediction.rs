@@ -0,0 +1,536 @@
+//! Async counterpart of [`crate::prediction`]. Used to interact with the [Prediction Endpoints](https://replicate.com/docs/reference/http#predictions.get).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use replicate_rust::{config::Config, nonblocking::Replicate};
+//!
+//! # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+//! let config = Config::default();
+//! let replicate = Replicate::new(config);
+//!
+//! // Construct the inputs.
+//! let mut inputs = std::collections::HashMap::new();
+//! inputs.insert("prompt", "a  19th century portrait of a wombat gentleman");
+//!
+//! let version = "stability-ai/stable-diffusion:27b93a2413e7f36cd83da926f3656280b2931564ff050bf9575f1fdf9bcd7478";
+//!
+//! // Run the model.
+//! let result = replicate.predictions.create(version, inputs).await?.wait().await?;
+//!
+//! // Print the result.
+//! println!("Result : {:?}", result.output);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use reqwest::Method;
+use tokio::io::AsyncWrite;
+
+use crate::{
+    api_definitions::{GetPrediction, ListPredictions, PredictionsListItem},
+    download::{self, DownloadOptions},
+    errors::ReplicateError,
+    file_input::FileInput,
+    prediction::{get_request, list_request, PredictionFilter, PredictionOptions},
+    schema::VersionSchema,
+};
+
+use super::{pagination::Paginator, prediction_client::PredictionClient};
+
+/// Used to interact with the [Prediction Endpoints](https://replicate.com/docs/reference/http#predictions.get).
+#[derive(Clone, Debug)]
+pub struct Prediction {
+    /// Holds a reference to a Config struct. Use to get the base url, auth token among other settings.
+    pub parent: crate::config::Config,
+}
+
+impl Prediction {
+    /// Create a new Prediction struct.
+    pub fn new(rep: crate::config::Config) -> Self {
+        Self { parent: rep }
+    }
+
+    /// Return a copy of this endpoint using the given policy to retry its requests, e.g. to override the client policy for a single call.
+    pub fn with_retry_policy(&self, policy: crate::retry::RetryPolicy) -> Self {
+        let mut config = self.parent.clone();
+        config.retry_policy = policy;
+        Self::new(config)
+    }
+
+    /// Create a new prediction, by passing in the model version and inputs to PredictionClient.
+    /// The model is parsed as a [`ModelRef`](crate::model_ref::ModelRef), see [`crate::prediction::Prediction::create`].
+    /// PredictionClient contains the necessary methods to interact with the prediction such as reload, cancel and wait.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{config::Config, nonblocking::Replicate};
+    ///
+    /// # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// // Construct the inputs.
+    /// let mut inputs = std::collections::HashMap::new();
+    /// inputs.insert("prompt", "a  19th century portrait of a wombat gentleman");
+    ///
+    /// let version = "stability-ai/stable-diffusion:27b93a2413e7f36cd83da926f3656280b2931564ff050bf9575f1fdf9bcd7478";
+    ///
+    /// // Run the model.
+    /// let mut prediction = replicate.predictions.create(version, inputs).await?;
+    ///
+    /// // Wait for the prediction to complete (or fail).
+    /// println!("Prediction : {:?}", prediction.wait().await?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create<K: serde::Serialize, V: serde::ser::Serialize>(
+        &self,
+        version: &str,
+        inputs: HashMap<K, V>,
+    ) -> Result<PredictionClient, ReplicateError> {
+        PredictionClient::create(self.parent.clone(), version, inputs).await
+    }
+
+    /// Create a new prediction with the given options, e.g. to stream its output.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use futures_util::StreamExt;
+    /// use replicate_rust::{
+    ///     config::Config, nonblocking::Replicate, prediction::PredictionOptions, stream::StreamEvent,
+    /// };
+    ///
+    /// # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// // Construct the inputs.
+    /// let mut inputs = std::collections::HashMap::new();
+    /// inputs.insert("prompt", "Tell me a story");
+    ///
+    /// let version = "meta/llama-2-70b-chat:02e509c789964a7ea8736978a43525956ef40397be9033abf9fd2badfe68c9e3";
+    ///
+    /// let prediction = replicate
+    ///     .predictions
+    ///     .create_with_options(
+    ///         version,
+    ///         inputs,
+    ///         PredictionOptions {
+    ///             stream: true,
+    ///             ..Default::default()
+    ///         },
+    ///     )
+    ///     .await?;
+    ///
+    /// // Print the tokens as they are generated.
+    /// let mut stream = prediction.stream()?;
+    /// while let Some(event) = stream.next().await {
+    ///     if let StreamEvent::Output(token) = event? {
+    ///         print!("{}", token);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_with_options<K: serde::Serialize, V: serde::ser::Serialize>(
+        &self,
+        version: &str,
+        inputs: HashMap<K, V>,
+        options: PredictionOptions,
+    ) -> Result<PredictionClient, ReplicateError> {
+        PredictionClient::create_with_options(self.parent.clone(), version, inputs, options).await
+    }
+
+    /// Validate the inputs against the schema of the model version, then create a new prediction.
+    /// Invalid inputs fail with a [`ReplicateError::InvalidInput`] error listing every invalid input, without sending any request.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{config::Config, nonblocking::Replicate};
+    ///
+    /// # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let schema = replicate
+    ///     .models
+    ///     .versions
+    ///     .get("replicate", "hello-world", "5c7d5dc6dd8bf75c1acaa8565735e7986bc5b66206b55cca93cb72c9bf15ccaa")
+    ///     .await?
+    ///     .schema()?;
+    ///
+    /// let mut inputs = std::collections::HashMap::new();
+    /// inputs.insert("text", "Alice");
+    ///
+    /// let prediction = replicate
+    ///     .predictions
+    ///     .create_validated(
+    ///         "replicate/hello-world:5c7d5dc6dd8bf75c1acaa8565735e7986bc5b66206b55cca93cb72c9bf15ccaa",
+    ///         &schema,
+    ///         inputs,
+    ///     )
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_validated<K: serde::Serialize, V: serde::ser::Serialize>(
+        &self,
+        version: &str,
+        schema: &VersionSchema,
+        inputs: HashMap<K, V>,
+    ) -> Result<PredictionClient, ReplicateError> {
+        schema.validate(&inputs)?;
+
+        self.create(version, inputs).await
+    }
+
+    /// Create a new prediction with local files as inputs, along with the other inputs.
+    /// Small files are sent inline as `data:` URIs, and large ones are uploaded first, see the [`file_input`](crate::file_input) module.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{config::Config, file_input::FileInput, nonblocking::Replicate};
+    ///
+    /// # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let mut inputs = std::collections::HashMap::new();
+    /// inputs.insert("scale", 4);
+    ///
+    /// let mut files = std::collections::HashMap::new();
+    /// files.insert("image", FileInput::path("wombat.png"));
+    ///
+    /// let prediction = replicate
+    ///     .predictions
+    ///     .create_with_files(
+    ///         "nightmareai/real-esrgan:42fed1c4974146d4d2414e2be2c5277c7fcf05fcc3a73abf41610695738c1d7b",
+    ///         inputs,
+    ///         files,
+    ///     )
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_with_files<K: serde::Serialize, V: serde::ser::Serialize>(
+        &self,
+        version: &str,
+        inputs: HashMap<K, V>,
+        files: HashMap<&str, FileInput>,
+    ) -> Result<PredictionClient, ReplicateError> {
+        let mut inputs: HashMap<String, serde_json::Value> =
+            serde_json::from_value(serde_json::to_value(inputs)?)?;
+
+        for (name, file) in files {
+            inputs.insert(
+                name.to_string(),
+                file.async_resolve(&self.parent).await?.into(),
+            );
+        }
+
+        self.create(version, inputs).await
+    }
+
+    /// List all predictions executed in Replicate by the user.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{config::Config, nonblocking::Replicate};
+    ///
+    /// # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let predictions = replicate.predictions.list().await?;
+    /// println!("Predictions : {:?}", predictions);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list(&self) -> Result<ListPredictions, ReplicateError> {
+        self.parent
+            .async_send_json(list_request(&self.parent))
+            .await
+    }
+
+    /// Iterate over all the predictions, following the pages lazily. See [`Paginator`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use futures_util::TryStreamExt;
+    /// use replicate_rust::{config::Config, nonblocking::Replicate};
+    ///
+    /// # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let predictions: Vec<_> = replicate.predictions.list_all().try_collect().await?;
+    /// println!("Predictions : {:?}", predictions);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn list_all(&self) -> Paginator<PredictionsListItem> {
+        Paginator::new(self.parent.clone(), list_request(&self.parent).url)
+    }
+
+    /// List the predictions matching the filter, on the first page of predictions.
+    ///
+    /// Filters not supported by the API are applied to the results of the page, so it may contain fewer results than the page size, or none.
+    /// Use [`list_all_with_filter`](Self::list_all_with_filter) to go through every page.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{config::Config, nonblocking::Replicate, prediction::PredictionFilter};
+    ///
+    /// # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let filter = PredictionFilter::new().created_after("2023-09-01T00:00:00Z");
+    /// let predictions = replicate.predictions.list_with_filter(&filter).await?;
+    /// println!("Predictions : {:?}", predictions);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list_with_filter(
+        &self,
+        filter: &PredictionFilter,
+    ) -> Result<ListPredictions, ReplicateError> {
+        let request = self
+            .parent
+            .request_url(Method::GET, filter.url(&self.parent));
+
+        let mut predictions: ListPredictions = self.parent.async_send_json(request).await?;
+        predictions
+            .results
+            .retain(|prediction| filter.matches(prediction));
+
+        Ok(predictions)
+    }
+
+    /// Iterate over all the predictions matching the filter, following the pages lazily. See [`Paginator`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use futures_util::TryStreamExt;
+    /// use replicate_rust::{
+    ///     api_definitions::PredictionStatus, config::Config, nonblocking::Replicate,
+    ///     prediction::PredictionFilter,
+    /// };
+    ///
+    /// # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let filter = PredictionFilter::new().status(PredictionStatus::Failed);
+    /// let failed: Vec<_> = replicate.predictions.list_all_with_filter(&filter).try_collect().await?;
+    /// println!("Failed predictions : {:?}", failed);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn list_all_with_filter(
+        &self,
+        filter: &PredictionFilter,
+    ) -> Paginator<PredictionsListItem> {
+        let paginator = Paginator::new(self.parent.clone(), filter.url(&self.parent));
+
+        match filter.result_filter() {
+            Some(result_filter) => paginator.with_filter(result_filter),
+            None => paginator,
+        }
+    }
+
+    /// Get a prediction by passing in the prediction id.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{config::Config, nonblocking::Replicate};
+    ///
+    /// # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let prediction = replicate.predictions.get("rrr4z55ocneqzikepnug6xezpe").await?;
+    /// println!("Prediction : {:?}", prediction);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get(&self, id: &str) -> Result<GetPrediction, ReplicateError> {
+        self.parent
+            .async_send_json(get_request(&self.parent, id))
+            .await
+    }
+
+    /// Download the files in the output of a prediction to a directory, created if missing, and return their paths in the order of the output.
+    /// See the [`download`] module for how the files are named, resumed and checked.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{config::Config, download::DownloadOptions, nonblocking::Replicate};
+    ///
+    /// # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let prediction = replicate.predictions.get("rrr4z55ocneqzikepnug6xezpe").await?;
+    ///
+    /// let paths = replicate
+    ///     .predictions
+    ///     .download_outputs(&prediction, "outputs", &DownloadOptions::default())
+    ///     .await?;
+    /// println!("Downloaded : {:?}", paths);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_outputs(
+        &self,
+        prediction: &GetPrediction,
+        dir: impl AsRef<Path>,
+        options: &DownloadOptions,
+    ) -> Result<Vec<PathBuf>, ReplicateError> {
+        download::async_download_files(
+            &self.parent,
+            &prediction.output_files(),
+            dir.as_ref(),
+            options,
+        )
+        .await
+    }
+
+    /// Download the single file in the output of a prediction into a writer, returning the number of bytes written.
+    ///
+    /// Returns a [`ReplicateError::UnexpectedOutput`] error if the output does not hold exactly one file.
+    pub async fn download_output_to(
+        &self,
+        prediction: &GetPrediction,
+        writer: &mut (impl AsyncWrite + Unpin),
+    ) -> Result<u64, ReplicateError> {
+        let file = download::single_file(prediction)?;
+
+        download::async_download_to_writer(&self.parent, &file.url, writer).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{config::Config, nonblocking::Replicate};
+
+    use super::*;
+    use httpmock::{Method::GET, MockServer};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_get() -> Result<(), ReplicateError> {
+        let server = MockServer::start_async().await;
+
+        let get_mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/predictions/rrr4z55ocneqzikepnug6xezpe");
+                then.status(200).json_body_obj(&json!({
+                    "id": "rrr4z55ocneqzikepnug6xezpe",
+                    "version": "be04660a5b93ef2aff61e3668dedb4cbeb14941e62a3fd5998364a32d613e35e",
+                    "urls": {
+                        "get": "https://api.replicate.com/v1/predictions/rrr4z55ocneqzikepnug6xezpe",
+                        "cancel": "https://api.replicate.com/v1/predictions/rrr4z55ocneqzikepnug6xezpe/cancel",
+                    },
+                    "created_at": "2022-09-13T22:54:18.578761Z",
+                    "started_at": "2022-09-13T22:54:19.438525Z",
+                    "completed_at": "2022-09-13T22:54:23.236610Z",
+                    "source": "api",
+                    "status": "succeeded",
+                    "input": {"prompt": "oak tree with boletus growing on its branches"},
+                    "output": ["https://replicate.com/api/models/stability-ai/stable-diffusion/files/9c3b6fe4-2d37-4571-a17a-83951b1cb120/out-0.png"],
+                    "error": None::<String>,
+                    "logs": "Using seed: 36941...",
+                    "metrics": {"predict_time": 4.484541},
+                }));
+            })
+            .await;
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: server.base_url(),
+            ..Config::default()
+        };
+        let replicate = Replicate::new(config);
+
+        let result = replicate
+            .predictions
+            .get("rrr4z55ocneqzikepnug6xezpe")
+            .await?;
+
+        assert_eq!(result.id, "rrr4z55ocneqzikepnug6xezpe");
+
+        // Ensure the mocks were called as expected
+        get_mock.assert_async().await;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_download_outputs() -> Result<(), ReplicateError> {
+        let server = MockServer::start_async().await;
+        let dir =
+            std::env::temp_dir().join(format!("replicate-async-download-{}", std::process::id()));
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+
+        let file_mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/files/out-0.png");
+                then.status(200).body("hello Alice");
+            })
+            .await;
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: server.base_url(),
+            ..Config::default()
+        };
+        let replicate = Replicate::new(config);
+
+        let prediction: GetPrediction = serde_json::from_value(json!({
+            "id": "rrr4z55ocneqzikepnug6xezpe",
+            "version": "be04660a5b93ef2aff61e3668dedb4cbeb14941e62a3fd5998364a32d613e35e",
+            "urls": {"get": "", "cancel": ""},
+            "created_at": "2022-09-13T22:54:18.578761Z",
+            "started_at": None::<String>,
+            "completed_at": None::<String>,
+            "source": "api",
+            "status": "succeeded",
+            "input": {},
+            "output": [server.url("/files/out-0.png"), "data:text/plain;base64,aGVsbG8="],
+            "error": None::<String>,
+            "logs": None::<String>,
+            "metrics": None::<serde_json::Value>,
+        }))?;
+
+        let paths = replicate
+            .predictions
+            .download_outputs(&prediction, &dir, &DownloadOptions::default())
+            .await?;
+
+        assert_eq!(paths, vec![dir.join("out-0.png"), dir.join("output-1.txt")]);
+        assert_eq!(tokio::fs::read(&paths[0]).await?, b"hello Alice");
+        assert_eq!(tokio::fs::read(&paths[1]).await?, b"hello");
+        file_mock.assert_async().await;
+
+        tokio::fs::remove_dir_all(&dir).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,449 @@
+//! Async counterpart of [`crate::prediction_client`].
+//!
+//! Used to create a prediction, reload for latest info, cancel it and wait for prediction to complete.
+//!
+//! # Example
+//! ```no_run
+//! use replicate_rust::{config::Config, nonblocking::Replicate};
+//!
+//! # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+//! let config = Config::default();
+//! let replicate = Replicate::new(config);
+//!
+//! // Creating the inputs
+//! let mut inputs = std::collections::HashMap::new();
+//! inputs.insert("prompt", "a  19th century portrait of a wombat gentleman");
+//!
+//! let version = "stability-ai/stable-diffusion:27b93a2413e7f36cd83da926f3656280b2931564ff050bf9575f1fdf9bcd7478";
+//!
+//! // Create a new prediction
+//! let mut prediction = replicate.predictions.create(version, inputs).await?;
+//!
+//! // Reload the prediction to get the latest info and logs
+//! prediction.reload().await?;
+//!
+//! // Wait for the prediction to complete
+//! let result = prediction.wait().await?;
+//!
+//! println!("Result : {:?}", result);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    api_definitions::{CreatePrediction, GetPrediction, PredictionStatus, PredictionsUrls},
+    download::{self, DownloadOptions},
+    errors::ReplicateError,
+    prediction::{get_request, PredictionOptions},
+    prediction_client::{cancel_request, create_request, is_done},
+    retry::RetryPolicy,
+    transport::Request,
+};
+
+use super::stream::PredictionStream;
+
+/// Async helper struct for the Prediction struct. Used to create a prediction, reload for latest info, cancel it and wait for prediction to complete.
+#[allow(missing_docs)]
+#[derive(Clone, Debug)]
+pub struct PredictionClient {
+    /// Holds a reference to a Configuration struct, which contains the base url,  auth token among other settings.
+    pub parent: crate::config::Config,
+
+    /// Unique identifier of the prediction
+    pub id: String,
+    pub version: String,
+
+    pub urls: PredictionsUrls,
+
+    pub created_at: String,
+
+    pub status: PredictionStatus,
+
+    pub input: HashMap<String, serde_json::Value>,
+
+    pub error: Option<String>,
+
+    pub logs: Option<String>,
+}
+
+impl PredictionClient {
+    /// Run the prediction of the model version with the given input
+    pub async fn create<K: serde::Serialize, V: serde::ser::Serialize>(
+        rep: crate::config::Config,
+        version: &str,
+        inputs: HashMap<K, V>,
+    ) -> Result<PredictionClient, ReplicateError> {
+        Self::create_with_options(rep, version, inputs, PredictionOptions::default()).await
+    }
+
+    /// Run the prediction of the model version with the given input and options, e.g. to stream its output.
+    pub async fn create_with_options<K: serde::Serialize, V: serde::ser::Serialize>(
+        rep: crate::config::Config,
+        version: &str,
+        inputs: HashMap<K, V>,
+        options: PredictionOptions,
+    ) -> Result<PredictionClient, ReplicateError> {
+        let request = create_request(&rep, version, inputs, options)?;
+
+        Self::send_create(rep, request).await
+    }
+
+    /// Create a prediction by sending the given request, e.g. to the predictions of a deployment.
+    pub(crate) async fn send_create(
+        rep: crate::config::Config,
+        request: Request,
+    ) -> Result<PredictionClient, ReplicateError> {
+        let result: CreatePrediction = rep.async_send_json(request).await?;
+
+        Ok(Self {
+            parent: rep,
+            id: result.id,
+            version: result.version,
+            urls: result.urls,
+            created_at: result.created_at,
+            status: result.status,
+            input: result.input,
+            error: result.error,
+            logs: result.logs,
+        })
+    }
+
+    /// Refetch the prediction and update its fields with the latest info and logs.
+    pub async fn reload(&mut self) -> Result<(), ReplicateError> {
+        let response_struct = self.fetch().await?;
+
+        self.id = response_struct.id;
+        self.version = response_struct.version;
+        self.urls = response_struct.urls;
+        self.created_at = response_struct.created_at;
+        self.status = response_struct.status;
+        self.input = response_struct.input;
+        self.error = response_struct.error;
+        self.logs = response_struct.logs;
+
+        Ok(())
+    }
+
+    /// Cancel the prediction
+    pub async fn cancel(&mut self) -> Result<(), ReplicateError> {
+        self.parent
+            .async_send(cancel_request(&self.parent, &self.id))
+            .await?;
+
+        self.reload().await?;
+
+        Ok(())
+    }
+
+    /// Stream the output of the prediction as it is generated, as an async stream of [`StreamEvent`](crate::stream::StreamEvent)s.
+    ///
+    /// The prediction must have been created with [`PredictionOptions::stream`] set, otherwise a [`ReplicateError::StreamingNotEnabled`] error is returned.
+    pub fn stream(&self) -> Result<PredictionStream, ReplicateError> {
+        match &self.urls.stream {
+            Some(url) => Ok(PredictionStream::new(self.parent.clone(), url.clone())),
+            None => Err(ReplicateError::StreamingNotEnabled(self.id.clone())),
+        }
+    }
+
+    /// Wait for the prediction to complete, then download the files in its output to a directory, created if missing.
+    /// Returns the paths of the files in the order of the output.
+    ///
+    /// Returns a [`ReplicateError::PredictionFailed`] error if the prediction failed or was canceled.
+    pub async fn download_outputs(
+        &self,
+        dir: impl AsRef<Path>,
+        options: &DownloadOptions,
+    ) -> Result<Vec<PathBuf>, ReplicateError> {
+        let prediction = self.wait().await?;
+
+        match prediction.status {
+            PredictionStatus::Succeeded => {
+                download::async_download_files(
+                    &self.parent,
+                    &prediction.output_files(),
+                    dir.as_ref(),
+                    options,
+                )
+                .await
+            }
+            status => Err(ReplicateError::PredictionFailed {
+                id: prediction.id,
+                status,
+                error: prediction.error,
+            }),
+        }
+    }
+
+    /// Return a copy of this prediction using the given policy to retry its requests.
+    pub fn with_retry_policy(&self, policy: RetryPolicy) -> Self {
+        let mut client = self.clone();
+        client.parent.retry_policy = policy;
+        client
+    }
+
+    /// Return a copy of this prediction using the given policy to space out the status checks in [`wait`](Self::wait).
+    pub fn with_poll_policy(&self, policy: RetryPolicy) -> Self {
+        let mut client = self.clone();
+        client.parent.poll_policy = policy;
+        client
+    }
+
+    /// Waits until the prediction is completed (or failed) and returns it, without blocking the async runtime.
+    ///
+    /// The status is checked according to the [poll policy](crate::config::Config::poll_policy).
+    /// Returns a [`ReplicateError::WaitTimeout`] error if the policy gives up before the prediction completes.
+    pub async fn wait(&self) -> Result<GetPrediction, ReplicateError> {
+        let mut backoff = self.parent.poll_policy.backoff();
+
+        loop {
+            let response_struct = self.fetch().await?;
+
+            if is_done(&response_struct.status) {
+                return Ok(response_struct);
+            }
+
+            match backoff.next_delay(None) {
+                Some(delay) => tokio::time::sleep(delay).await,
+                None => return Err(ReplicateError::WaitTimeout(self.id.clone())),
+            }
+        }
+    }
+
+    /// Waits until the `completed` webhook of the prediction is received by the server, and returns the prediction it carries.
+    ///
+    /// The API is still checked according to the [poll policy](crate::config::Config::poll_policy) in case a webhook is lost,
+    /// see [`crate::prediction_client::PredictionClient::wait_with_webhooks`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// use replicate_rust::{
+    ///     config::Config,
+    ///     nonblocking::Replicate,
+    ///     prediction::PredictionOptions,
+    ///     webhooks::{server::WebhookServer, WebhookVerifier},
+    /// };
+    ///
+    /// # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let secret = replicate.webhooks.default_secret().await?;
+    /// let server = WebhookServer::bind("0.0.0.0:8080", WebhookVerifier::new(&secret.key)?)?
+    ///     .public_url("https://example.com/webhooks");
+    ///
+    /// // Creating the inputs
+    /// let mut inputs = std::collections::HashMap::new();
+    /// inputs.insert("prompt", "a  19th century portrait of a wombat gentleman");
+    ///
+    /// let version = "stability-ai/stable-diffusion:27b93a2413e7f36cd83da926f3656280b2931564ff050bf9575f1fdf9bcd7478";
+    ///
+    /// let options = PredictionOptions {
+    ///     webhook: Some(server.url().to_string()),
+    ///     ..Default::default()
+    /// };
+    /// let prediction = replicate.predictions.create_with_options(version, inputs, options).await?;
+    ///
+    /// let result = prediction.wait_with_webhooks(&server).await?;
+    /// println!("Result : {:?}", result);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "webhook-server")]
+    pub async fn wait_with_webhooks(
+        &self,
+        server: &crate::webhooks::server::WebhookServer,
+    ) -> Result<GetPrediction, ReplicateError> {
+        // Watch before checking the API, so a webhook received in between is not missed.
+        let (sender, mut receiver) = tokio::sync::oneshot::channel();
+        let _watch = server.watch(&self.id, move |prediction| {
+            let _ = sender.send(prediction);
+        });
+
+        let mut backoff = self.parent.poll_policy.backoff();
+
+        loop {
+            let response_struct = self.fetch().await?;
+
+            if is_done(&response_struct.status) {
+                return Ok(response_struct);
+            }
+
+            let delay = backoff
+                .next_delay(None)
+                .ok_or_else(|| ReplicateError::WaitTimeout(self.id.clone()))?;
+
+            match tokio::time::timeout(delay, &mut receiver).await {
+                Ok(Ok(prediction)) => return Ok(prediction),
+                Ok(Err(_)) => tokio::time::sleep(delay).await,
+                Err(_) => {}
+            }
+        }
+    }
+
+    /// Fetch the latest state of the prediction.
+    async fn fetch(&self) -> Result<GetPrediction, ReplicateError> {
+        self.parent
+            .async_send_json(get_request(&self.parent, &self.id))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{config::Config, nonblocking::Replicate};
+
+    use super::*;
+    use httpmock::{
+        Method::{GET, POST},
+        MockServer,
+    };
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_create() -> Result<(), ReplicateError> {
+        let server = MockServer::start_async().await;
+
+        let post_mock = server
+            .mock_async(|when, then| {
+                when.method(POST).path("/predictions");
+                then.status(200).json_body_obj(&json!({
+                    "id": "ufawqhfynnddngldkgtslldrkq",
+                    "version": "5c7d5dc6dd8bf75c1acaa8565735e7986bc5b66206b55cca93cb72c9bf15ccaa",
+                    "urls": {
+                        "get": "https://api.replicate.com/v1/predictions/ufawqhfynnddngldkgtslldrkq",
+                        "cancel": "https://api.replicate.com/v1/predictions/ufawqhfynnddngldkgtslldrkq/cancel",
+                    },
+                    "created_at": "2022-04-26T22:13:06.224088Z",
+                    "started_at": None::<String>,
+                    "completed_at": None::<String>,
+                    "status": "starting",
+                    "input": {"text": "Alice"},
+                    "output": None::<String>,
+                    "error": None::<String>,
+                    "logs": None::<String>,
+                    "metrics": {},
+                }));
+            })
+            .await;
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: server.base_url(),
+            ..Config::default()
+        };
+        let replicate = Replicate::new(config);
+
+        let mut input = HashMap::new();
+        input.insert("text", "Alice");
+
+        let result = replicate
+            .predictions
+            .create(
+                "owner/model:632231d0d49d34d5c4633bd838aee3d81d936e59a886fbf28524702003b4c532",
+                input,
+            )
+            .await?;
+        assert_eq!(result.id, "ufawqhfynnddngldkgtslldrkq");
+
+        // Ensure the mocks were called as expected
+        post_mock.assert_async().await;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cancel() -> Result<(), ReplicateError> {
+        let server = MockServer::start_async().await;
+
+        let cancel_mock = server
+            .mock_async(|when, then| {
+                when.method(POST).path("/predictions/p1/cancel");
+                then.status(200);
+            })
+            .await;
+
+        let get_mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/predictions/p1");
+                then.status(200).json_body_obj(&json!({
+                    "id": "p1",
+                    "version": "v1",
+                    "urls": {
+                        "get": format!("{}/predictions/p1", server.base_url()),
+                        "cancel": format!("{}/predictions/p1/cancel", server.base_url()),
+                    },
+                    "created_at": "2022-04-26T20:00:40.658234Z",
+                    "status": "canceled",
+                    "input": {"text": "world"},
+                    "output": None::<String>,
+                    "error": None::<String>,
+                    "logs": None::<String>,
+                }));
+            })
+            .await;
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: server.base_url(),
+            ..Config::default()
+        };
+
+        let mut prediction = PredictionClient {
+            parent: config,
+            id: String::from("p1"),
+            version: String::from("v1"),
+            urls: PredictionsUrls {
+                cancel: format!("{}/predictions/p1/cancel", server.base_url()),
+                get: format!("{}/predictions/p1", server.base_url()),
+                stream: None,
+            },
+            created_at: String::from("2022-04-26T20:00:40.658234Z"),
+            status: PredictionStatus::Processing,
+            input: HashMap::new(),
+            error: None,
+            logs: None,
+        };
+
+        prediction.cancel().await?;
+        assert_eq!(prediction.status, PredictionStatus::Canceled);
+
+        // Ensure the mocks were called as expected
+        cancel_mock.assert_async().await;
+        get_mock.assert_async().await;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retry() {
+        let server = MockServer::start_async().await;
+
+        let get_mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/predictions/p1");
+                then.status(502).body("Bad gateway");
+            })
+            .await;
+
+        let config = Config::builder()
+            .auth("test")
+            .base_url(server.base_url())
+            .retry_policy(RetryPolicy::new(
+                2,
+                crate::retry::RetryStrategy::ExponentialBackoff { initial: 1, max: 5 },
+            ))
+            .build();
+        let replicate = Replicate::new(config);
+
+        let result = replicate.predictions.get("p1").await;
+        assert!(matches!(result, Err(ReplicateError::ResponseError(_))));
+
+        // The first attempt and the two retries.
+        get_mock.assert_hits_async(3).await;
+    }
+}
@@ -0,0 +1,249 @@
+//! Async counterpart of [`crate::stream`]. Used to consume the output of a prediction as it is generated, through [server-sent events](https://replicate.com/docs/streaming).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use futures_util::StreamExt;
+//! use replicate_rust::{
+//!     config::Config, nonblocking::Replicate, prediction::PredictionOptions, stream::StreamEvent,
+//! };
+//!
+//! # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+//! let config = Config::default();
+//! let replicate = Replicate::new(config);
+//!
+//! // Construct the inputs.
+//! let mut inputs = std::collections::HashMap::new();
+//! inputs.insert("prompt", "Tell me a story");
+//!
+//! let version = "meta/llama-2-70b-chat:02e509c789964a7ea8736978a43525956ef40397be9033abf9fd2badfe68c9e3";
+//!
+//! // Create the prediction with streaming enabled.
+//! let options = PredictionOptions {
+//!     stream: true,
+//!     ..Default::default()
+//! };
+//! let prediction = replicate.predictions.create_with_options(version, inputs, options).await?;
+//!
+//! // Print the tokens as they are generated.
+//! let mut stream = prediction.stream()?;
+//! while let Some(event) = stream.next().await {
+//!     match event? {
+//!         StreamEvent::Output(token) => print!("{}", token),
+//!         StreamEvent::Logs(logs) => eprintln!("{}", logs),
+//!         StreamEvent::Error(error) => eprintln!("Error : {}", error),
+//!         StreamEvent::Done { .. } => break,
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_util::{
+    stream::{self, BoxStream},
+    Stream, StreamExt,
+};
+
+use crate::{
+    config::Config,
+    errors::ReplicateError,
+    stream::{disconnected, StreamEvent, StreamState},
+    transport::ByteStream,
+};
+
+/// Async stream of the events of a prediction. Created with [`PredictionClient::stream`](super::prediction_client::PredictionClient::stream).
+///
+/// Yields events until the [`StreamEvent::Done`] event, reconnecting according to the [retry policy](crate::config::Config::retry_policy) when the connection drops.
+pub struct PredictionStream {
+    inner: BoxStream<'static, Result<StreamEvent, ReplicateError>>,
+}
+
+/// State of a [`PredictionStream`] between two events.
+struct State {
+    stream: StreamState,
+    body: Option<ByteStream>,
+}
+
+impl PredictionStream {
+    /// Create a new stream reading the events sent to the given url.
+    pub(crate) fn new(config: Config, url: String) -> Self {
+        let state = State {
+            stream: StreamState::new(config, url),
+            body: None,
+        };
+
+        let inner = stream::unfold(state, |mut state| async move {
+            let event = state.next_event().await?;
+            Some((event, state))
+        });
+
+        Self {
+            inner: inner.boxed(),
+        }
+    }
+}
+
+impl Stream for PredictionStream {
+    type Item = Result<StreamEvent, ReplicateError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next_unpin(cx)
+    }
+}
+
+impl State {
+    /// Read until the next event, connecting and reconnecting as needed.
+    async fn next_event(&mut self) -> Option<Result<StreamEvent, ReplicateError>> {
+        while !self.stream.done {
+            if let Some(event) = self.stream.next_event() {
+                return Some(Ok(event));
+            }
+
+            if let Err(error) = self.read().await {
+                self.stream.done = true;
+                return Some(Err(error));
+            }
+        }
+
+        None
+    }
+
+    /// Read the next chunk of the body, connecting first if needed.
+    async fn read(&mut self) -> Result<(), ReplicateError> {
+        let body = match &mut self.body {
+            Some(body) => body,
+            None => {
+                let request = self.stream.request();
+                let response = self.stream.config.async_send_streaming(request).await?;
+                self.body.insert(response.body)
+            }
+        };
+
+        match body.next().await {
+            Some(Ok(chunk)) => {
+                self.stream.feed(&chunk);
+                Ok(())
+            }
+            Some(Err(error)) => self.disconnect(Some(error)).await,
+            None => self.disconnect(None).await,
+        }
+    }
+
+    /// Drop the connection and wait before reconnecting, or fail if the retry policy gives up.
+    async fn disconnect(&mut self, error: Option<ReplicateError>) -> Result<(), ReplicateError> {
+        self.body = None;
+
+        match self.stream.disconnect() {
+            Some(delay) => {
+                tokio::time::sleep(delay).await;
+                Ok(())
+            }
+            None => Err(error.unwrap_or_else(|| disconnected(None))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{config::Config, nonblocking::Replicate, prediction::PredictionOptions};
+
+    use super::*;
+    use httpmock::{
+        Method::{GET, POST},
+        MockServer,
+    };
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_stream() -> Result<(), ReplicateError> {
+        let server = MockServer::start_async().await;
+
+        let post_mock = server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .path("/predictions")
+                    .json_body_obj(&json!({
+                        "version": "v1",
+                        "input": {"prompt": "Tell me a story"},
+                        "stream": true,
+                    }));
+                then.status(201).json_body_obj(&json!({
+                    "id": "p1",
+                    "version": "v1",
+                    "urls": {
+                        "get": format!("{}/predictions/p1", server.base_url()),
+                        "cancel": format!("{}/predictions/p1/cancel", server.base_url()),
+                        "stream": format!("{}/stream/p1", server.base_url()),
+                    },
+                    "created_at": "2022-04-26T20:00:40.658234Z",
+                    "source": "api",
+                    "status": "starting",
+                    "input": {"prompt": "Tell me a story"},
+                    "output": None::<String>,
+                    "error": None::<String>,
+                    "logs": None::<String>,
+                }));
+            })
+            .await;
+
+        let stream_mock = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/stream/p1")
+                    .header("accept", "text/event-stream")
+                    .header("authorization", "Token test");
+                then.status(200)
+                    .header("content-type", "text/event-stream")
+                    .body("event: output\nid: 1\ndata: Once\n\nevent: logs\nid: 2\ndata: step 1\n\nevent: output\nid: 3\ndata:  upon\n\nevent: done\nid: 4\ndata: {}\n\n");
+            })
+            .await;
+
+        let config = Config::builder()
+            .auth("test")
+            .base_url(server.base_url())
+            .build();
+        let replicate = Replicate::new(config);
+
+        let mut inputs = std::collections::HashMap::new();
+        inputs.insert("prompt", "Tell me a story");
+
+        let prediction = replicate
+            .predictions
+            .create_with_options(
+                "owner/model:v1",
+                inputs,
+                PredictionOptions {
+                    stream: true,
+                    ..Default::default()
+                },
+            )
+            .await?;
+
+        let events: Vec<_> = prediction
+            .stream()?
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<_, _>>()?;
+
+        assert_eq!(
+            events,
+            vec![
+                StreamEvent::Output("Once".into()),
+                StreamEvent::Logs("step 1".into()),
+                StreamEvent::Output(" upon".into()),
+                StreamEvent::Done { reason: None },
+            ]
+        );
+
+        post_mock.assert_async().await;
+        stream_mock.assert_async().await;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,224 @@
+//! Async counterpart of [`crate::training`]. Used to interact with the [Training Endpoints](https://replicate.com/docs/reference/http#trainings.create).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use replicate_rust::{config::Config, nonblocking::Replicate};
+//!
+//! # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+//! let config = Config::default();
+//! let replicate = Replicate::new(config);
+//!
+//! let trainings = replicate.trainings.list().await?;
+//! println!("Trainings : {:?}", trainings);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    api_definitions::{CreateTraining, GetTraining, ListTraining, ListTrainingItem},
+    errors::ReplicateError,
+    training::{cancel_request, create_request, get_request, list_request, TrainingOptions},
+};
+
+use super::pagination::Paginator;
+
+/// Used to interact with the [Training Endpoints](https://replicate.com/docs/reference/http#trainings.create).
+#[derive(Clone, Debug)]
+pub struct Training {
+    /// Holds a reference to a Configuration struct, which contains the base url, auth token among other settings.
+    pub parent: crate::config::Config,
+}
+
+impl Training {
+    /// Create a new Training struct.
+    pub fn new(rep: crate::config::Config) -> Self {
+        Self { parent: rep }
+    }
+
+    /// Return a copy of this endpoint using the given policy to retry its requests, e.g. to override the client policy for a single call.
+    pub fn with_retry_policy(&self, policy: crate::retry::RetryPolicy) -> Self {
+        let mut config = self.parent.clone();
+        config.retry_policy = policy;
+        Self::new(config)
+    }
+
+    /// Create a new training. See [`crate::training::Training::create`] for a description of the options.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use replicate_rust::{config::Config, nonblocking::Replicate, training::TrainingOptions};
+    /// use std::collections::HashMap;
+    ///
+    /// # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let mut input = HashMap::new();
+    /// input.insert(String::from("training_data"), String::from("https://example.com/70k_samples.jsonl"));
+    ///
+    /// let result = replicate.trainings.create(
+    ///     "owner",
+    ///     "model",
+    ///     "632231d0d49d34d5c4633bd838aee3d81d936e59a886fbf28524702003b4c532",
+    ///     TrainingOptions {
+    ///         destination: String::from("new_owner/new_name"),
+    ///         input,
+    ///         webhook: String::from("https://example.com/my-webhook"),
+    ///         webhook_events_filter: None,
+    ///     },
+    /// ).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create(
+        &self,
+        model_owner: &str,
+        model_name: &str,
+        version_id: &str,
+        options: TrainingOptions,
+    ) -> Result<CreateTraining, ReplicateError> {
+        let request = create_request(&self.parent, model_owner, model_name, version_id, options)?;
+
+        self.parent.async_send_json(request).await
+    }
+
+    /// Get the details of a training.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use replicate_rust::{config::Config, nonblocking::Replicate};
+    ///
+    /// # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let training = replicate.trainings.get("zz4ibbonubfz7carwiefibzgga").await?;
+    /// println!("Training : {:?}", training);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get(&self, training_id: &str) -> Result<GetTraining, ReplicateError> {
+        self.parent
+            .async_send_json(get_request(&self.parent, training_id))
+            .await
+    }
+
+    /// Get a paginated list of trainings that you've created with your account. Returns 100 records per page.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use replicate_rust::{config::Config, nonblocking::Replicate};
+    ///
+    /// # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let trainings = replicate.trainings.list().await?;
+    /// println!("Trainings : {:?}", trainings);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list(&self) -> Result<ListTraining, ReplicateError> {
+        self.parent
+            .async_send_json(list_request(&self.parent))
+            .await
+    }
+
+    /// Iterate over all the trainings, following the pages lazily. See [`Paginator`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use futures_util::TryStreamExt;
+    /// use replicate_rust::{config::Config, nonblocking::Replicate};
+    ///
+    /// # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let trainings: Vec<_> = replicate.trainings.list_all().try_collect().await?;
+    /// println!("Trainings : {:?}", trainings);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn list_all(&self) -> Paginator<ListTrainingItem> {
+        Paginator::new(self.parent.clone(), list_request(&self.parent).url)
+    }
+
+    /// Cancel a training.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use replicate_rust::{config::Config, nonblocking::Replicate};
+    ///
+    /// # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let result = replicate.trainings.cancel("zz4ibbonubfz7carwiefibzgga").await?;
+    /// println!("Result : {:?}", result);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn cancel(&self, training_id: &str) -> Result<GetTraining, ReplicateError> {
+        self.parent
+            .async_send_json(cancel_request(&self.parent, training_id))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        api_definitions::PredictionStatus, config::Config, errors::ReplicateError,
+        nonblocking::Replicate,
+    };
+
+    use httpmock::{Method::POST, MockServer};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_cancel() -> Result<(), ReplicateError> {
+        let server = MockServer::start_async().await;
+
+        let post_mock = server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .path("/trainings/zz4ibbonubfz7carwiefibzgga/cancel");
+                then.status(200).json_body_obj(&json!({
+                    "id": "zz4ibbonubfz7carwiefibzgga",
+                    "version": "{version}",
+                    "status": "canceled",
+                    "input": {"text": "..."},
+                    "output": {"version": "..."},
+                    "error": None::<String>,
+                    "logs": None::<String>,
+                    "webhook_completed": None::<String>,
+                    "started_at": None::<String>,
+                    "created_at": "2023-03-28T21:47:58.566434Z",
+                    "completed_at": None::<String>,
+                }));
+            })
+            .await;
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: server.base_url(),
+            ..Config::default()
+        };
+        let replicate = Replicate::new(config);
+
+        let result = replicate
+            .trainings
+            .cancel("zz4ibbonubfz7carwiefibzgga")
+            .await?;
+
+        assert_eq!(result.status, PredictionStatus::Canceled);
+
+        // Ensure the mocks were called as expected
+        post_mock.assert_async().await;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,143 @@
+//! Async counterpart of [`crate::version`]. Used to interact with the [Model Versions Endpoints](https://replicate.com/docs/reference/http#models.versions.get).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use replicate_rust::{config::Config, nonblocking::Replicate};
+//!
+//! # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+//! let config = Config::default();
+//! let replicate = Replicate::new(config);
+//!
+//! let versions = replicate.models.versions.list("replicate", "hello-world").await?;
+//! println!("Versions : {:?}", versions);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    api_definitions::{GetModelVersion, ListModelVersions},
+    errors::ReplicateError,
+    version::{delete_request, get_request, list_request},
+};
+
+use super::pagination::Paginator;
+
+/// Used to interact with the [Model Versions Endpoints](https://replicate.com/docs/reference/http#models.versions.get).
+#[derive(Clone, Debug)]
+pub struct Version {
+    /// Holds a reference to a Configuration struct, which contains the base url,  auth token among other settings.
+    pub parent: crate::config::Config,
+}
+
+impl Version {
+    /// Create a new Version struct.
+    pub fn new(rep: crate::config::Config) -> Self {
+        Self { parent: rep }
+    }
+
+    /// Return a copy of this endpoint using the given policy to retry its requests, e.g. to override the client policy for a single call.
+    pub fn with_retry_policy(&self, policy: crate::retry::RetryPolicy) -> Self {
+        let mut config = self.parent.clone();
+        config.retry_policy = policy;
+        Self::new(config)
+    }
+
+    /// Get the details of a model version.
+    /// ```no_run
+    /// use replicate_rust::{config::Config, nonblocking::Replicate};
+    ///
+    /// # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let version = replicate.models.versions.get(
+    ///         "kvfrans",
+    ///         "clipdraw",
+    ///         "5797a99edc939ea0e9242d5e8c9cb3bc7d125b1eac21bda852e5cb79ede2cd9b",
+    ///     ).await?;
+    /// println!("Version : {:?}", version);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get(
+        &self,
+        model_owner: &str,
+        model_name: &str,
+        version_id: &str,
+    ) -> Result<GetModelVersion, ReplicateError> {
+        self.parent
+            .async_send_json(get_request(
+                &self.parent,
+                model_owner,
+                model_name,
+                version_id,
+            ))
+            .await
+    }
+
+    /// List the versions of a model.
+    /// ```no_run
+    /// use replicate_rust::{config::Config, nonblocking::Replicate};
+    ///
+    /// # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let versions = replicate.models.versions.list("replicate", "hello-world").await?;
+    /// println!("Versions : {:?}", versions);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list(
+        &self,
+        model_owner: &str,
+        model_name: &str,
+    ) -> Result<ListModelVersions, ReplicateError> {
+        self.parent
+            .async_send_json(list_request(&self.parent, model_owner, model_name))
+            .await
+    }
+
+    /// Iterate over all the versions of a model, following the pages lazily. See [`Paginator`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use futures_util::TryStreamExt;
+    /// use replicate_rust::{config::Config, nonblocking::Replicate};
+    ///
+    /// # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let versions: Vec<_> = replicate.models.versions.list_all("replicate", "hello-world").try_collect().await?;
+    /// println!("Versions : {:?}", versions);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn list_all(&self, model_owner: &str, model_name: &str) -> Paginator<GetModelVersion> {
+        Paginator::new(
+            self.parent.clone(),
+            list_request(&self.parent, model_owner, model_name).url,
+        )
+    }
+    /// Delete a version of a model, along with its predictions and their outputs.
+    pub async fn delete(
+        &self,
+        model_owner: &str,
+        model_name: &str,
+        version_id: &str,
+    ) -> Result<(), ReplicateError> {
+        self.parent
+            .async_send(delete_request(
+                &self.parent,
+                model_owner,
+                model_name,
+                version_id,
+            ))
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+//! Async counterpart of [`crate::webhooks`]. Used to fetch the secret signing the [webhooks](https://replicate.com/docs/webhooks) sent by Replicate.
+//!
+//! The webhooks themselves are verified with a [`WebhookVerifier`](crate::webhooks::WebhookVerifier), which does not send any request.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use replicate_rust::{config::Config, nonblocking::Replicate, webhooks::WebhookVerifier};
+//!
+//! # async fn run(headers: reqwest::header::HeaderMap, body: &[u8]) -> Result<(), replicate_rust::errors::ReplicateError> {
+//! let config = Config::default();
+//! let replicate = Replicate::new(config);
+//!
+//! let secret = replicate.webhooks.default_secret().await?;
+//! let verifier = WebhookVerifier::new(&secret.key)?;
+//!
+//! let prediction = verifier.parse_prediction(&headers, body)?;
+//! println!("Prediction {} is {:?}", prediction.id, prediction.status);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    api_definitions::GetWebhookSecret, errors::ReplicateError, webhooks::default_secret_request,
+};
+
+/// Used to interact with the [Webhook Endpoints](https://replicate.com/docs/reference/http#webhooks.default.secret.get).
+#[derive(Clone, Debug)]
+pub struct Webhooks {
+    /// Holds a reference to a Config struct, which contains the base url,  auth token among other settings.
+    pub parent: crate::config::Config,
+}
+
+impl Webhooks {
+    /// Create a new Webhooks struct.
+    pub fn new(rep: crate::config::Config) -> Self {
+        Self { parent: rep }
+    }
+
+    /// Return a copy of this endpoint using the given policy to retry its requests, e.g. to override the client policy for a single call.
+    pub fn with_retry_policy(&self, policy: crate::retry::RetryPolicy) -> Self {
+        let mut config = self.parent.clone();
+        config.retry_policy = policy;
+        Self::new(config)
+    }
+
+    /// Get the secret used to sign the webhooks sent to the account.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{config::Config, nonblocking::Replicate};
+    ///
+    /// # async fn run() -> Result<(), replicate_rust::errors::ReplicateError> {
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let secret = replicate.webhooks.default_secret().await?;
+    /// println!("Secret : {}", secret.key);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn default_secret(&self) -> Result<GetWebhookSecret, ReplicateError> {
+        self.parent
+            .async_send_json(default_secret_request(&self.parent))
+            .await
+    }
+}
@@ -0,0 +1,268 @@
+//! Used to decode the output of a prediction into typed values.
+//!
+//! The output of a prediction is a JSON value, whose shape depends on the model: a url for most image models, a list of urls
+//! for models generating several files, a list of text chunks for language models, or an object.
+//! [`GetPrediction::output_as`] decodes it into any type implementing `Deserialize`, and helpers decode the common shapes.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use replicate_rust::{Replicate, config::Config};
+//!
+//! let config = Config::default();
+//! let replicate = Replicate::new(config);
+//!
+//! let mut inputs = std::collections::HashMap::new();
+//! inputs.insert("prompt", "Tell me a story");
+//!
+//! let prediction = replicate.run(
+//!     "meta/llama-2-70b-chat:02e509c789964a7ea8736978a43525956ef40397be9033abf9fd2badfe68c9e3",
+//!     inputs,
+//! )?;
+//!
+//! // The chunks of text generated by the model, joined together.
+//! println!("Story : {}", prediction.output_text()?);
+//!
+//! # Ok::<(), replicate_rust::errors::ReplicateError>(())
+//! ```
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::{api_definitions::GetPrediction, errors::ReplicateError, schema::VersionSchema};
+
+/// Check that a string is an absolute url.
+fn url(value: &str) -> Result<String, ReplicateError> {
+    match reqwest::Url::parse(value) {
+        Ok(_) => Ok(value.to_string()),
+        Err(error) => Err(ReplicateError::UnexpectedOutput(format!(
+            "{:?} is not a url: {}",
+            value, error
+        ))),
+    }
+}
+
+impl GetPrediction {
+    /// The output, or a [`ReplicateError::UnexpectedOutput`] error if the prediction has no output yet.
+    fn output_value(&self) -> Result<&Value, ReplicateError> {
+        match &self.output {
+            Some(Value::Null) | None => Err(ReplicateError::UnexpectedOutput(format!(
+                "prediction {} has no output",
+                self.id
+            ))),
+            Some(output) => Ok(output),
+        }
+    }
+
+    /// Decode the output into any type implementing `Deserialize`. A missing output is decoded from `null`, e.g. into `None`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let prediction: replicate_rust::api_definitions::GetPrediction = serde_json::from_value(serde_json::json!({
+    /// #     "id": "ufawqhfynnddngldkgtslldrkq",
+    /// #     "version": "5c7d5dc6dd8bf75c1acaa8565735e7986bc5b66206b55cca93cb72c9bf15ccaa",
+    /// #     "urls": {"get": "", "cancel": ""},
+    /// #     "created_at": "2022-04-26T22:13:06.224088Z",
+    /// #     "started_at": null, "completed_at": null, "source": null,
+    /// #     "status": "succeeded",
+    /// #     "input": {},
+    /// #     "output": {"caption": "a wombat", "score": 0.9},
+    /// #     "error": null, "logs": null, "metrics": null,
+    /// # }))?;
+    /// #[derive(serde::Deserialize)]
+    /// struct Caption {
+    ///     caption: String,
+    ///     score: f64,
+    /// }
+    ///
+    /// let output: Caption = prediction.output_as()?;
+    /// assert_eq!(output.caption, "a wombat");
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn output_as<T: DeserializeOwned>(&self) -> Result<T, ReplicateError> {
+        let output = self.output.clone().unwrap_or(Value::Null);
+
+        Ok(serde_json::from_value(output)?)
+    }
+
+    /// Check the output against the `Output` of the version schema, then decode it like [`output_as`](Self::output_as).
+    ///
+    /// Returns a [`ReplicateError::UnexpectedOutput`] error if the output does not match the schema.
+    pub fn output_checked<T: DeserializeOwned>(
+        &self,
+        schema: &VersionSchema,
+    ) -> Result<T, ReplicateError> {
+        if let Some(output) = &schema.output {
+            if !output.matches(self.output.as_ref().unwrap_or(&Value::Null)) {
+                return Err(ReplicateError::UnexpectedOutput(format!(
+                    "the output of prediction {} does not match the version schema",
+                    self.id
+                )));
+            }
+        }
+
+        self.output_as()
+    }
+
+    /// Decode an output made of a single url, e.g. the image generated by a model.
+    /// A list holding a single url is also accepted.
+    pub fn output_url(&self) -> Result<String, ReplicateError> {
+        match self.output_value()? {
+            Value::String(value) => url(value),
+            Value::Array(values) if values.len() == 1 => match &values[0] {
+                Value::String(value) => url(value),
+                value => Err(ReplicateError::UnexpectedOutput(format!(
+                    "expected a url, found {}",
+                    value
+                ))),
+            },
+            value => Err(ReplicateError::UnexpectedOutput(format!(
+                "expected a url, found {}",
+                value
+            ))),
+        }
+    }
+
+    /// Decode an output made of a list of urls, e.g. the images generated by a model.
+    /// A single url is also accepted.
+    pub fn output_urls(&self) -> Result<Vec<String>, ReplicateError> {
+        match self.output_value()? {
+            Value::String(value) => Ok(vec![url(value)?]),
+            Value::Array(values) => values
+                .iter()
+                .map(|value| match value {
+                    Value::String(value) => url(value),
+                    value => Err(ReplicateError::UnexpectedOutput(format!(
+                        "expected a url, found {}",
+                        value
+                    ))),
+                })
+                .collect(),
+            value => Err(ReplicateError::UnexpectedOutput(format!(
+                "expected a list of urls, found {}",
+                value
+            ))),
+        }
+    }
+
+    /// Decode an output made of text: either a single string, or the chunks of text generated by a language model, joined together.
+    pub fn output_text(&self) -> Result<String, ReplicateError> {
+        match self.output_value()? {
+            Value::String(text) => Ok(text.clone()),
+            Value::Array(chunks) => chunks
+                .iter()
+                .map(|chunk| match chunk {
+                    Value::String(chunk) => Ok(chunk.as_str()),
+                    chunk => Err(ReplicateError::UnexpectedOutput(format!(
+                        "expected a chunk of text, found {}",
+                        chunk
+                    ))),
+                })
+                .collect(),
+            value => Err(ReplicateError::UnexpectedOutput(format!(
+                "expected text, found {}",
+                value
+            ))),
+        }
+    }
+
+    /// Decode an output made of a JSON object.
+    pub fn output_object(&self) -> Result<Map<String, Value>, ReplicateError> {
+        match self.output_value()? {
+            Value::Object(object) => Ok(object.clone()),
+            value => Err(ReplicateError::UnexpectedOutput(format!(
+                "expected an object, found {}",
+                value
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn prediction(output: Value) -> GetPrediction {
+        serde_json::from_value(json!({
+            "id": "rrr4z55ocneqzikepnug6xezpe",
+            "version": "be04660a5b93ef2aff61e3668dedb4cbeb14941e62a3fd5998364a32d613e35e",
+            "urls": {
+                "get": "https://api.replicate.com/v1/predictions/rrr4z55ocneqzikepnug6xezpe",
+                "cancel": "https://api.replicate.com/v1/predictions/rrr4z55ocneqzikepnug6xezpe/cancel",
+            },
+            "created_at": "2022-09-13T22:54:18.578761Z",
+            "started_at": "2022-09-13T22:54:19.438525Z",
+            "completed_at": "2022-09-13T22:54:23.236610Z",
+            "source": "api",
+            "status": "succeeded",
+            "input": {},
+            "output": output,
+            "error": None::<String>,
+            "logs": None::<String>,
+            "metrics": None::<Value>,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_helpers() -> Result<(), ReplicateError> {
+        let image = "https://replicate.delivery/pbxt/out-0.png";
+
+        assert_eq!(prediction(json!(image)).output_url()?, image);
+        assert_eq!(prediction(json!([image])).output_url()?, image);
+        assert_eq!(prediction(json!(image)).output_urls()?, vec![image]);
+        assert_eq!(
+            prediction(json!([image, image])).output_urls()?,
+            vec![image, image]
+        );
+        assert!(prediction(json!([image, image])).output_url().is_err());
+        assert!(prediction(json!("not a url")).output_url().is_err());
+        assert!(prediction(Value::Null).output_url().is_err());
+
+        assert_eq!(
+            prediction(json!(["Once", " upon", " a time"])).output_text()?,
+            "Once upon a time"
+        );
+        assert!(prediction(json!([1, 2])).output_text().is_err());
+
+        let object = prediction(json!({"caption": "a wombat"})).output_object()?;
+        assert_eq!(object["caption"], json!("a wombat"));
+
+        let output: Option<Vec<String>> = prediction(Value::Null).output_as()?;
+        assert_eq!(output, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_output_checked() -> Result<(), ReplicateError> {
+        let schema = VersionSchema::from_openapi(&serde_json::from_value(json!({
+            "components": {
+                "schemas": {
+                    "Output": {
+                        "type": "array",
+                        "items": {"type": "string"},
+                        "x-cog-array-type": "iterator",
+                        "x-cog-array-display": "concatenate",
+                    },
+                },
+            },
+        }))?)?;
+
+        let chunks: Vec<String> = prediction(json!(["Once", " upon"])).output_checked(&schema)?;
+        assert_eq!(chunks, vec!["Once", " upon"]);
+
+        assert!(matches!(
+            prediction(json!({"text": "Once upon"})).output_checked::<Value>(&schema),
+            Err(ReplicateError::UnexpectedOutput(_))
+        ));
+        assert!(matches!(
+            prediction(json!([1, 2])).output_checked::<Value>(&schema),
+            Err(ReplicateError::UnexpectedOutput(_))
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,455 @@
+//! Used to go through the pages of the list endpoints, following their `next` cursors.
+//!
+//! Every list endpoint returns a [`Page`] of results, with the url of the next page in its `next` field.
+//! The `list_all` methods return a [`Paginator`] instead, which iterates over the results of every page, fetching the pages lazily.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use replicate_rust::{Replicate, config::Config};
+//!
+//! let config = Config::default();
+//! let replicate = Replicate::new(config);
+//!
+//! // Only fetches the pages needed for the first 250 predictions.
+//! for prediction in replicate.predictions.list_all().page_size(100).take(250) {
+//!     println!("Prediction : {}", prediction?.id);
+//! }
+//!
+//! # Ok::<(), replicate_rust::errors::ReplicateError>(())
+//! ```
+//!
+//! Iteration can be resumed later from [`Paginator::next_cursor`], at a page boundary:
+//!
+//! ```no_run
+//! use replicate_rust::{Replicate, config::Config};
+//!
+//! let config = Config::default();
+//! let replicate = Replicate::new(config);
+//!
+//! let mut predictions = replicate.predictions.list_all();
+//! let first_page = predictions.next_page()?;
+//!
+//! // Store the cursor, e.g. in a database.
+//! let cursor = predictions.next_cursor().map(String::from);
+//!
+//! if let Some(cursor) = cursor {
+//!     for prediction in replicate.predictions.list_all().cursor(cursor) {
+//!         println!("Prediction : {}", prediction?.id);
+//!     }
+//! }
+//!
+//! # Ok::<(), replicate_rust::errors::ReplicateError>(())
+//! ```
+
+use std::{fmt, sync::Arc};
+
+use reqwest::{
+    header::{HeaderValue, CONTENT_TYPE},
+    Method, Url,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{config::Config, errors::ReplicateError, transport::Request};
+
+/// A page of results returned by a list endpoint.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Page<T> {
+    /// Url of the previous page, if any.
+    pub previous: Option<String>,
+
+    /// Url of the next page, if any.
+    pub next: Option<String>,
+
+    /// Results of the page.
+    pub results: Vec<T>,
+}
+
+impl<T: Serialize + fmt::Debug> fmt::Display for Page<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match serde_json::to_string_pretty(&self) {
+            Ok(formatted) => write!(f, "{:?}", formatted),
+            Err(_) => write!(f, "{:?}", self),
+        }
+    }
+}
+
+/// Predicate applied to the results of every page, for the filters that the API does not support.
+pub(crate) type ResultFilter<T> = Arc<dyn Fn(&T) -> bool + Send + Sync>;
+
+/// Add the page size hint to the query of the url, unless it is already set.
+fn page_url(url: &str, page_size: Option<usize>) -> String {
+    let (Some(page_size), Ok(mut parsed)) = (page_size, Url::parse(url)) else {
+        return url.to_string();
+    };
+
+    if !parsed.query_pairs().any(|(name, _)| name == "page_size") {
+        parsed
+            .query_pairs_mut()
+            .append_pair("page_size", &page_size.to_string());
+    }
+
+    parsed.into()
+}
+
+/// Request of the page at the given url. With a search query, the query is sent as the plain text body of a `QUERY` request, as expected by the search endpoint.
+///
+/// Fails with [`ReplicateError::InvalidCursor`] when the url is not on the host of the API, e.g. a tampered cursor, as the request carries the API token.
+pub(crate) fn page_request(
+    config: &Config,
+    url: &str,
+    page_size: Option<usize>,
+    search: Option<&str>,
+) -> Result<Request, ReplicateError> {
+    let origin = |url: &str| Url::parse(url).map(|parsed| parsed.origin()).ok();
+    if origin(url).is_none() || origin(url) != origin(&config.base_url) {
+        return Err(ReplicateError::InvalidCursor(url.to_string()));
+    }
+
+    let url = page_url(url, page_size);
+
+    Ok(match (search, Method::from_bytes(b"QUERY")) {
+        (Some(query), Ok(method)) => config
+            .request_url(method, url)
+            .header(CONTENT_TYPE, HeaderValue::from_static("text/plain"))
+            .body(query),
+        _ => config.request_url(Method::GET, url),
+    })
+}
+
+/// State of a paginated listing, shared by the [`Paginator`] and the [async paginator](crate::nonblocking::pagination::Paginator),
+/// which only differ in how the pages are sent.
+pub(crate) struct Pages<T> {
+    pub(crate) config: Config,
+    next: Option<String>,
+    pub(crate) page_size: Option<usize>,
+    pub(crate) search: Option<String>,
+    pub(crate) remaining: Option<usize>,
+    pub(crate) filter: Option<ResultFilter<T>>,
+    results: std::vec::IntoIter<T>,
+    pub(crate) failed: bool,
+}
+
+impl<T> fmt::Debug for Pages<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Paginator")
+            .field("next", &self.next)
+            .field("page_size", &self.page_size)
+            .field("search", &self.search)
+            .field("remaining", &self.remaining)
+            .field("filtered", &self.filter.is_some())
+            .field("failed", &self.failed)
+            .finish()
+    }
+}
+
+impl<T> Pages<T> {
+    /// Start at the given url.
+    pub(crate) fn new(config: Config, url: String) -> Self {
+        Self {
+            config,
+            next: Some(url),
+            page_size: None,
+            search: None,
+            remaining: None,
+            filter: None,
+            results: Vec::new().into_iter(),
+            failed: false,
+        }
+    }
+
+    /// Resume from the given cursor, dropping the results left.
+    pub(crate) fn set_cursor(&mut self, cursor: String) {
+        self.next = Some(cursor);
+        self.results = Vec::new().into_iter();
+    }
+
+    /// Cursor of the next page to fetch, or `None` once the last page was fetched.
+    pub(crate) fn next_cursor(&self) -> Option<&str> {
+        self.next.as_deref()
+    }
+
+    /// Drop the results left and create the request of the next page, or `None` once the last page was fetched.
+    pub(crate) fn next_request(&mut self) -> Result<Option<Request>, ReplicateError> {
+        self.results = Vec::new().into_iter();
+
+        match &self.next {
+            Some(next) => {
+                page_request(&self.config, next, self.page_size, self.search.as_deref()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Keep the results of a fetched page matching the filter, and move the cursor to the next page.
+    pub(crate) fn receive(&mut self, mut page: Page<T>) -> Page<T> {
+        if let Some(filter) = &self.filter {
+            page.results.retain(|result| filter(result));
+        }
+
+        self.next = page.next.clone();
+        page
+    }
+
+    /// Keep the results of a page, to return them one by one.
+    pub(crate) fn buffer(&mut self, results: Vec<T>) {
+        self.results = results.into_iter();
+    }
+
+    /// Next result of the current page, unless the limit is reached.
+    pub(crate) fn next_result(&mut self) -> Option<T> {
+        if self.remaining == Some(0) {
+            return None;
+        }
+
+        let result = self.results.next()?;
+        self.remaining = self.remaining.map(|remaining| remaining - 1);
+        Some(result)
+    }
+
+    /// Whether no page should be fetched anymore, once the limit is reached or after an error.
+    pub(crate) fn is_over(&self) -> bool {
+        self.remaining == Some(0) || self.failed
+    }
+}
+
+/// Iterator over the results of every page of a list endpoint. Created with the `list_all` methods.
+///
+/// The next page is only fetched once all the results of the current one have been returned.
+/// Iteration stops after the first error, and can be resumed from [`next_cursor`](Self::next_cursor).
+pub struct Paginator<T> {
+    pages: Pages<T>,
+}
+
+impl<T> fmt::Debug for Paginator<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.pages.fmt(f)
+    }
+}
+
+impl<T: DeserializeOwned> Paginator<T> {
+    /// Create a new paginator starting at the given url.
+    pub(crate) fn new(config: Config, url: String) -> Self {
+        Self {
+            pages: Pages::new(config, url),
+        }
+    }
+
+    /// Only return the results matching the predicate.
+    pub(crate) fn with_filter(mut self, filter: ResultFilter<T>) -> Self {
+        self.pages.filter = Some(filter);
+        self
+    }
+
+    /// Send the query along with the request of every page, for the search endpoint.
+    pub(crate) fn with_search(mut self, query: &str) -> Self {
+        self.pages.search = Some(query.to_string());
+        self
+    }
+
+    /// Stop after the given number of results, without fetching the pages past them.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.pages.remaining = Some(limit);
+        self
+    }
+
+    /// Ask the API for pages of the given size. This is only a hint, the API may return pages of a different size.
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.pages.page_size = Some(page_size);
+        self
+    }
+
+    /// Resume from a cursor returned by [`next_cursor`](Self::next_cursor).
+    ///
+    /// Fetching the page fails with [`ReplicateError::InvalidCursor`] if the cursor is not an url of the API.
+    pub fn cursor(mut self, cursor: impl Into<String>) -> Self {
+        self.pages.set_cursor(cursor.into());
+        self
+    }
+
+    /// Cursor of the next page to fetch, or `None` once the last page was fetched.
+    ///
+    /// Results of the current page that were not returned yet are not included when resuming from this cursor.
+    pub fn next_cursor(&self) -> Option<&str> {
+        self.pages.next_cursor()
+    }
+
+    /// Fetch the next page, or `None` once the last page was fetched.
+    ///
+    /// Results of the current page that were not returned by the iterator yet are dropped.
+    /// When the results are filtered on the client, the page only contains the matching results, and may be empty.
+    pub fn next_page(&mut self) -> Result<Option<Page<T>>, ReplicateError> {
+        let Some(request) = self.pages.next_request()? else {
+            return Ok(None);
+        };
+
+        let page = self.pages.config.send_json(request)?;
+        Ok(Some(self.pages.receive(page)))
+    }
+}
+
+impl<T: DeserializeOwned> Iterator for Paginator<T> {
+    type Item = Result<T, ReplicateError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(result) = self.pages.next_result() {
+                return Some(Ok(result));
+            }
+
+            if self.pages.is_over() {
+                return None;
+            }
+
+            match self.next_page() {
+                Ok(Some(page)) => self.pages.buffer(page.results),
+                Ok(None) => return None,
+                Err(error) => {
+                    self.pages.failed = true;
+                    return Some(Err(error));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Replicate;
+    use httpmock::{Method::GET, MockServer};
+    use serde_json::json;
+
+    fn item(id: &str) -> serde_json::Value {
+        json!({
+            "name": id,
+            "slug": id,
+            "description": "",
+        })
+    }
+
+    #[test]
+    fn test_paginate() -> Result<(), ReplicateError> {
+        let server = MockServer::start();
+
+        let second_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/collections")
+                .query_param("cursor", "2")
+                .query_param("page_size", "2");
+            then.status(200).json_body_obj(&json!({
+                "previous": format!("{}/collections?cursor=1", server.base_url()),
+                "next": None::<String>,
+                "results": [item("c")],
+            }));
+        });
+        let first_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/collections")
+                .query_param("page_size", "2");
+            then.status(200).json_body_obj(&json!({
+                "previous": None::<String>,
+                "next": format!("{}/collections?cursor=2", server.base_url()),
+                "results": [item("a"), item("b")],
+            }));
+        });
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: server.base_url(),
+            ..Config::default()
+        };
+        let replicate = Replicate::new(config);
+
+        // Stops before fetching the second page.
+        let slugs = replicate
+            .collections
+            .list_all()
+            .page_size(2)
+            .take(2)
+            .map(|collection| collection.map(|collection| collection.slug))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(slugs, vec!["a", "b"]);
+        first_mock.assert_hits(1);
+        second_mock.assert_hits(0);
+
+        let slugs = replicate
+            .collections
+            .list_all()
+            .page_size(2)
+            .map(|collection| collection.map(|collection| collection.slug))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(slugs, vec!["a", "b", "c"]);
+        first_mock.assert_hits(2);
+        second_mock.assert_hits(1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cursor() -> Result<(), ReplicateError> {
+        let server = MockServer::start();
+
+        let second_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/collections")
+                .query_param("cursor", "2");
+            then.status(200).json_body_obj(&json!({
+                "previous": None::<String>,
+                "next": None::<String>,
+                "results": [item("c")],
+            }));
+        });
+        let first_mock = server.mock(|when, then| {
+            when.method(GET).path("/collections");
+            then.status(200).json_body_obj(&json!({
+                "previous": None::<String>,
+                "next": format!("{}/collections?cursor=2", server.base_url()),
+                "results": [item("a"), item("b")],
+            }));
+        });
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: server.base_url(),
+            ..Config::default()
+        };
+        let replicate = Replicate::new(config);
+
+        let mut collections = replicate.collections.list_all();
+        let page = collections.next_page()?.unwrap();
+        assert_eq!(page.results.len(), 2);
+
+        let cursor = collections.next_cursor().unwrap().to_string();
+        assert_eq!(
+            cursor,
+            format!("{}/collections?cursor=2", server.base_url())
+        );
+
+        let resumed = replicate
+            .collections
+            .list_all()
+            .cursor(cursor)
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(resumed.len(), 1);
+        assert_eq!(resumed[0].slug, "c");
+
+        // Tampered cursors are rejected before sending the API token.
+        for cursor in [
+            String::from("https://example.com/collections?cursor=2"),
+            format!("{}/collections?cursor=2", server.base_url()).replace("http://", "https://"),
+            String::from("not an url"),
+        ] {
+            let mut tampered = replicate.collections.list_all().cursor(cursor);
+            assert!(matches!(
+                tampered.next(),
+                Some(Err(ReplicateError::InvalidCursor(_)))
+            ));
+        }
+
+        first_mock.assert_hits(1);
+        second_mock.assert_hits(1);
+
+        Ok(())
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! # Example
 //!
-//! ```
+//! ```no_run
 //! use replicate_rust::{Replicate, config::Config};
 //!
 //! let config = Config::default();
@@ -25,7 +25,7 @@
 //!
 //! ## Another example to showcase other methods
 //!
-//! ```
+//! ```no_run
 //! use replicate_rust::{Replicate, config::Config};
 //!
 //! let config = Config::default();
@@ -59,22 +59,208 @@
 //!
 
 use serde::Serialize;
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use reqwest::Method;
 
 use crate::{
-    api_definitions::{GetPrediction, ListPredictions},
+    api_definitions::{
+        GetPrediction, ListPredictions, PredictionSource, PredictionStatus, PredictionsListItem,
+        WebhookEvents,
+    },
+    config::Config,
+    download::{self, DownloadOptions},
     errors::ReplicateError,
+    file_input::FileInput,
+    pagination::{Paginator, ResultFilter},
     prediction_client::PredictionClient,
+    schema::VersionSchema,
+    transport::Request,
 };
 
 /// Used to interact with the [Prediction Endpoints](https://replicate.com/docs/reference/http#predictions.get).
 #[derive(Serialize)]
 pub struct PredictionPayload<K: serde::Serialize, V: serde::ser::Serialize> {
-    /// Version of the model used for the prediction
+    /// Version of the model used for the prediction. Left empty, and not sent, when the url already names what runs the prediction:
+    /// a deployment, or an official model run by name.
+    #[serde(skip_serializing_if = "String::is_empty")]
     pub version: String,
 
     /// Input to the model
     pub input: HashMap<K, V>,
+
+    /// Request a url to stream the output of the model as server-sent events.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub stream: bool,
+
+    /// An HTTPS URL receiving a webhook when the prediction has new output or completes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<String>,
+
+    /// Events sending a webhook. If not specified, all events send one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook_events_filter: Option<Vec<WebhookEvents>>,
+}
+
+impl<K: serde::Serialize, V: serde::ser::Serialize> PredictionPayload<K, V> {
+    /// Create the payload of a prediction of the given version, empty when the url names what runs the prediction.
+    pub(crate) fn new(version: String, input: HashMap<K, V>, options: PredictionOptions) -> Self {
+        Self {
+            version,
+            input,
+            stream: options.stream,
+            webhook: options.webhook,
+            webhook_events_filter: options.webhook_events_filter,
+        }
+    }
+}
+
+/// Contains all the options for creating a prediction.
+#[derive(Clone, Debug, Default)]
+pub struct PredictionOptions {
+    /// Request a url to stream the output of the model as it is generated, see [`PredictionClient::stream`]. Only supported by some models, e.g. language models.
+    pub stream: bool,
+
+    /// An HTTPS URL for receiving a webhook when the prediction has new output or completes. The webhook will be a POST request where the request body is the same as the response body of the get prediction operation. If there are network problems, we will retry the webhook a few times, so make sure it can be safely called more than once.
+    pub webhook: Option<String>,
+
+    /// To only send specific events to the webhook, use this field. If not specified, all events will be sent.
+    pub webhook_events_filter: Option<Vec<WebhookEvents>>,
+}
+
+/// Filters for listing predictions, see [`Prediction::list_with_filter`].
+///
+/// The creation time range is sent to the API. The other filters are not supported by the API, and are applied to the results of every page instead.
+///
+/// # Example
+///
+/// ```
+/// use replicate_rust::{api_definitions::PredictionStatus, prediction::PredictionFilter};
+///
+/// let filter = PredictionFilter::new()
+///     .created_after("2023-09-01T00:00:00Z")
+///     .created_before("2023-10-01T00:00:00Z")
+///     .status(PredictionStatus::Failed)
+///     .model("stability-ai/stable-diffusion");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PredictionFilter {
+    /// Only predictions created at or after this time, as an ISO 8601 timestamp.
+    pub created_after: Option<String>,
+
+    /// Only predictions created before this time, as an ISO 8601 timestamp.
+    pub created_before: Option<String>,
+
+    /// Only predictions with one of these statuses. Empty for any status.
+    pub statuses: Vec<PredictionStatus>,
+
+    /// Only predictions created from this source.
+    pub source: Option<PredictionSource>,
+
+    /// Only predictions of this model, as `{owner}/{name}`.
+    pub model: Option<String>,
+
+    /// Only predictions of this model version id.
+    pub version: Option<String>,
+
+    /// Only predictions created with this deployment, as `{owner}/{name}`.
+    pub deployment: Option<String>,
+}
+
+impl PredictionFilter {
+    /// Create a filter matching every prediction.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only predictions created at or after this time, as an ISO 8601 timestamp.
+    pub fn created_after(mut self, time: impl Into<String>) -> Self {
+        self.created_after = Some(time.into());
+        self
+    }
+
+    /// Only predictions created before this time, as an ISO 8601 timestamp.
+    pub fn created_before(mut self, time: impl Into<String>) -> Self {
+        self.created_before = Some(time.into());
+        self
+    }
+
+    /// Only predictions with this status. Can be called several times to match any of the statuses.
+    pub fn status(mut self, status: PredictionStatus) -> Self {
+        self.statuses.push(status);
+        self
+    }
+
+    /// Only predictions created from this source.
+    pub fn source(mut self, source: PredictionSource) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// Only predictions of this model, as `{owner}/{name}`.
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Only predictions of this model version id.
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// Only predictions created with this deployment, as `{owner}/{name}`.
+    pub fn deployment(mut self, deployment: impl Into<String>) -> Self {
+        self.deployment = Some(deployment.into());
+        self
+    }
+
+    /// Check the filters that are not sent to the API.
+    pub fn matches(&self, prediction: &PredictionsListItem) -> bool {
+        (self.statuses.is_empty() || self.statuses.contains(&prediction.status))
+            && (self.source.is_none() || prediction.source == self.source)
+            && (self.model.is_none() || prediction.model == self.model)
+            && (self.version.is_none() || self.version.as_ref() == Some(&prediction.version))
+            && (self.deployment.is_none() || prediction.deployment == self.deployment)
+    }
+
+    /// Url of the first page of predictions, with the filters supported by the API.
+    pub(crate) fn url(&self, config: &crate::config::Config) -> String {
+        let url = config.url("/predictions");
+        let query = [
+            ("created_after", &self.created_after),
+            ("created_before", &self.created_before),
+        ];
+
+        match reqwest::Url::parse(&url) {
+            Ok(mut parsed) => {
+                for (name, value) in query {
+                    if let Some(value) = value {
+                        parsed.query_pairs_mut().append_pair(name, value);
+                    }
+                }
+                parsed.into()
+            }
+            Err(_) => url,
+        }
+    }
+
+    /// Predicate applying the filters that are not sent to the API, if any.
+    pub(crate) fn result_filter(&self) -> Option<ResultFilter<PredictionsListItem>> {
+        let client_side = Self {
+            created_after: None,
+            created_before: None,
+            ..self.clone()
+        };
+
+        (client_side != Self::default()).then(|| -> ResultFilter<PredictionsListItem> {
+            std::sync::Arc::new(move |prediction| client_side.matches(prediction))
+        })
+    }
 }
 
 /// Used to interact with the [Prediction Endpoints](https://replicate.com/docs/reference/http#predictions.get).
@@ -90,12 +276,22 @@ impl Prediction {
         Self { parent: rep }
     }
 
+    /// Return a copy of this endpoint using the given policy to retry its requests, e.g. to override the client policy for a single call.
+    pub fn with_retry_policy(&self, policy: crate::retry::RetryPolicy) -> Self {
+        let mut config = self.parent.clone();
+        config.retry_policy = policy;
+        Self::new(config)
+    }
+
     /// Create a new prediction, by passing in the model version and inputs to PredictionClient.
     /// PredictionClient contains the necessary methods to interact with the prediction such as reload, cancel and wait.
     ///
+    /// The model is given as `{owner}/{name}:{version_id}`, as a version id alone, or as `{owner}/{name}` to run the latest version of an official model.
+    /// See [`ModelRef`](crate::model_ref::ModelRef).
+    ///
     /// # Example
     ///
-    /// ```
+    /// ```no_run
     /// use replicate_rust::{Replicate, config::Config};
     ///
     /// let config = Config::default();
@@ -127,18 +323,132 @@ impl Prediction {
         version: &str,
         inputs: HashMap<K, V>,
     ) -> Result<PredictionClient, ReplicateError> {
-        Ok(PredictionClient::create(
-            self.parent.clone(),
-            version,
-            inputs,
-        )?)
+        PredictionClient::create(self.parent.clone(), version, inputs)
     }
 
-    /// List all predictions executed in Replicate by the user.
+    /// Create a new prediction with the given options, e.g. to stream its output or to receive webhooks.
     ///
     /// # Example
     ///
+    /// ```no_run
+    /// use replicate_rust::{
+    ///     Replicate, api_definitions::WebhookEvents, config::Config, prediction::PredictionOptions,
+    /// };
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// // Construct the inputs.
+    /// let mut inputs = std::collections::HashMap::new();
+    /// inputs.insert("prompt", "Tell me a story");
+    ///
+    /// let version = "meta/llama-2-70b-chat:02e509c789964a7ea8736978a43525956ef40397be9033abf9fd2badfe68c9e3";
+    ///
+    /// let prediction = replicate.predictions.create_with_options(
+    ///     version,
+    ///     inputs,
+    ///     PredictionOptions {
+    ///         stream: true,
+    ///         webhook: Some(String::from("https://example.com/my-webhook")),
+    ///         webhook_events_filter: Some(vec![WebhookEvents::Completed]),
+    ///     },
+    /// )?;
+    ///
+    /// println!("Stream url : {:?}", prediction.urls.stream);
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
     /// ```
+    pub fn create_with_options<K: serde::Serialize, V: serde::ser::Serialize>(
+        &self,
+        version: &str,
+        inputs: HashMap<K, V>,
+        options: PredictionOptions,
+    ) -> Result<PredictionClient, ReplicateError> {
+        PredictionClient::create_with_options(self.parent.clone(), version, inputs, options)
+    }
+
+    /// Validate the inputs against the schema of the model version, then create a new prediction.
+    /// Invalid inputs fail with a [`ReplicateError::InvalidInput`] error listing every invalid input, without sending any request.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let schema = replicate
+    ///     .models
+    ///     .versions
+    ///     .get("replicate", "hello-world", "5c7d5dc6dd8bf75c1acaa8565735e7986bc5b66206b55cca93cb72c9bf15ccaa")?
+    ///     .schema()?;
+    ///
+    /// let mut inputs = std::collections::HashMap::new();
+    /// inputs.insert("text", "Alice");
+    ///
+    /// let prediction = replicate.predictions.create_validated(
+    ///     "replicate/hello-world:5c7d5dc6dd8bf75c1acaa8565735e7986bc5b66206b55cca93cb72c9bf15ccaa",
+    ///     &schema,
+    ///     inputs,
+    /// )?;
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn create_validated<K: serde::Serialize, V: serde::ser::Serialize>(
+        &self,
+        version: &str,
+        schema: &VersionSchema,
+        inputs: HashMap<K, V>,
+    ) -> Result<PredictionClient, ReplicateError> {
+        schema.validate(&inputs)?;
+
+        self.create(version, inputs)
+    }
+
+    /// Create a new prediction with local files as inputs, along with the other inputs.
+    /// Small files are sent inline as `data:` URIs, and large ones are uploaded first, see the [`file_input`](crate::file_input) module.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config, file_input::FileInput};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let mut inputs = std::collections::HashMap::new();
+    /// inputs.insert("scale", 4);
+    ///
+    /// let mut files = std::collections::HashMap::new();
+    /// files.insert("image", FileInput::path("wombat.png"));
+    ///
+    /// let prediction = replicate.predictions.create_with_files(
+    ///     "nightmareai/real-esrgan:42fed1c4974146d4d2414e2be2c5277c7fcf05fcc3a73abf41610695738c1d7b",
+    ///     inputs,
+    ///     files,
+    /// )?;
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn create_with_files<K: serde::Serialize, V: serde::ser::Serialize>(
+        &self,
+        version: &str,
+        inputs: HashMap<K, V>,
+        files: HashMap<&str, FileInput>,
+    ) -> Result<PredictionClient, ReplicateError> {
+        let mut inputs: HashMap<String, serde_json::Value> =
+            serde_json::from_value(serde_json::to_value(inputs)?)?;
+
+        for (name, file) in files {
+            inputs.insert(name.to_string(), file.resolve(&self.parent)?.into());
+        }
+
+        self.create(version, inputs)
+    }
+
+    /// List all predictions executed in Replicate by the user.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
     /// use replicate_rust::{Replicate, config::Config};
     ///
     /// let config = Config::default();
@@ -150,22 +460,96 @@ impl Prediction {
     /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
     /// ```
     pub fn list(&self) -> Result<ListPredictions, ReplicateError> {
-        let client = reqwest::blocking::Client::new();
+        self.parent.send_json(list_request(&self.parent))
+    }
+
+    /// Iterate over all the predictions, following the pages lazily. See [`Paginator`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// for prediction in replicate.predictions.list_all() {
+    ///     println!("Prediction : {:?}", prediction?);
+    /// }
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn list_all(&self) -> Paginator<PredictionsListItem> {
+        Paginator::new(self.parent.clone(), list_request(&self.parent).url)
+    }
+
+    /// List the predictions matching the filter, on the first page of predictions.
+    ///
+    /// Filters not supported by the API are applied to the results of the page, so it may contain fewer results than the page size, or none.
+    /// Use [`list_all_with_filter`](Self::list_all_with_filter) to go through every page.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config, prediction::PredictionFilter};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let filter = PredictionFilter::new().created_after("2023-09-01T00:00:00Z");
+    /// let predictions = replicate.predictions.list_with_filter(&filter)?;
+    /// println!("Predictions : {:?}", predictions);
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn list_with_filter(
+        &self,
+        filter: &PredictionFilter,
+    ) -> Result<ListPredictions, ReplicateError> {
+        let request = self
+            .parent
+            .request_url(Method::GET, filter.url(&self.parent));
 
-        let response = client
-            .get(format!("{}/predictions", self.parent.base_url))
-            .header("Authorization", format!("Token {}", self.parent.auth))
-            .header("User-Agent", &self.parent.user_agent)
-            .send()?;
+        let mut predictions: ListPredictions = self.parent.send_json(request)?;
+        predictions
+            .results
+            .retain(|prediction| filter.matches(prediction));
 
-        if !response.status().is_success() {
-            return Err(ReplicateError::ResponseError(response.text()?));
-        }
+        Ok(predictions)
+    }
 
-        let response_string = response.text()?;
-        let response_struct: ListPredictions = serde_json::from_str(&response_string)?;
+    /// Iterate over all the predictions matching the filter, following the pages lazily. See [`Paginator`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{
+    ///     Replicate, api_definitions::PredictionStatus, config::Config, prediction::PredictionFilter,
+    /// };
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let filter = PredictionFilter::new()
+    ///     .created_after("2023-09-01T00:00:00Z")
+    ///     .status(PredictionStatus::Failed);
+    ///
+    /// for prediction in replicate.predictions.list_all_with_filter(&filter) {
+    ///     println!("Failed prediction : {}", prediction?.id);
+    /// }
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn list_all_with_filter(
+        &self,
+        filter: &PredictionFilter,
+    ) -> Paginator<PredictionsListItem> {
+        let paginator = Paginator::new(self.parent.clone(), filter.url(&self.parent));
 
-        Ok(response_struct)
+        match filter.result_filter() {
+            Some(result_filter) => paginator.with_filter(result_filter),
+            None => paginator,
+        }
     }
 
     /// Get a prediction by passing in the prediction id.
@@ -173,7 +557,7 @@ impl Prediction {
     ///
     /// # Example
     ///
-    /// ```
+    /// ```no_run
     /// use replicate_rust::{Replicate, config::Config};
     ///
     /// let config = Config::default();
@@ -185,33 +569,205 @@ impl Prediction {
     /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
     /// ```
     pub fn get(&self, id: &str) -> Result<GetPrediction, ReplicateError> {
-        let client = reqwest::blocking::Client::new();
-
-        let response = client
-            .get(format!("{}/predictions/{}", self.parent.base_url, id))
-            .header("Authorization", format!("Token {}", self.parent.auth))
-            .header("User-Agent", &self.parent.user_agent)
-            .send()?;
+        self.parent.send_json(get_request(&self.parent, id))
+    }
 
-        if !response.status().is_success() {
-            return Err(ReplicateError::ResponseError(response.text()?));
-        }
+    /// Download the files in the output of a prediction to a directory, created if missing, and return their paths in the order of the output.
+    /// See the [`download`] module for how the files are named, resumed and checked.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config, download::DownloadOptions};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let prediction = replicate.predictions.get("rrr4z55ocneqzikepnug6xezpe")?;
+    ///
+    /// let paths = replicate
+    ///     .predictions
+    ///     .download_outputs(&prediction, "outputs", &DownloadOptions::default())?;
+    /// println!("Downloaded : {:?}", paths);
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn download_outputs(
+        &self,
+        prediction: &GetPrediction,
+        dir: impl AsRef<Path>,
+        options: &DownloadOptions,
+    ) -> Result<Vec<PathBuf>, ReplicateError> {
+        download::download_files(
+            &self.parent,
+            &prediction.output_files(),
+            dir.as_ref(),
+            options,
+        )
+    }
 
-        let response_string = response.text()?;
-        let response_struct: GetPrediction = serde_json::from_str(&response_string)?;
+    /// Download the single file in the output of a prediction into a writer, returning the number of bytes written.
+    ///
+    /// Returns a [`ReplicateError::UnexpectedOutput`] error if the output does not hold exactly one file.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let prediction = replicate.predictions.get("rrr4z55ocneqzikepnug6xezpe")?;
+    ///
+    /// let mut file = std::fs::File::create("output.png")?;
+    /// replicate.predictions.download_output_to(&prediction, &mut file)?;
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn download_output_to(
+        &self,
+        prediction: &GetPrediction,
+        writer: &mut impl Write,
+    ) -> Result<u64, ReplicateError> {
+        let file = download::single_file(prediction)?;
 
-        Ok(response_struct)
+        download::download_to_writer(&self.parent, &file.url, writer)
     }
 }
 
+/// Request getting a prediction, shared with the [async client](crate::nonblocking::prediction) and the prediction clients.
+pub(crate) fn get_request(config: &Config, id: &str) -> Request {
+    config.request(Method::GET, &format!("/predictions/{}", id))
+}
+
+/// Request listing the predictions, shared with the [async client](crate::nonblocking::prediction).
+pub(crate) fn list_request(config: &Config) -> Request {
+    config.request(Method::GET, "/predictions")
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{config::Config, Replicate};
 
     use super::*;
-    use httpmock::{Method::GET, MockServer};
+    use httpmock::{
+        Method::{GET, POST},
+        MockServer,
+    };
     use serde_json::json;
 
+    #[test]
+    fn test_list_with_filter() -> Result<(), ReplicateError> {
+        let server = MockServer::start();
+
+        let prediction = |id: &str, status: &str, source: &str| {
+            json!({
+                "id": id,
+                "version": "b21cbe271e65c1718f2999b038c18b45e21e4fba961181fbfae9342fc53b9e05",
+                "urls": {
+                    "get": format!("https://api.replicate.com/v1/predictions/{}", id),
+                    "cancel": format!("https://api.replicate.com/v1/predictions/{}/cancel", id),
+                },
+                "created_at": "2023-09-02T20:00:40.658234Z",
+                "started_at": "2023-09-02T20:00:41.583803Z",
+                "completed_at": "2023-09-02T20:02:27.648305Z",
+                "source": source,
+                "status": status,
+            })
+        };
+
+        let second_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/predictions")
+                .query_param("cursor", "2");
+            then.status(200).json_body_obj(&json!({
+                "next": None::<String>,
+                "previous": None::<String>,
+                "results": [prediction("c", "failed", "api")],
+            }));
+        });
+        let first_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/predictions")
+                .query_param("created_after", "2023-09-01T00:00:00Z");
+            then.status(200).json_body_obj(&json!({
+                "next": format!("{}/predictions?cursor=2", server.base_url()),
+                "previous": None::<String>,
+                "results": [
+                    prediction("a", "failed", "web"),
+                    prediction("b", "succeeded", "api"),
+                ],
+            }));
+        });
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: server.base_url(),
+            ..Config::default()
+        };
+        let replicate = Replicate::new(config);
+
+        let filter = PredictionFilter::new()
+            .created_after("2023-09-01T00:00:00Z")
+            .status(PredictionStatus::Failed);
+
+        let page = replicate.predictions.list_with_filter(&filter)?;
+        assert_eq!(page.results.len(), 1);
+        assert_eq!(page.results[0].id, "a");
+
+        let ids = replicate
+            .predictions
+            .list_all_with_filter(&filter.source(PredictionSource::Api))
+            .map(|prediction| prediction.map(|prediction| prediction.id))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(ids, vec!["c"]);
+
+        first_mock.assert_hits(2);
+        second_mock.assert_hits(1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_enum_values() -> Result<(), ReplicateError> {
+        let prediction: PredictionsListItem = serde_json::from_value(json!({
+            "id": "jpzd7hm5gfcapbfyt4mqytarku",
+            "version": "b21cbe271e65c1718f2999b038c18b45e21e4fba961181fbfae9342fc53b9e05",
+            "urls": {
+                "get": "https://api.replicate.com/v1/predictions/jpzd7hm5gfcapbfyt4mqytarku",
+                "cancel": "https://api.replicate.com/v1/predictions/jpzd7hm5gfcapbfyt4mqytarku/cancel",
+            },
+            "created_at": "2022-04-26T20:00:40.658234Z",
+            "started_at": "2022-04-26T20:00:41.583803Z",
+            "completed_at": None::<String>,
+            "source": "deployment",
+            "status": "booting",
+        }))?;
+
+        assert_eq!(
+            prediction.source,
+            Some(PredictionSource::Unknown(String::from("deployment")))
+        );
+        assert_eq!(
+            prediction.status,
+            PredictionStatus::Unknown(String::from("booting"))
+        );
+
+        // Unknown values are kept as is, known ones use the API names.
+        assert_eq!(serde_json::to_value(&prediction.status)?, json!("booting"));
+        assert_eq!(
+            serde_json::to_value(PredictionStatus::Succeeded)?,
+            json!("succeeded")
+        );
+        assert_eq!(
+            serde_json::from_value::<WebhookEvents>(json!("completed"))?,
+            WebhookEvents::Completed
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn test_list() -> Result<(), ReplicateError> {
         let server = MockServer::start();
@@ -314,4 +870,217 @@ mod tests {
 
         Ok(())
     }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_timestamps() -> Result<(), ReplicateError> {
+        let prediction = |started_at: Option<&str>| {
+            json!({
+                "id": "rrr4z55ocneqzikepnug6xezpe",
+                "version": "be04660a5b93ef2aff61e3668dedb4cbeb14941e62a3fd5998364a32d613e35e",
+                "urls": {
+                    "get": "https://api.replicate.com/v1/predictions/rrr4z55ocneqzikepnug6xezpe",
+                    "cancel": "https://api.replicate.com/v1/predictions/rrr4z55ocneqzikepnug6xezpe/cancel",
+                },
+                "created_at": "2022-09-13T22:54:18.578761Z",
+                "started_at": started_at,
+                "completed_at": "2022-09-13T22:54:23.236610Z",
+                "source": "api",
+                "status": "succeeded",
+                "input": {},
+            })
+        };
+
+        let result: GetPrediction =
+            serde_json::from_value(prediction(Some("2022-09-13T22:54:19.438525Z")))?;
+
+        assert_eq!(
+            result.queue_duration()?,
+            Some(chrono::Duration::microseconds(859_764))
+        );
+        assert_eq!(
+            result.run_duration()?,
+            Some(chrono::Duration::microseconds(3_798_085))
+        );
+        assert_eq!(
+            result.total_duration()?,
+            Some(chrono::Duration::microseconds(4_657_849))
+        );
+
+        // Invalid timestamps still deserialize, as strings, but are rejected once parsed.
+        let result: GetPrediction =
+            serde_json::from_value(prediction(Some("2022-09-13T22:54:84.438525Z")))?;
+        assert_eq!(
+            result.started_at.as_deref(),
+            Some("2022-09-13T22:54:84.438525Z")
+        );
+        assert!(matches!(
+            result.started_at_parsed(),
+            Err(ReplicateError::InvalidTimestamp(timestamp)) if timestamp == "2022-09-13T22:54:84.438525Z"
+        ));
+        assert!(result.queue_duration().is_err());
+        assert!(result.total_duration()?.is_some());
+
+        // Timestamps which are not set yet are not errors.
+        let result: GetPrediction = serde_json::from_value(prediction(None))?;
+        assert_eq!(result.started_at_parsed()?, None);
+        assert_eq!(result.queue_duration()?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_with_webhook() -> Result<(), ReplicateError> {
+        let server = MockServer::start();
+
+        let post_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/predictions")
+                .json_body_obj(&json!({
+                    "version": "v1",
+                    "input": {"text": "Alice"},
+                    "webhook": "https://example.com/my-webhook",
+                    "webhook_events_filter": ["start", "completed"],
+                }));
+            then.status(201).json_body_obj(&json!({
+                "id": "ufawqhfynnddngldkgtslldrkq",
+                "version": "v1",
+                "urls": {
+                    "get": "https://api.replicate.com/v1/predictions/ufawqhfynnddngldkgtslldrkq",
+                    "cancel": "https://api.replicate.com/v1/predictions/ufawqhfynnddngldkgtslldrkq/cancel",
+                },
+                "created_at": "2022-04-26T22:13:06.224088Z",
+                "status": "starting",
+                "input": {"text": "Alice"},
+                "output": None::<String>,
+                "error": None::<String>,
+                "logs": None::<String>,
+            }));
+        });
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: server.base_url(),
+            ..Config::default()
+        };
+        let replicate = Replicate::new(config);
+
+        let mut input = HashMap::new();
+        input.insert("text", "Alice");
+
+        let result = replicate.predictions.create_with_options(
+            "owner/model:v1",
+            input,
+            PredictionOptions {
+                webhook: Some(String::from("https://example.com/my-webhook")),
+                webhook_events_filter: Some(vec![WebhookEvents::Start, WebhookEvents::Completed]),
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(result.id, "ufawqhfynnddngldkgtslldrkq");
+
+        // Ensure the mocks were called as expected
+        post_mock.assert();
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_official_model() -> Result<(), ReplicateError> {
+        let server = MockServer::start();
+
+        let post_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/models/meta/meta-llama-3-8b-instruct/predictions")
+                .json_body_obj(&json!({"input": {"prompt": "Tell me a story"}}));
+            then.status(201).json_body_obj(&json!({
+                "id": "ufawqhfynnddngldkgtslldrkq",
+                "version": "dp-4a5b1c86f7e545c7a8b4c4e1ce2e4b1c",
+                "urls": {
+                    "get": "https://api.replicate.com/v1/predictions/ufawqhfynnddngldkgtslldrkq",
+                    "cancel": "https://api.replicate.com/v1/predictions/ufawqhfynnddngldkgtslldrkq/cancel",
+                },
+                "created_at": "2022-04-26T22:13:06.224088Z",
+                "status": "starting",
+                "input": {"prompt": "Tell me a story"},
+                "output": None::<String>,
+                "error": None::<String>,
+                "logs": None::<String>,
+            }));
+        });
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: server.base_url(),
+            ..Config::default()
+        };
+        let replicate = Replicate::new(config);
+
+        let mut input = HashMap::new();
+        input.insert("prompt", "Tell me a story");
+
+        let result = replicate
+            .predictions
+            .create("meta/meta-llama-3-8b-instruct", input)?;
+        assert_eq!(result.id, "ufawqhfynnddngldkgtslldrkq");
+
+        // Ensure the mocks were called as expected
+        post_mock.assert();
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_with_files() -> Result<(), ReplicateError> {
+        let server = MockServer::start();
+
+        let post_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/predictions")
+                .json_body_obj(&json!({
+                    "version": "v1",
+                    "input": {
+                        "scale": 4,
+                        "image": "data:image/png;base64,iVBORw0KGgo=",
+                    },
+                }));
+            then.status(201).json_body_obj(&json!({
+                "id": "ufawqhfynnddngldkgtslldrkq",
+                "version": "v1",
+                "urls": {
+                    "get": "https://api.replicate.com/v1/predictions/ufawqhfynnddngldkgtslldrkq",
+                    "cancel": "https://api.replicate.com/v1/predictions/ufawqhfynnddngldkgtslldrkq/cancel",
+                },
+                "created_at": "2022-04-26T22:13:06.224088Z",
+                "status": "starting",
+                "input": {"scale": 4, "image": "data:image/png;base64,iVBORw0KGgo="},
+                "output": None::<String>,
+                "error": None::<String>,
+                "logs": None::<String>,
+            }));
+        });
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: server.base_url(),
+            ..Config::default()
+        };
+        let replicate = Replicate::new(config);
+
+        let mut input = HashMap::new();
+        input.insert("scale", 4);
+
+        let mut files = HashMap::new();
+        files.insert("image", FileInput::bytes(b"\x89PNG\r\n\x1a\n".to_vec()));
+
+        let result = replicate
+            .predictions
+            .create_with_files("owner/model:v1", input, files)?;
+        assert_eq!(result.id, "ufawqhfynnddngldkgtslldrkq");
+
+        // Ensure the mocks were called as expected
+        post_mock.assert();
+
+        Ok(())
+    }
 }
@@ -3,7 +3,7 @@
 //! Used to create a prediction, reload for latest info, cancel it and wait for prediction to complete.
 //!
 //! # Example
-//! ```
+//! ```no_run
 //! use replicate_rust::{Replicate, config::Config};
 //!
 //! let config = Config::default();
@@ -31,31 +31,39 @@
 //! # Ok::<(), replicate_rust::errors::ReplicateError>(())
 //! ```
 
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use reqwest::Method;
 
 use crate::{
     api_definitions::{CreatePrediction, GetPrediction, PredictionStatus, PredictionsUrls},
+    config::Config,
+    download::{self, DownloadOptions},
     errors::ReplicateError,
-    prediction::PredictionPayload,
+    model_ref::ModelRef,
+    prediction::{self, PredictionOptions, PredictionPayload},
+    stream::PredictionStream,
+    transport::Request,
 };
 
-use super::retry::{RetryPolicy, RetryStrategy};
+use super::retry::RetryPolicy;
+
+#[cfg(feature = "webhook-server")]
+use crate::webhooks::server::WebhookServer;
 
 /// Parse a model version string into its model and version parts.
+#[deprecated(
+    since = "0.0.6",
+    note = "use `ModelRef`, which also parses models run by name and version ids alone"
+)]
 pub fn parse_version(s: &str) -> Option<(&str, &str)> {
-    // Split the string at the colon.
-    let mut parts = s.splitn(2, ':');
-
-    // Extract the model and version parts.
-    let model = parts.next()?;
-    let version = parts.next()?;
-
-    // Check if the model part contains a slash.
-    if !model.contains('/') {
-        return None;
+    match s.parse() {
+        Ok(ModelRef::Version { .. }) => s.split_once(':'),
+        _ => None,
     }
-
-    Some((model, version))
 }
 
 /// Helper struct for the Prediction struct. Used to create a prediction, reload for latest info, cancel it and wait for prediction to complete.
@@ -85,7 +93,7 @@ pub struct PredictionClient {
 impl PredictionClient {
     /// Run the prediction of the model version with the given input
     /// # Example
-    /// ```
+    /// ```no_run
     /// use replicate_rust::{Replicate, config::Config};
     ///
     /// let config = Config::default();
@@ -106,38 +114,28 @@ impl PredictionClient {
         version: &str,
         inputs: HashMap<K, V>,
     ) -> Result<PredictionClient, ReplicateError> {
-        // Parse the model version string.
-        let (_model, version) = match parse_version(version) {
-            Some((model, version)) => (model, version),
-            None => return Err(ReplicateError::InvalidVersionString(version.to_string())),
-        };
-
-        // Construct the request payload
-        let payload = PredictionPayload {
-            version: version.to_string(),
-            input: inputs,
-        };
-
-        // println!("Payload : {:?}", &payload);
-        let client = reqwest::blocking::Client::new();
-        let response = client
-            .post(format!("{}/predictions", rep.base_url))
-            .header("Authorization", format!("Token {}", rep.auth))
-            .header("User-Agent", &rep.user_agent)
-            .json(&payload)
-            .send()?;
-
-        if !response.status().is_success() {
-            return Err(ReplicateError::ResponseError(response.text()?));
-        }
+        Self::create_with_options(rep, version, inputs, PredictionOptions::default())
+    }
 
-        if !response.status().is_success() {
-            return Err(ReplicateError::ResponseError(response.text()?));
-        }
+    /// Run the prediction of the model version with the given input and options, e.g. to stream its output.
+    /// See [`Prediction::create_with_options`](crate::prediction::Prediction::create_with_options).
+    pub fn create_with_options<K: serde::Serialize, V: serde::ser::Serialize>(
+        rep: crate::config::Config,
+        version: &str,
+        inputs: HashMap<K, V>,
+        options: PredictionOptions,
+    ) -> Result<PredictionClient, ReplicateError> {
+        let request = create_request(&rep, version, inputs, options)?;
 
-        // println!("Response : {:?}", response.text()?);
+        Self::send_create(rep, request)
+    }
 
-        let result: CreatePrediction = response.json()?;
+    /// Create a prediction by sending the given request, e.g. to the predictions of a deployment.
+    pub(crate) fn send_create(
+        rep: crate::config::Config,
+        request: Request,
+    ) -> Result<PredictionClient, ReplicateError> {
+        let result: CreatePrediction = rep.send_json(request)?;
 
         Ok(Self {
             parent: rep,
@@ -154,7 +152,7 @@ impl PredictionClient {
 
     /// Returns the latest info of the prediction
     // # Example
-    /// ```
+    /// ```no_run
     /// use replicate_rust::{Replicate, config::Config};
     ///
     /// let config = Config::default();
@@ -176,20 +174,9 @@ impl PredictionClient {
     /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
     /// ```
     pub fn reload(&mut self) -> Result<(), ReplicateError> {
-        let client = reqwest::blocking::Client::new();
-
-        let response = client
-            .get(format!("{}/predictions/{}", self.parent.base_url, self.id))
-            .header("Authorization", format!("Token {}", self.parent.auth))
-            .header("User-Agent", &self.parent.user_agent)
-            .send()?;
-
-        if !response.status().is_success() {
-            return Err(ReplicateError::ResponseError(response.text()?));
-        }
-
-        let response_string = response.text()?;
-        let response_struct: GetPrediction = serde_json::from_str(&response_string)?;
+        let response_struct: GetPrediction = self
+            .parent
+            .send_json(prediction::get_request(&self.parent, &self.id))?;
 
         self.id = response_struct.id;
         self.version = response_struct.version;
@@ -205,7 +192,7 @@ impl PredictionClient {
 
     /// Cancel the prediction
     /// # Example
-    /// ```
+    /// ```no_run
     /// use replicate_rust::{Replicate, config::Config};
     ///
     /// let config = Config::default();
@@ -230,28 +217,122 @@ impl PredictionClient {
     /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
     /// ```
     pub fn cancel(&mut self) -> Result<(), ReplicateError> {
-        let client = reqwest::blocking::Client::new();
-        let response = client
-            .post(format!(
-                "{}/predictions/{}/cancel",
-                self.parent.base_url, self.id
-            ))
-            .header("Authorization", format!("Token {}", &self.parent.auth))
-            .header("User-Agent", &self.parent.user_agent)
-            .send()?;
-
-        if !response.status().is_success() {
-            return Err(ReplicateError::ResponseError(response.text()?));
-        }
+        self.parent.send(cancel_request(&self.parent, &self.id))?;
 
         self.reload()?;
 
         Ok(())
     }
 
-    /// Blocks until the predictions are ready and returns the predictions
+    /// Stream the output of the prediction as it is generated, as an iterator of [`StreamEvent`](crate::stream::StreamEvent)s.
+    ///
+    /// The prediction must have been created with [`PredictionOptions::stream`] set, otherwise a [`ReplicateError::StreamingNotEnabled`] error is returned.
     /// # Example
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config, prediction::PredictionOptions, stream::StreamEvent};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// // Creating the inputs
+    /// let mut inputs = std::collections::HashMap::new();
+    /// inputs.insert("prompt", "Tell me a story");
+    ///
+    /// let version = "meta/llama-2-70b-chat:02e509c789964a7ea8736978a43525956ef40397be9033abf9fd2badfe68c9e3";
+    ///
+    /// // Create a new prediction with streaming enabled
+    /// let prediction = replicate.predictions.create_with_options(
+    ///     version,
+    ///     inputs,
+    ///     PredictionOptions {
+    ///         stream: true,
+    ///         ..Default::default()
+    ///     },
+    /// )?;
+    ///
+    /// for event in prediction.stream()? {
+    ///     if let StreamEvent::Output(token) = event? {
+    ///         print!("{}", token);
+    ///     }
+    /// }
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn stream(&self) -> Result<PredictionStream, ReplicateError> {
+        match &self.urls.stream {
+            Some(url) => Ok(PredictionStream::new(self.parent.clone(), url.clone())),
+            None => Err(ReplicateError::StreamingNotEnabled(self.id.clone())),
+        }
+    }
+
+    /// Wait for the prediction to complete, then download the files in its output to a directory, created if missing.
+    /// Returns the paths of the files in the order of the output.
+    ///
+    /// Returns a [`ReplicateError::PredictionFailed`] error if the prediction failed or was canceled.
+    /// See the [`download`] module for how the files are named, resumed and checked.
+    /// # Example
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config, download::DownloadOptions};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// // Creating the inputs
+    /// let mut inputs = std::collections::HashMap::new();
+    /// inputs.insert("prompt", "a  19th century portrait of a wombat gentleman");
+    ///
+    /// let version = "stability-ai/stable-diffusion:27b93a2413e7f36cd83da926f3656280b2931564ff050bf9575f1fdf9bcd7478";
+    ///
+    /// // Create a new prediction
+    /// let prediction = replicate.predictions.create(version, inputs)?;
+    ///
+    /// // Download the generated images once the prediction completes.
+    /// let paths = prediction.download_outputs("outputs", &DownloadOptions::default())?;
+    ///
+    /// println!("Downloaded : {:?}", paths);
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
     /// ```
+    pub fn download_outputs(
+        &self,
+        dir: impl AsRef<Path>,
+        options: &DownloadOptions,
+    ) -> Result<Vec<PathBuf>, ReplicateError> {
+        let prediction = self.wait()?;
+
+        match prediction.status {
+            PredictionStatus::Succeeded => download::download_files(
+                &self.parent,
+                &prediction.output_files(),
+                dir.as_ref(),
+                options,
+            ),
+            status => Err(ReplicateError::PredictionFailed {
+                id: prediction.id,
+                status,
+                error: prediction.error,
+            }),
+        }
+    }
+
+    /// Return a copy of this prediction using the given policy to retry its requests.
+    pub fn with_retry_policy(&self, policy: RetryPolicy) -> Self {
+        let mut client = self.clone();
+        client.parent.retry_policy = policy;
+        client
+    }
+
+    /// Return a copy of this prediction using the given policy to space out the status checks in [`wait`](Self::wait).
+    pub fn with_poll_policy(&self, policy: RetryPolicy) -> Self {
+        let mut client = self.clone();
+        client.parent.poll_policy = policy;
+        client
+    }
+
+    /// Blocks until the predictions are ready and returns the predictions
+    ///
+    /// The status is checked according to the [poll policy](crate::config::Config::poll_policy).
+    /// Returns a [`ReplicateError::WaitTimeout`] error if the policy gives up before the prediction completes.
+    /// # Example
+    /// ```no_run
     /// use replicate_rust::{Replicate, config::Config};
     ///
     /// let config = Config::default();
@@ -274,48 +355,165 @@ impl PredictionClient {
     /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
     /// ```
     pub fn wait(&self) -> Result<GetPrediction, ReplicateError> {
-        // TODO : Implement a retry policy
-        let retry_policy = RetryPolicy::new(5, RetryStrategy::FixedDelay(1000));
-        let client = reqwest::blocking::Client::new();
+        let mut backoff = self.parent.poll_policy.backoff();
+
+        loop {
+            let response_struct: GetPrediction = self
+                .parent
+                .send_json(prediction::get_request(&self.parent, &self.id))?;
+
+            if is_done(&response_struct.status) {
+                return Ok(response_struct);
+            }
+
+            match backoff.next_delay(None) {
+                Some(delay) => std::thread::sleep(delay),
+                None => return Err(ReplicateError::WaitTimeout(self.id.clone())),
+            }
+        }
+    }
+
+    /// Blocks until the `completed` webhook of the prediction is received by the server, and returns the prediction it carries.
+    ///
+    /// The prediction must have been created with the [url](WebhookServer::url) of the server as its webhook.
+    /// The API is still checked according to the [poll policy](crate::config::Config::poll_policy) in case a webhook is lost,
+    /// so use a policy with a long delay to rely on the webhooks only.
+    /// Returns a [`ReplicateError::WaitTimeout`] error if the policy gives up before the prediction completes.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use replicate_rust::{
+    ///     config::Config,
+    ///     prediction::PredictionOptions,
+    ///     retry::{RetryPolicy, RetryStrategy},
+    ///     webhooks::{server::WebhookServer, WebhookVerifier},
+    ///     Replicate,
+    /// };
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let secret = replicate.webhooks.default_secret()?;
+    /// let server = WebhookServer::bind("0.0.0.0:8080", WebhookVerifier::new(&secret.key)?)?
+    ///     .public_url("https://example.com/webhooks");
+    ///
+    /// // Creating the inputs
+    /// let mut inputs = std::collections::HashMap::new();
+    /// inputs.insert("prompt", "a  19th century portrait of a wombat gentleman");
+    ///
+    /// let version = "stability-ai/stable-diffusion:27b93a2413e7f36cd83da926f3656280b2931564ff050bf9575f1fdf9bcd7478";
+    ///
+    /// // Create a new prediction sending its webhooks to the server
+    /// let prediction = replicate.predictions.create_with_options(
+    ///     version,
+    ///     inputs,
+    ///     PredictionOptions {
+    ///         webhook: Some(server.url().to_string()),
+    ///         ..Default::default()
+    ///     },
+    /// )?;
+    ///
+    /// // Only check the API every minute.
+    /// let result = prediction
+    ///     .with_poll_policy(RetryPolicy::new(60, RetryStrategy::FixedDelay(60_000)))
+    ///     .wait_with_webhooks(&server)?;
+    ///
+    /// println!("Result : {:?}", result);
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    #[cfg(feature = "webhook-server")]
+    pub fn wait_with_webhooks(
+        &self,
+        server: &WebhookServer,
+    ) -> Result<GetPrediction, ReplicateError> {
+        use std::sync::mpsc::{sync_channel, RecvTimeoutError};
+
+        // Watch before checking the API, so a webhook received in between is not missed.
+        let (sender, receiver) = sync_channel(1);
+        let _watch = server.watch(&self.id, move |prediction| {
+            let _ = sender.send(prediction);
+        });
+
+        let mut backoff = self.parent.poll_policy.backoff();
 
         loop {
-            let response = client
-                .get(format!("{}/predictions/{}", self.parent.base_url, self.id))
-                .header("Authorization", format!("Token {}", self.parent.auth))
-                .header("User-Agent", &self.parent.user_agent)
-                .send()?;
-
-            if !response.status().is_success() {
-                return Err(ReplicateError::ResponseError(response.text()?));
+            let response_struct: GetPrediction = self
+                .parent
+                .send_json(prediction::get_request(&self.parent, &self.id))?;
+
+            if is_done(&response_struct.status) {
+                return Ok(response_struct);
             }
 
-            let response_string = response.text()?;
-            let response_struct: GetPrediction = serde_json::from_str(&response_string)?;
-
-            match response_struct.status {
-                PredictionStatus::succeeded
-                | PredictionStatus::failed
-                | PredictionStatus::canceled => {
-                    return Ok(response_struct);
-                }
-                PredictionStatus::processing | PredictionStatus::starting => {
-                    // Retry
-                    // TODO : Fix the retry implementation
-                    retry_policy.step();
-                }
+            let delay = backoff
+                .next_delay(None)
+                .ok_or_else(|| ReplicateError::WaitTimeout(self.id.clone()))?;
+
+            match receiver.recv_timeout(delay) {
+                Ok(prediction) => return Ok(prediction),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => std::thread::sleep(delay),
             }
         }
     }
 }
 
+/// Request creating a prediction of a model version, or of the latest version of a model run by name, shared with the [async client](crate::nonblocking::prediction_client).
+pub(crate) fn create_request<K: serde::Serialize, V: serde::ser::Serialize>(
+    config: &Config,
+    version: &str,
+    inputs: HashMap<K, V>,
+    options: PredictionOptions,
+) -> Result<Request, ReplicateError> {
+    // Parse the model string, to pick the endpoint creating the prediction.
+    let model: ModelRef = version.parse()?;
+
+    // Construct the request payload, without version when running the model by name.
+    let version = model.version_id().unwrap_or_default().to_string();
+
+    config
+        .request(Method::POST, &model.predictions_path())
+        .json(&PredictionPayload::new(version, inputs, options))
+}
+
+/// Request canceling a prediction, shared with the [async client](crate::nonblocking::prediction_client).
+pub(crate) fn cancel_request(config: &Config, id: &str) -> Request {
+    config.request(Method::POST, &format!("/predictions/{}/cancel", id))
+}
+
+/// Whether the prediction is over, whether it succeeded or not.
+/// A status added to the API after this version of the crate is not, so that waiting keeps polling.
+pub(crate) fn is_done(status: &PredictionStatus) -> bool {
+    matches!(
+        status,
+        PredictionStatus::Succeeded | PredictionStatus::Failed | PredictionStatus::Canceled
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{config::Config, Replicate};
 
     use super::*;
-    use httpmock::{Method::POST, MockServer};
+    use crate::retry::RetryStrategy;
+    use httpmock::{
+        Method::{GET, POST},
+        MockServer,
+    };
     use serde_json::json;
 
+    #[test]
+    #[allow(deprecated)]
+    fn test_parse_version() {
+        assert_eq!(
+            parse_version("replicate/hello-world:5c7d5dc6"),
+            Some(("replicate/hello-world", "5c7d5dc6"))
+        );
+        assert_eq!(parse_version("meta/meta-llama-3-8b-instruct"), None);
+        assert_eq!(parse_version("hello-world:5c7d5dc6"), None);
+    }
+
     #[test]
     fn test_create() -> Result<(), ReplicateError> {
         let server = MockServer::start();
@@ -367,4 +565,59 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_wait_poll_policy() -> Result<(), ReplicateError> {
+        let server = MockServer::start();
+
+        let get_mock = server.mock(|when, then| {
+            when.method(GET).path("/predictions/p1");
+            then.status(200).json_body_obj(&json!({
+                "id": "p1",
+                "version": "v1",
+                "urls": {
+                    "get": format!("{}/predictions/p1", server.base_url()),
+                    "cancel": format!("{}/predictions/p1/cancel", server.base_url()),
+                },
+                "created_at": "2022-04-26T20:00:40.658234Z",
+                "source": "api",
+                "status": "processing",
+                "input": {"text": "world"},
+                "output": None::<String>,
+                "error": None::<String>,
+                "logs": "",
+            }));
+        });
+
+        let config = Config::builder()
+            .auth("test")
+            .base_url(server.base_url())
+            .build();
+
+        let prediction = PredictionClient {
+            parent: config,
+            id: String::from("p1"),
+            version: String::from("v1"),
+            urls: PredictionsUrls {
+                cancel: format!("{}/predictions/p1/cancel", server.base_url()),
+                get: format!("{}/predictions/p1", server.base_url()),
+                stream: None,
+            },
+            created_at: String::from("2022-04-26T20:00:40.658234Z"),
+            status: PredictionStatus::Starting,
+            input: HashMap::new(),
+            error: None,
+            logs: None,
+        };
+
+        let result = prediction
+            .with_poll_policy(RetryPolicy::new(2, RetryStrategy::FixedDelay(1)))
+            .wait();
+        assert!(matches!(result, Err(ReplicateError::WaitTimeout(id)) if id == "p1"));
+
+        // The first check and the two polls allowed by the policy.
+        get_mock.assert_hits(3);
+
+        Ok(())
+    }
 }
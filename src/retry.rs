@@ -1,34 +1,379 @@
-//! Helper struct for the prediction struct. Used to retry pooling the api for latest prediction status until it is completed.
+//! Retry policies, used to retry failed API requests and to poll the API for the latest prediction status until it is completed.
+//!
+//! Every request sent by the client is retried according to the [`Config::retry_policy`](crate::config::Config::retry_policy) when it fails with
+//! * a `429 Too Many Requests` response,
+//! * a `5xx` response, for idempotent methods only (`GET`, `HEAD`, `PUT`, `DELETE`, `OPTIONS` and `QUERY`), so a prediction is never created twice,
+//! * a connection error, i.e. the request never reached the API.
+//!
+//! When the response carries a `Retry-After` header, its value is used as the delay before the next attempt.
+//! Without a [`max_elapsed`](RetryPolicy::max_elapsed) limit, that delay is capped at [`MAX_RETRY_AFTER`].
+//!
+//! Waiting for a prediction to complete uses the [`Config::poll_policy`](crate::config::Config::poll_policy) instead, to space out the status checks.
+//!
+//! # Example
+//! ```no_run
+//! use std::time::Duration;
+//! use replicate_rust::{
+//!     config::Config,
+//!     retry::{RetryPolicy, RetryStrategy},
+//!     Replicate,
+//! };
+//!
+//! let config = Config::builder()
+//!     .auth("REPLICATE_API_TOKEN")
+//!     .retry_policy(
+//!         RetryPolicy::new(5, RetryStrategy::ExponentialBackoff { initial: 200, max: 5000 })
+//!             .max_elapsed(Duration::from_secs(30)),
+//!     )
+//!     .build();
+//! let replicate = Replicate::new(config);
+//!
+//! // Override the policy for a single call.
+//! let prediction = replicate
+//!     .predictions
+//!     .with_retry_policy(RetryPolicy::none())
+//!     .get("rrr4z55ocneqzikepnug6xezpe")?;
+//! # Ok::<(), replicate_rust::errors::ReplicateError>(())
+//! ```
 
-/// Strategy to use for retrying. Currently only fixed delay is supported.
+use std::time::{Duration, Instant, SystemTime};
+
+use rand::Rng;
+use reqwest::{
+    header::{HeaderMap, RETRY_AFTER},
+    Method,
+};
+
+use crate::errors::ReplicateError;
+
+/// Longest `Retry-After` delay honored by a policy without a [`max_elapsed`](RetryPolicy::max_elapsed) limit.
+pub const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Strategy used to compute the delay between retries. All the durations are in milliseconds.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum RetryStrategy {
-    // Retry with a fixed delay.
+    /// Retry with a fixed delay, in milliseconds.
     FixedDelay(u64),
-    // Retry with an exponential backoff.
-    // ExponentialBackoff(u32),
+
+    /// Retry with a delay doubling after every attempt, starting at `initial` and capped at `max`.
+    ExponentialBackoff {
+        /// Delay before the first retry.
+        initial: u64,
+
+        /// Maximum delay between two attempts.
+        max: u64,
+    },
+
+    /// Retry with a random delay between `base` and three times the previous delay, capped at `max`.
+    /// See [Exponential Backoff And Jitter](https://aws.amazon.com/blogs/architecture/exponential-backoff-and-jitter/).
+    DecorrelatedJitter {
+        /// Minimum delay between two attempts.
+        base: u64,
+
+        /// Maximum delay between two attempts.
+        max: u64,
+    },
 }
 
-/// TODO : Unimplemented
+/// Policy deciding how many times and how long to wait before retrying.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct RetryPolicy {
+    /// Maximum number of retries, not counting the first attempt.
     pub max_retries: u32,
+
+    /// Maximum time spent retrying, measured from the first attempt. No retry is made if its delay would end past this limit.
+    pub max_elapsed: Option<Duration>,
+
+    /// Strategy used to compute the delay between retries.
     pub strategy: RetryStrategy,
-    // step: u32,
+}
+
+impl Default for RetryPolicy {
+    /// Retry up to 3 times within 60 seconds, with a decorrelated jitter between 500 milliseconds and 10 seconds.
+    fn default() -> Self {
+        Self::new(
+            3,
+            RetryStrategy::DecorrelatedJitter {
+                base: 500,
+                max: 10_000,
+            },
+        )
+        .max_elapsed(Duration::from_secs(60))
+    }
 }
 
 impl RetryPolicy {
+    /// Create a new RetryPolicy struct, without limit on the elapsed time.
     pub fn new(max_retries: u32, strategy: RetryStrategy) -> Self {
         Self {
             max_retries,
+            max_elapsed: None,
             strategy,
-            // step: 0,
         }
     }
 
-    pub fn step(&self) {
+    /// A policy that never retries.
+    pub fn none() -> Self {
+        Self::new(0, RetryStrategy::FixedDelay(0))
+    }
+
+    /// Default policy used to poll the status of a prediction: every second, until it completes.
+    pub fn polling() -> Self {
+        Self::new(u32::MAX, RetryStrategy::FixedDelay(1000))
+    }
+
+    /// Set the maximum time spent retrying.
+    pub fn max_elapsed(mut self, max_elapsed: Duration) -> Self {
+        self.max_elapsed = Some(max_elapsed);
+        self
+    }
+
+    /// Delay to wait before the given retry (starting at 0), knowing the delay waited before the previous one.
+    pub fn delay(&self, retry: u32, previous: Duration) -> Duration {
         match self.strategy {
-            RetryStrategy::FixedDelay(delay) => {
-                std::thread::sleep(std::time::Duration::from_millis(delay))
-            } // RetryStrategy::ExponentialBackoff(delay) => delay * attempt,
+            RetryStrategy::FixedDelay(delay) => Duration::from_millis(delay),
+            RetryStrategy::ExponentialBackoff { initial, max } => {
+                let delay = initial.saturating_mul(2u64.saturating_pow(retry));
+                Duration::from_millis(delay.min(max))
+            }
+            RetryStrategy::DecorrelatedJitter { base, max } => {
+                let upper = (previous.as_millis() as u64).saturating_mul(3).max(base);
+                let delay = rand::thread_rng().gen_range(base..=upper);
+                Duration::from_millis(delay.min(max))
+            }
+        }
+    }
+
+    /// Start tracking the retries of a new operation.
+    pub fn backoff(&self) -> Backoff {
+        Backoff {
+            policy: self.clone(),
+            retries: 0,
+            started: Instant::now(),
+            previous: Duration::ZERO,
+        }
+    }
+}
+
+/// Tracks the retries of a single operation against a [`RetryPolicy`].
+#[derive(Clone, Debug)]
+pub struct Backoff {
+    policy: RetryPolicy,
+    retries: u32,
+    started: Instant,
+    previous: Duration,
+}
+
+impl Backoff {
+    /// Delay to wait before the next retry, or `None` if the policy gives up.
+    ///
+    /// The `retry_after` delay requested by the API, if any, takes precedence over the strategy.
+    /// It is capped at [`MAX_RETRY_AFTER`] when the policy has no `max_elapsed` limit.
+    pub fn next_delay(&mut self, retry_after: Option<Duration>) -> Option<Duration> {
+        if self.retries >= self.policy.max_retries {
+            return None;
+        }
+
+        let delay = match retry_after {
+            Some(delay) if self.policy.max_elapsed.is_none() => delay.min(MAX_RETRY_AFTER),
+            Some(delay) => delay,
+            None => self.policy.delay(self.retries, self.previous),
+        };
+
+        if let Some(max_elapsed) = self.policy.max_elapsed {
+            if self.started.elapsed() + delay > max_elapsed {
+                return None;
+            }
+        }
+
+        self.retries += 1;
+        self.previous = delay;
+
+        Some(delay)
+    }
+
+    /// Number of retries made so far.
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    /// Start over, as if no retry was made, e.g. once a reconnected stream receives events again.
+    pub fn reset(&mut self) {
+        self.retries = 0;
+        self.started = Instant::now();
+        self.previous = Duration::ZERO;
+    }
+
+    /// Delay to wait before sending the request again after it failed with the given error,
+    /// or `None` if the error should not be retried (see [`ReplicateError::is_retryable`]) or the policy gives up.
+    pub(crate) fn retry(&mut self, error: &ReplicateError) -> Option<Duration> {
+        if !error.is_retryable() {
+            return None;
+        }
+
+        self.next_delay(
+            error
+                .api_error()
+                .and_then(|error| error.rate_limit.retry_after),
+        )
+    }
+}
+
+/// Whether sending a request with the given method twice has the same effect as sending it once,
+/// so that it can be retried after a server error.
+pub(crate) fn is_idempotent(method: &Method) -> bool {
+    // `QUERY` is a safe method, like `GET` with a body, used by the search endpoints.
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS
+    ) || method.as_str() == "QUERY"
+}
+
+/// Parse the `Retry-After` header, given either in seconds or as an HTTP date.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(
+        date.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        errors::ApiError,
+        transport::{Request, Response},
+    };
+    use reqwest::{header::HeaderValue, StatusCode};
+
+    #[test]
+    fn test_fixed_delay() {
+        let policy = RetryPolicy::new(2, RetryStrategy::FixedDelay(10));
+        let mut backoff = policy.backoff();
+
+        assert_eq!(backoff.next_delay(None), Some(Duration::from_millis(10)));
+        assert_eq!(backoff.next_delay(None), Some(Duration::from_millis(10)));
+        assert_eq!(backoff.next_delay(None), None);
+        assert_eq!(backoff.retries(), 2);
+    }
+
+    #[test]
+    fn test_exponential_backoff() {
+        let policy = RetryPolicy::new(
+            10,
+            RetryStrategy::ExponentialBackoff {
+                initial: 100,
+                max: 1000,
+            },
+        );
+
+        let delays: Vec<_> = (0..5)
+            .map(|retry| policy.delay(retry, Duration::ZERO).as_millis())
+            .collect();
+        assert_eq!(delays, vec![100, 200, 400, 800, 1000]);
+
+        // Never overflows, however many retries.
+        assert_eq!(
+            policy.delay(200, Duration::ZERO),
+            Duration::from_millis(1000)
+        );
+    }
+
+    #[test]
+    fn test_decorrelated_jitter() {
+        let policy = RetryPolicy::new(
+            100,
+            RetryStrategy::DecorrelatedJitter { base: 10, max: 500 },
+        );
+        let mut previous = Duration::ZERO;
+
+        for retry in 0..100 {
+            let delay = policy.delay(retry, previous);
+            assert!(delay >= Duration::from_millis(10));
+            assert!(delay <= Duration::from_millis(500));
+            assert!(delay <= (previous * 3).max(Duration::from_millis(10)));
+            previous = delay;
         }
     }
+
+    #[test]
+    fn test_max_elapsed() {
+        let policy = RetryPolicy::new(10, RetryStrategy::FixedDelay(1000))
+            .max_elapsed(Duration::from_millis(1500));
+        let mut backoff = policy.backoff();
+
+        assert_eq!(backoff.next_delay(None), Some(Duration::from_millis(1000)));
+        // The delay is not actually waited here, but a Retry-After past the limit is refused.
+        assert_eq!(backoff.next_delay(Some(Duration::from_secs(2))), None);
+    }
+
+    #[test]
+    fn test_retry_after_cap() {
+        let policy = RetryPolicy::new(10, RetryStrategy::FixedDelay(1000));
+        let mut backoff = policy.backoff();
+
+        assert_eq!(
+            backoff.next_delay(Some(Duration::from_secs(5))),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            backoff.next_delay(Some(Duration::from_secs(86_400))),
+            Some(MAX_RETRY_AFTER)
+        );
+
+        // With a limit on the elapsed time, a longer delay is refused instead.
+        let mut backoff = policy.max_elapsed(Duration::from_secs(600)).backoff();
+        assert_eq!(
+            backoff.next_delay(Some(Duration::from_secs(120))),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(backoff.next_delay(Some(Duration::from_secs(86_400))), None);
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("3"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(3)));
+
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+
+        let date = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(120));
+        headers.insert(RETRY_AFTER, HeaderValue::from_str(&date).unwrap());
+        let delay = retry_after(&headers).unwrap();
+        assert!(delay > Duration::from_secs(100) && delay <= Duration::from_secs(120));
+    }
+
+    #[test]
+    fn test_retryable_status() {
+        let retryable = |method: Method, status: StatusCode| {
+            let request = Request::new(method.clone(), "");
+            let response = Response::new(status, "");
+            let error = ReplicateError::ResponseError(Box::new(ApiError::new(&request, &response)));
+            error.is_retryable()
+        };
+
+        assert!(retryable(Method::POST, StatusCode::TOO_MANY_REQUESTS));
+        assert!(retryable(Method::GET, StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!retryable(Method::POST, StatusCode::SERVICE_UNAVAILABLE));
+        assert!(retryable(
+            Method::from_bytes(b"QUERY").unwrap(),
+            StatusCode::BAD_GATEWAY
+        ));
+        assert!(!retryable(Method::GET, StatusCode::NOT_IMPLEMENTED));
+        assert!(!retryable(Method::GET, StatusCode::NOT_FOUND));
+    }
 }
@@ -0,0 +1,940 @@
+//! Used to read the inputs and the output of a model version from its OpenAPI schema, and to validate the inputs of a prediction against it.
+//!
+//! Models packaged with [Cog](https://github.com/replicate/cog) describe their inputs and output in the `openapi_schema` of their versions,
+//! under the `Input` and `Output` components. [`VersionSchema`] parses these components, so that invalid inputs are caught locally,
+//! instead of being rejected by the API.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use replicate_rust::{Replicate, config::Config};
+//!
+//! let config = Config::default();
+//! let replicate = Replicate::new(config);
+//!
+//! let version = replicate.models.versions.get(
+//!     "stability-ai",
+//!     "stable-diffusion",
+//!     "27b93a2413e7f36cd83da926f3656280b2931564ff050bf9575f1fdf9bcd7478",
+//! )?;
+//! let schema = version.schema()?;
+//!
+//! for input in &schema.inputs {
+//!     println!("{} ({:?}) : {:?}", input.name, input.input_type, input.description);
+//! }
+//!
+//! let mut inputs = std::collections::HashMap::new();
+//! inputs.insert("prompt", serde_json::json!("a 19th century portrait of a wombat gentleman"));
+//! inputs.insert("num_outputs", serde_json::json!(100));
+//!
+//! // Fails before sending any request, as `num_outputs` is above its maximum.
+//! let result = replicate.predictions.create_validated(
+//!     "stability-ai/stable-diffusion:27b93a2413e7f36cd83da926f3656280b2931564ff050bf9575f1fdf9bcd7478",
+//!     &schema,
+//!     inputs,
+//! );
+//! assert!(result.is_err());
+//!
+//! # Ok::<(), replicate_rust::errors::ReplicateError>(())
+//! ```
+
+use std::{collections::HashMap, fmt};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{api_definitions::GetModelVersion, errors::ReplicateError};
+
+/// Type of an input or of the output of a model, as defined by JSON schema.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaType {
+    /// A string, also used for files with the `uri` format.
+    String,
+    /// An integer.
+    Integer,
+    /// A number, either an integer or a float.
+    Number,
+    /// A boolean.
+    Boolean,
+    /// An array.
+    Array,
+    /// An object.
+    Object,
+
+    /// A type not known by this version of the crate. Any value is accepted for it.
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+impl SchemaType {
+    /// Whether the value has this type. Integers are also numbers.
+    pub fn matches(&self, value: &Value) -> bool {
+        match self {
+            Self::String => value.is_string(),
+            Self::Integer => value.is_i64() || value.is_u64(),
+            Self::Number => value.is_number(),
+            Self::Boolean => value.is_boolean(),
+            Self::Array => value.is_array(),
+            Self::Object => value.is_object(),
+            Self::Unknown(_) => true,
+        }
+    }
+}
+
+impl fmt::Display for SchemaType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::String => write!(f, "string"),
+            Self::Integer => write!(f, "integer"),
+            Self::Number => write!(f, "number"),
+            Self::Boolean => write!(f, "boolean"),
+            Self::Array => write!(f, "array"),
+            Self::Object => write!(f, "object"),
+            Self::Unknown(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Name of the JSON type of a value, used in the validation errors.
+fn value_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(number) if number.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Property of the `Input` or `Output` components, as written in the OpenAPI schema.
+#[derive(Deserialize, Clone, Debug, Default)]
+struct RawSchema {
+    #[serde(rename = "type")]
+    schema_type: Option<SchemaType>,
+    title: Option<String>,
+    description: Option<String>,
+    format: Option<String>,
+    default: Option<Value>,
+    #[serde(rename = "enum")]
+    allowed_values: Option<Vec<Value>>,
+    minimum: Option<f64>,
+    maximum: Option<f64>,
+    items: Option<Box<RawSchema>>,
+    #[serde(default)]
+    properties: HashMap<String, RawSchema>,
+    #[serde(default)]
+    required: Vec<String>,
+    #[serde(rename = "$ref")]
+    reference: Option<String>,
+    #[serde(rename = "allOf", default)]
+    all_of: Vec<RawSchema>,
+    #[serde(rename = "x-order")]
+    order: Option<i64>,
+    #[serde(rename = "x-cog-array-type")]
+    cog_array_type: Option<String>,
+    #[serde(rename = "x-cog-array-display")]
+    cog_array_display: Option<String>,
+}
+
+impl RawSchema {
+    /// Merge the components referenced with `$ref` or `allOf`, e.g. the enums of the inputs, into the property.
+    ///
+    /// Fails with [`ReplicateError::InvalidSchema`] when a component references itself, directly or not.
+    fn resolve(self, components: &Map<String, Value>) -> Result<Self, ReplicateError> {
+        self.resolve_with(components, &mut Vec::new())
+    }
+
+    /// Merge the referenced components, given the names of the components being resolved, from the outermost one.
+    fn resolve_with(
+        mut self,
+        components: &Map<String, Value>,
+        resolving: &mut Vec<String>,
+    ) -> Result<Self, ReplicateError> {
+        let mut referenced = std::mem::take(&mut self.all_of);
+        if let Some(reference) = self.reference.take() {
+            referenced.push(Self {
+                reference: Some(reference),
+                ..Self::default()
+            });
+        }
+
+        for schema in referenced {
+            let schema = match &schema.reference {
+                Some(reference) => {
+                    let name =
+                        reference
+                            .strip_prefix("#/components/schemas/")
+                            .ok_or_else(|| {
+                                ReplicateError::InvalidSchema(format!(
+                                    "unsupported reference {}",
+                                    reference
+                                ))
+                            })?;
+                    let component = components.get(name).ok_or_else(|| {
+                        ReplicateError::InvalidSchema(format!("missing component {}", name))
+                    })?;
+                    if resolving.iter().any(|resolved| resolved == name) {
+                        return Err(ReplicateError::InvalidSchema(format!(
+                            "component {} references itself",
+                            name
+                        )));
+                    }
+
+                    resolving.push(name.to_string());
+                    let schema = serde_json::from_value::<Self>(component.clone())?
+                        .resolve_with(components, resolving);
+                    resolving.pop();
+
+                    schema?
+                }
+                None => schema.resolve_with(components, resolving)?,
+            };
+
+            self.schema_type = self.schema_type.or(schema.schema_type);
+            self.description = self.description.or(schema.description);
+            self.format = self.format.or(schema.format);
+            self.allowed_values = self.allowed_values.or(schema.allowed_values);
+            self.minimum = self.minimum.or(schema.minimum);
+            self.maximum = self.maximum.or(schema.maximum);
+            self.items = self.items.or(schema.items);
+            if self.properties.is_empty() {
+                self.properties = schema.properties;
+            }
+        }
+
+        Ok(self)
+    }
+}
+
+/// An input of a model version.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Input {
+    /// Name of the input, used as the key in the inputs of a prediction.
+    pub name: String,
+
+    /// Human readable name of the input.
+    pub title: Option<String>,
+
+    /// Description of the input.
+    pub description: Option<String>,
+
+    /// Type of the input. `None` if the schema does not specify it, in which case any value is accepted.
+    pub input_type: Option<SchemaType>,
+
+    /// Format of the input, e.g. `uri` for files.
+    pub format: Option<String>,
+
+    /// Type of the items, for array inputs.
+    pub items_type: Option<SchemaType>,
+
+    /// Value used by the model when the input is not set.
+    pub default: Option<Value>,
+
+    /// Whether the input must be set. Inputs with a default value are never required.
+    pub required: bool,
+
+    /// Values allowed for the input, if restricted.
+    pub allowed_values: Option<Vec<Value>>,
+
+    /// Minimum value of a numeric input.
+    pub minimum: Option<f64>,
+
+    /// Maximum value of a numeric input.
+    pub maximum: Option<f64>,
+
+    /// Position of the input in the model's predict function.
+    pub order: Option<i64>,
+}
+
+impl Input {
+    /// Check a value against the input, returning the first problem found.
+    pub fn validate(&self, value: &Value) -> Result<(), InputErrorKind> {
+        if value.is_null() {
+            return match self.required {
+                true => Err(InputErrorKind::Missing),
+                false => Ok(()),
+            };
+        }
+
+        if let Some(input_type) = &self.input_type {
+            if !input_type.matches(value) {
+                return Err(InputErrorKind::WrongType {
+                    expected: input_type.clone(),
+                    found: value_type(value).to_string(),
+                });
+            }
+        }
+
+        if let (Some(items_type), Some(items)) = (&self.items_type, value.as_array()) {
+            if let Some(item) = items.iter().find(|item| !items_type.matches(item)) {
+                return Err(InputErrorKind::WrongItemType {
+                    expected: items_type.clone(),
+                    found: value_type(item).to_string(),
+                });
+            }
+        }
+
+        if let Some(allowed_values) = &self.allowed_values {
+            if !allowed_values.contains(value) {
+                return Err(InputErrorKind::NotAllowed {
+                    allowed: allowed_values.clone(),
+                });
+            }
+        }
+
+        if let Some(number) = value.as_f64() {
+            if let Some(minimum) = self.minimum.filter(|minimum| number < *minimum) {
+                return Err(InputErrorKind::BelowMinimum { minimum });
+            }
+            if let Some(maximum) = self.maximum.filter(|maximum| number > *maximum) {
+                return Err(InputErrorKind::AboveMaximum { maximum });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Output of a model version.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Output {
+    /// Human readable name of the output.
+    pub title: Option<String>,
+
+    /// Type of the output. `None` if the schema does not specify it.
+    pub output_type: Option<SchemaType>,
+
+    /// Format of the output, e.g. `uri` for files.
+    pub format: Option<String>,
+
+    /// Description of the items, for array outputs.
+    pub items: Option<Box<Output>>,
+
+    /// Description of the properties, for object outputs.
+    pub properties: HashMap<String, Output>,
+
+    /// Properties always present, for object outputs.
+    pub required: Vec<String>,
+
+    /// Set to `iterator` when the model yields the items of the output as they are generated.
+    pub cog_array_type: Option<String>,
+
+    /// Set to `concatenate` when the items of the output are chunks of a single text, e.g. for language models.
+    pub cog_array_display: Option<String>,
+}
+
+impl Output {
+    /// Whether the value matches the output: its type, the type of its items, and its required properties.
+    pub fn matches(&self, value: &Value) -> bool {
+        if let Some(output_type) = &self.output_type {
+            if !output_type.matches(value) {
+                return false;
+            }
+        }
+
+        if let (Some(items), Some(values)) = (&self.items, value.as_array()) {
+            if !values.iter().all(|value| items.matches(value)) {
+                return false;
+            }
+        }
+
+        if let Some(object) = value.as_object() {
+            let required = self.required.iter().all(|name| object.contains_key(name));
+            let properties =
+                self.properties
+                    .iter()
+                    .all(|(name, property)| match object.get(name) {
+                        Some(value) => value.is_null() || property.matches(value),
+                        None => true,
+                    });
+
+            return required && properties;
+        }
+
+        true
+    }
+}
+
+impl From<RawSchema> for Output {
+    fn from(schema: RawSchema) -> Self {
+        Self {
+            title: schema.title,
+            output_type: schema.schema_type,
+            format: schema.format,
+            items: schema.items.map(|items| Box::new(Self::from(*items))),
+            properties: schema
+                .properties
+                .into_iter()
+                .map(|(name, property)| (name, Self::from(property)))
+                .collect(),
+            required: schema.required,
+            cog_array_type: schema.cog_array_type,
+            cog_array_display: schema.cog_array_display,
+        }
+    }
+}
+
+/// Inputs and output of a model version, parsed from its OpenAPI schema.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VersionSchema {
+    /// Inputs of the model, in the order of the model's predict function.
+    pub inputs: Vec<Input>,
+
+    /// Output of the model, if described by the schema.
+    pub output: Option<Output>,
+}
+
+impl VersionSchema {
+    /// Parse the `Input` and `Output` components of the OpenAPI schema of a model version.
+    pub fn from_openapi(openapi_schema: &HashMap<String, Value>) -> Result<Self, ReplicateError> {
+        let components = openapi_schema
+            .get("components")
+            .and_then(|components| components.get("schemas"))
+            .and_then(Value::as_object)
+            .ok_or_else(|| {
+                ReplicateError::InvalidSchema(String::from("missing components.schemas"))
+            })?;
+
+        let input = match components.get("Input") {
+            Some(input) => serde_json::from_value::<RawSchema>(input.clone())?,
+            None => RawSchema::default(),
+        };
+
+        let mut inputs = input
+            .properties
+            .into_iter()
+            .map(|(name, property)| {
+                let property = property.resolve(components)?;
+
+                Ok(Input {
+                    required: input.required.contains(&name) && property.default.is_none(),
+                    name,
+                    title: property.title,
+                    description: property.description,
+                    input_type: property.schema_type,
+                    format: property.format,
+                    items_type: property.items.and_then(|items| items.schema_type),
+                    default: property.default,
+                    allowed_values: property.allowed_values,
+                    minimum: property.minimum,
+                    maximum: property.maximum,
+                    order: property.order,
+                })
+            })
+            .collect::<Result<Vec<_>, ReplicateError>>()?;
+        inputs.sort_by(|a, b| {
+            (a.order.is_none(), a.order, &a.name).cmp(&(b.order.is_none(), b.order, &b.name))
+        });
+
+        let output = match components.get("Output") {
+            Some(output) => Some(Output::from(
+                serde_json::from_value::<RawSchema>(output.clone())?.resolve(components)?,
+            )),
+            None => None,
+        };
+
+        Ok(Self { inputs, output })
+    }
+
+    /// Get an input by name.
+    pub fn input(&self, name: &str) -> Option<&Input> {
+        self.inputs.iter().find(|input| input.name == name)
+    }
+
+    /// Check the inputs of a prediction, e.g. the `HashMap` passed to [`Prediction::create`](crate::prediction::Prediction::create).
+    ///
+    /// Returns a [`ReplicateError::InvalidInput`] error listing every invalid input. Inputs unknown to the schema are not checked.
+    pub fn validate<K: Serialize, V: Serialize>(
+        &self,
+        inputs: &HashMap<K, V>,
+    ) -> Result<(), ReplicateError> {
+        let inputs = match serde_json::to_value(inputs)? {
+            Value::Object(inputs) => inputs,
+            _ => Map::new(),
+        };
+
+        self.validate_map(&inputs)
+            .map_err(ReplicateError::InvalidInput)
+    }
+
+    /// Check the inputs of a prediction as a JSON object, returning every invalid input.
+    pub fn validate_map(&self, inputs: &Map<String, Value>) -> Result<(), InputErrors> {
+        let errors: Vec<InputError> = self
+            .inputs
+            .iter()
+            .filter_map(|input| {
+                input
+                    .validate(inputs.get(&input.name).unwrap_or(&Value::Null))
+                    .err()
+                    .map(|kind| InputError {
+                        name: input.name.clone(),
+                        kind,
+                    })
+            })
+            .collect();
+
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(InputErrors(errors)),
+        }
+    }
+}
+
+impl GetModelVersion {
+    /// Parse the inputs and output of the version from its OpenAPI schema. See [`VersionSchema`].
+    pub fn schema(&self) -> Result<VersionSchema, ReplicateError> {
+        VersionSchema::from_openapi(&self.openapi_schema)
+    }
+
+    /// Create a builder for the inputs of a prediction of the version. See [`InputBuilder`].
+    pub fn input_builder(&self) -> Result<InputBuilder, ReplicateError> {
+        Ok(InputBuilder::new(self.schema()?))
+    }
+}
+
+/// Builds the inputs of a prediction, checking every value against the schema of the model version as it is set.
+///
+/// The builder also describes the inputs, e.g. to render a form or to auto-complete the inputs from the schema.
+///
+/// # Example
+///
+/// ```no_run
+/// use replicate_rust::{Replicate, config::Config};
+///
+/// let config = Config::default();
+/// let replicate = Replicate::new(config);
+///
+/// let version = replicate.models.versions.get(
+///     "stability-ai",
+///     "stable-diffusion",
+///     "27b93a2413e7f36cd83da926f3656280b2931564ff050bf9575f1fdf9bcd7478",
+/// )?;
+///
+/// let mut builder = version.input_builder()?;
+/// for input in builder.inputs() {
+///     println!("{} : {:?} (default {:?})", input.name, input.description, input.default);
+/// }
+///
+/// let inputs = builder
+///     .set("prompt", "a 19th century portrait of a wombat gentleman")?
+///     .set("num_outputs", 2)?
+///     .build()?;
+///
+/// let prediction = replicate.predictions.create(
+///     "stability-ai/stable-diffusion:27b93a2413e7f36cd83da926f3656280b2931564ff050bf9575f1fdf9bcd7478",
+///     inputs,
+/// )?;
+/// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputBuilder {
+    schema: VersionSchema,
+    values: Map<String, Value>,
+}
+
+impl InputBuilder {
+    /// Create a builder for the inputs described by the schema.
+    pub fn new(schema: VersionSchema) -> Self {
+        Self {
+            schema,
+            values: Map::new(),
+        }
+    }
+
+    /// Inputs of the model, in the order of the model's predict function.
+    pub fn inputs(&self) -> &[Input] {
+        &self.schema.inputs
+    }
+
+    /// Get an input by name.
+    pub fn input(&self, name: &str) -> Option<&Input> {
+        self.schema.input(name)
+    }
+
+    /// Value of an input: the value that was set, or else its default.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.values
+            .get(name)
+            .or_else(|| self.input(name)?.default.as_ref())
+    }
+
+    /// Set the value of an input.
+    ///
+    /// Returns a [`ReplicateError::InvalidInput`] error if the schema has no such input, or if the value does not match it,
+    /// e.g. a wrong type, a value that is not allowed, or a number out of range. The builder is left unchanged in that case.
+    pub fn set(&mut self, name: &str, value: impl Serialize) -> Result<&mut Self, ReplicateError> {
+        let value = serde_json::to_value(value)?;
+
+        let result = match self.input(name) {
+            Some(input) => input.validate(&value),
+            None => Err(InputErrorKind::NotInSchema),
+        };
+
+        match result {
+            Ok(()) => {
+                self.values.insert(name.to_string(), value);
+                Ok(self)
+            }
+            Err(kind) => Err(ReplicateError::InvalidInput(InputErrors(vec![
+                InputError {
+                    name: name.to_string(),
+                    kind,
+                },
+            ]))),
+        }
+    }
+
+    /// Remove the value of an input, so that the model uses its default.
+    pub fn unset(&mut self, name: &str) -> &mut Self {
+        self.values.remove(name);
+        self
+    }
+
+    /// Build the inputs passed to [`Prediction::create`](crate::prediction::Prediction::create).
+    ///
+    /// Returns a [`ReplicateError::InvalidInput`] error listing the required inputs that are not set.
+    /// Inputs that are not set are left out, so that the model uses their default.
+    pub fn build(&self) -> Result<HashMap<String, Value>, ReplicateError> {
+        self.schema
+            .validate_map(&self.values)
+            .map_err(ReplicateError::InvalidInput)?;
+
+        Ok(self.values.clone().into_iter().collect())
+    }
+}
+
+/// Problem with the value of an input.
+#[derive(Clone, Debug, PartialEq)]
+pub enum InputErrorKind {
+    /// The input is required, but not set.
+    Missing,
+
+    /// The schema has no input with this name.
+    NotInSchema,
+
+    /// The value does not have the type of the input.
+    WrongType {
+        /// Type of the input.
+        expected: SchemaType,
+        /// JSON type of the value.
+        found: String,
+    },
+
+    /// An item of an array does not have the type of the items of the input.
+    WrongItemType {
+        /// Type of the items of the input.
+        expected: SchemaType,
+        /// JSON type of the item.
+        found: String,
+    },
+
+    /// The value is not one of the allowed values of the input.
+    NotAllowed {
+        /// Values allowed for the input.
+        allowed: Vec<Value>,
+    },
+
+    /// The value is below the minimum of the input.
+    BelowMinimum {
+        /// Minimum value of the input.
+        minimum: f64,
+    },
+
+    /// The value is above the maximum of the input.
+    AboveMaximum {
+        /// Maximum value of the input.
+        maximum: f64,
+    },
+}
+
+impl fmt::Display for InputErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing => write!(f, "required input is missing"),
+            Self::NotInSchema => write!(f, "no such input in the schema"),
+            Self::WrongType { expected, found } => {
+                write!(f, "expected {}, found {}", expected, found)
+            }
+            Self::WrongItemType { expected, found } => {
+                write!(f, "expected items of type {}, found {}", expected, found)
+            }
+            Self::NotAllowed { allowed } => {
+                let allowed: Vec<String> = allowed.iter().map(Value::to_string).collect();
+                write!(f, "expected one of {}", allowed.join(", "))
+            }
+            Self::BelowMinimum { minimum } => write!(f, "must be at least {}", minimum),
+            Self::AboveMaximum { maximum } => write!(f, "must be at most {}", maximum),
+        }
+    }
+}
+
+/// Invalid input of a prediction.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputError {
+    /// Name of the input.
+    pub name: String,
+
+    /// Problem with the value of the input.
+    pub kind: InputErrorKind,
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.kind)
+    }
+}
+
+/// Every invalid input of a prediction.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputErrors(pub Vec<InputError>);
+
+impl fmt::Display for InputErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let errors: Vec<String> = self.0.iter().map(InputError::to_string).collect();
+        write!(f, "{}", errors.join("; "))
+    }
+}
+
+impl std::error::Error for InputErrors {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn openapi_schema() -> HashMap<String, Value> {
+        serde_json::from_value(json!({
+            "openapi": "3.0.2",
+            "components": {
+                "schemas": {
+                    "Input": {
+                        "type": "object",
+                        "title": "Input",
+                        "required": ["prompt"],
+                        "properties": {
+                            "prompt": {
+                                "type": "string",
+                                "title": "Prompt",
+                                "x-order": 0,
+                                "description": "Input prompt",
+                            },
+                            "width": {
+                                "type": "integer",
+                                "title": "Width",
+                                "default": 768,
+                                "x-order": 1,
+                                "minimum": 128,
+                                "maximum": 1024,
+                            },
+                            "scheduler": {
+                                "allOf": [{"$ref": "#/components/schemas/scheduler"}],
+                                "default": "DPMSolverMultistep",
+                                "x-order": 2,
+                                "description": "Choose a scheduler.",
+                            },
+                            "guidance_scale": {
+                                "type": "number",
+                                "title": "Guidance Scale",
+                                "default": 7.5,
+                                "x-order": 3,
+                                "maximum": 20,
+                                "minimum": 1,
+                            },
+                            "image": {
+                                "type": "string",
+                                "title": "Image",
+                                "format": "uri",
+                                "x-order": 4,
+                            },
+                        },
+                    },
+                    "Output": {
+                        "type": "array",
+                        "items": {"type": "string", "format": "uri"},
+                        "title": "Output",
+                    },
+                    "scheduler": {
+                        "enum": ["DDIM", "K_EULER", "DPMSolverMultistep"],
+                        "type": "string",
+                        "title": "scheduler",
+                        "description": "An enumeration.",
+                    },
+                },
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse() -> Result<(), ReplicateError> {
+        let schema = VersionSchema::from_openapi(&openapi_schema())?;
+
+        let names: Vec<_> = schema
+            .inputs
+            .iter()
+            .map(|input| input.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["prompt", "width", "scheduler", "guidance_scale", "image"]
+        );
+
+        let prompt = schema.input("prompt").unwrap();
+        assert!(prompt.required);
+        assert_eq!(prompt.input_type, Some(SchemaType::String));
+        assert_eq!(prompt.description.as_deref(), Some("Input prompt"));
+
+        let scheduler = schema.input("scheduler").unwrap();
+        assert!(!scheduler.required);
+        assert_eq!(scheduler.input_type, Some(SchemaType::String));
+        assert_eq!(
+            scheduler.description.as_deref(),
+            Some("Choose a scheduler.")
+        );
+        assert_eq!(scheduler.allowed_values.as_ref().unwrap().len(), 3);
+        assert_eq!(scheduler.default, Some(json!("DPMSolverMultistep")));
+
+        let width = schema.input("width").unwrap();
+        assert_eq!(width.minimum, Some(128.0));
+        assert_eq!(width.maximum, Some(1024.0));
+
+        let output = schema.output.unwrap();
+        assert_eq!(output.output_type, Some(SchemaType::Array));
+        assert_eq!(output.items.unwrap().format.as_deref(), Some("uri"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reference_cycle() -> Result<(), ReplicateError> {
+        let openapi_schema = |components: Value| -> HashMap<String, Value> {
+            serde_json::from_value(json!({"components": {"schemas": components}})).unwrap()
+        };
+
+        // A component referencing itself, directly or through another one.
+        for components in [
+            json!({
+                "Input": {"type": "object", "properties": {"node": {"$ref": "#/components/schemas/node"}}},
+                "node": {"allOf": [{"$ref": "#/components/schemas/node"}]},
+            }),
+            json!({
+                "Input": {"type": "object", "properties": {"a": {"$ref": "#/components/schemas/a"}}},
+                "a": {"allOf": [{"$ref": "#/components/schemas/b"}]},
+                "b": {"$ref": "#/components/schemas/a"},
+            }),
+            json!({"Output": {"$ref": "#/components/schemas/Output"}}),
+        ] {
+            assert!(matches!(
+                VersionSchema::from_openapi(&openapi_schema(components)),
+                Err(ReplicateError::InvalidSchema(_))
+            ));
+        }
+
+        // The same component referenced twice is not a cycle.
+        let schema = VersionSchema::from_openapi(&openapi_schema(json!({
+            "Input": {"type": "object", "properties": {"a": {"$ref": "#/components/schemas/a"}}},
+            "a": {"allOf": [{"$ref": "#/components/schemas/b"}, {"$ref": "#/components/schemas/b"}]},
+            "b": {"type": "string", "enum": ["x", "y"]},
+        })))?;
+        assert_eq!(
+            schema.input("a").unwrap().input_type,
+            Some(SchemaType::String)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate() -> Result<(), ReplicateError> {
+        let schema = VersionSchema::from_openapi(&openapi_schema())?;
+
+        let mut inputs = HashMap::new();
+        inputs.insert("prompt", json!("a wombat"));
+        inputs.insert("guidance_scale", json!(10));
+        schema.validate(&inputs)?;
+
+        let mut inputs = HashMap::new();
+        inputs.insert("width", json!(2048));
+        inputs.insert("scheduler", json!("EULER"));
+        inputs.insert("guidance_scale", json!("high"));
+
+        let errors = match schema.validate(&inputs) {
+            Err(ReplicateError::InvalidInput(errors)) => errors,
+            result => panic!("expected invalid inputs, got {:?}", result),
+        };
+        assert_eq!(
+            errors.0,
+            vec![
+                InputError {
+                    name: String::from("prompt"),
+                    kind: InputErrorKind::Missing,
+                },
+                InputError {
+                    name: String::from("width"),
+                    kind: InputErrorKind::AboveMaximum { maximum: 1024.0 },
+                },
+                InputError {
+                    name: String::from("scheduler"),
+                    kind: InputErrorKind::NotAllowed {
+                        allowed: vec![json!("DDIM"), json!("K_EULER"), json!("DPMSolverMultistep")],
+                    },
+                },
+                InputError {
+                    name: String::from("guidance_scale"),
+                    kind: InputErrorKind::WrongType {
+                        expected: SchemaType::Number,
+                        found: String::from("string"),
+                    },
+                },
+            ]
+        );
+        assert_eq!(
+            errors.to_string(),
+            "prompt: required input is missing; width: must be at most 1024; scheduler: expected one of \"DDIM\", \"K_EULER\", \"DPMSolverMultistep\"; guidance_scale: expected number, found string"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_input_builder() -> Result<(), ReplicateError> {
+        let mut builder = InputBuilder::new(VersionSchema::from_openapi(&openapi_schema())?);
+
+        assert_eq!(builder.inputs().len(), 5);
+        assert_eq!(builder.get("width"), Some(&json!(768)));
+
+        builder.set("width", 512)?;
+        assert_eq!(builder.get("width"), Some(&json!(512)));
+
+        assert!(matches!(
+            builder.set("width", "wide"),
+            Err(ReplicateError::InvalidInput(errors)) if errors.0[0].kind == InputErrorKind::WrongType {
+                expected: SchemaType::Integer,
+                found: String::from("string"),
+            }
+        ));
+        assert!(matches!(
+            builder.set("height", 512),
+            Err(ReplicateError::InvalidInput(errors)) if errors.0[0].kind == InputErrorKind::NotInSchema
+        ));
+
+        // Invalid values leave the builder unchanged.
+        assert_eq!(builder.get("width"), Some(&json!(512)));
+
+        // The prompt is required.
+        assert!(builder.build().is_err());
+
+        let inputs = builder.set("prompt", "a wombat")?.build()?;
+        assert_eq!(inputs.len(), 2);
+        assert_eq!(inputs["prompt"], json!("a wombat"));
+
+        builder.unset("width");
+        assert_eq!(builder.get("width"), Some(&json!(768)));
+        assert_eq!(builder.build()?.len(), 1);
+
+        Ok(())
+    }
+}
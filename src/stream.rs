@@ -0,0 +1,433 @@
+//! Used to consume the output of a prediction as it is generated, through [server-sent events](https://replicate.com/docs/streaming).
+//!
+//! Create the prediction with streaming enabled, then iterate over the events of [`PredictionClient::stream`](crate::prediction_client::PredictionClient::stream).
+//! The stream reconnects with the `Last-Event-ID` header if the connection drops before the prediction completes.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use replicate_rust::{Replicate, config::Config, prediction::PredictionOptions, stream::StreamEvent};
+//!
+//! let config = Config::default();
+//! let replicate = Replicate::new(config);
+//!
+//! // Construct the inputs.
+//! let mut inputs = std::collections::HashMap::new();
+//! inputs.insert("prompt", "Tell me a story");
+//!
+//! let version = "meta/llama-2-70b-chat:02e509c789964a7ea8736978a43525956ef40397be9033abf9fd2badfe68c9e3";
+//!
+//! // Create the prediction with streaming enabled.
+//! let prediction = replicate.predictions.create_with_options(
+//!     version,
+//!     inputs,
+//!     PredictionOptions {
+//!         stream: true,
+//!         ..Default::default()
+//!     },
+//! )?;
+//!
+//! // Print the tokens as they are generated.
+//! for event in prediction.stream()? {
+//!     match event? {
+//!         StreamEvent::Output(token) => print!("{}", token),
+//!         StreamEvent::Logs(logs) => eprintln!("{}", logs),
+//!         StreamEvent::Error(error) => eprintln!("Error : {}", error),
+//!         StreamEvent::Done { .. } => break,
+//!     }
+//! }
+//! # Ok::<(), replicate_rust::errors::ReplicateError>(())
+//! ```
+
+use std::{
+    io::{self, Read},
+    time::Duration,
+};
+
+use reqwest::{
+    header::{HeaderName, HeaderValue, ACCEPT, CACHE_CONTROL},
+    Method,
+};
+
+use crate::{config::Config, errors::ReplicateError, retry::Backoff, transport::Request};
+
+/// An event sent by the API while a prediction is running.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StreamEvent {
+    /// A chunk of output generated by the model, e.g. a token.
+    Output(String),
+
+    /// Logs printed by the model.
+    Logs(String),
+
+    /// An error occurred while running the model. The prediction has failed.
+    Error(String),
+
+    /// The prediction has completed, and the stream is over.
+    Done {
+        /// Why the prediction stopped when it did not succeed, e.g. `canceled`.
+        reason: Option<String>,
+    },
+}
+
+/// Incremental parser for a stream of [server-sent events](https://html.spec.whatwg.org/multipage/server-sent-events.html).
+#[derive(Debug, Default)]
+pub(crate) struct EventParser {
+    buffer: Vec<u8>,
+    event: String,
+    data: String,
+    has_data: bool,
+    id: Option<String>,
+
+    /// Id of the last event received, sent back in the `Last-Event-ID` header when reconnecting.
+    pub(crate) last_event_id: Option<String>,
+
+    /// Reconnection delay requested by the API.
+    pub(crate) retry: Option<Duration>,
+}
+
+impl EventParser {
+    /// Add the bytes received from the API.
+    pub(crate) fn feed(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Drop the partially received event, when the connection drops.
+    pub(crate) fn reset(&mut self) {
+        self.buffer.clear();
+        self.event.clear();
+        self.data.clear();
+        self.has_data = false;
+        self.id = None;
+    }
+
+    /// Parse the complete lines received so far, until an event is dispatched.
+    pub(crate) fn next_event(&mut self) -> Option<StreamEvent> {
+        while let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches('\n').trim_end_matches('\r');
+
+            if line.is_empty() {
+                if let Some(event) = self.dispatch() {
+                    return Some(event);
+                }
+                continue;
+            }
+
+            // Lines starting with a colon are comments, e.g. keep-alives.
+            if line.starts_with(':') {
+                continue;
+            }
+
+            let (field, value) = match line.split_once(':') {
+                Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+                None => (line, ""),
+            };
+
+            match field {
+                "event" => self.event = value.to_string(),
+                "data" => {
+                    if self.has_data {
+                        self.data.push('\n');
+                    }
+                    self.data.push_str(value);
+                    self.has_data = true;
+                }
+                "id" if !value.contains('\0') => self.id = Some(value.to_string()),
+                "retry" => {
+                    if let Ok(retry) = value.parse() {
+                        self.retry = Some(Duration::from_millis(retry));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        None
+    }
+
+    /// Turn the fields received since the last blank line into an event.
+    fn dispatch(&mut self) -> Option<StreamEvent> {
+        let event = std::mem::take(&mut self.event);
+        let data = std::mem::take(&mut self.data);
+        let has_data = std::mem::replace(&mut self.has_data, false);
+
+        // The id only counts once the whole event has been received.
+        if let Some(id) = self.id.take() {
+            self.last_event_id = Some(id);
+        }
+
+        match event.as_str() {
+            "output" if has_data => Some(StreamEvent::Output(data)),
+            "logs" if has_data => Some(StreamEvent::Logs(data)),
+            "error" => Some(StreamEvent::Error(data)),
+            "done" => {
+                let reason = serde_json::from_str::<serde_json::Value>(&data)
+                    .ok()
+                    .and_then(|data| data.get("reason")?.as_str().map(String::from))
+                    .filter(|reason| !reason.is_empty());
+
+                Some(StreamEvent::Done { reason })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Create the request to the stream url, resuming after the given event if any.
+fn stream_request(config: &Config, url: &str, last_event_id: Option<&str>) -> Request {
+    let mut request = config
+        .request_url(Method::GET, url)
+        .header(ACCEPT, HeaderValue::from_static("text/event-stream"))
+        .header(CACHE_CONTROL, HeaderValue::from_static("no-store"));
+
+    if let Some(value) = last_event_id.and_then(|id| HeaderValue::from_str(id).ok()) {
+        request = request.header(HeaderName::from_static("last-event-id"), value);
+    }
+
+    request
+}
+
+/// Error returned when the connection drops and the retry policy gives up reconnecting.
+pub(crate) fn disconnected(error: Option<io::Error>) -> ReplicateError {
+    ReplicateError::IoError(error.unwrap_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "the stream ended before the prediction completed",
+        )
+    }))
+}
+
+/// State of a [`PredictionStream`], shared with the [async stream](crate::nonblocking::stream::PredictionStream),
+/// which only differ in how the body is read and how to wait before reconnecting.
+pub(crate) struct StreamState {
+    pub(crate) config: Config,
+    url: String,
+    parser: EventParser,
+    backoff: Backoff,
+    reconnected: bool,
+    pub(crate) done: bool,
+}
+
+impl StreamState {
+    /// Start reading the events sent to the given url.
+    pub(crate) fn new(config: Config, url: String) -> Self {
+        let backoff = config.retry_policy.backoff();
+
+        Self {
+            config,
+            url,
+            parser: EventParser::default(),
+            backoff,
+            reconnected: false,
+            done: false,
+        }
+    }
+
+    /// Id of the last event received.
+    pub(crate) fn last_event_id(&self) -> Option<&str> {
+        self.parser.last_event_id.as_deref()
+    }
+
+    /// Create the request connecting to the stream, resuming after the last event received.
+    pub(crate) fn request(&self) -> Request {
+        stream_request(&self.config, &self.url, self.last_event_id())
+    }
+
+    /// Parse a chunk of the body.
+    pub(crate) fn feed(&mut self, bytes: &[u8]) {
+        self.parser.feed(bytes);
+    }
+
+    /// Next event parsed from the chunks received so far.
+    pub(crate) fn next_event(&mut self) -> Option<StreamEvent> {
+        let event = self.parser.next_event()?;
+        self.done = matches!(event, StreamEvent::Done { .. });
+
+        // The new connection works, later drops get the full retry policy again.
+        if std::mem::take(&mut self.reconnected) {
+            self.backoff.reset();
+        }
+
+        Some(event)
+    }
+
+    /// Forget the partial event of the dropped connection, returning the delay before reconnecting, or `None` if the retry policy gives up.
+    pub(crate) fn disconnect(&mut self) -> Option<Duration> {
+        self.parser.reset();
+        self.reconnected = true;
+
+        self.backoff.next_delay(self.parser.retry)
+    }
+}
+
+/// Blocking iterator over the events of a prediction. Created with [`PredictionClient::stream`](crate::prediction_client::PredictionClient::stream).
+///
+/// Yields events until the [`StreamEvent::Done`] event, reconnecting according to the [retry policy](crate::config::Config::retry_policy) when the connection drops.
+pub struct PredictionStream {
+    state: StreamState,
+    body: Option<Box<dyn Read + Send>>,
+}
+
+impl PredictionStream {
+    /// Create a new stream reading the events sent to the given url.
+    pub(crate) fn new(config: Config, url: String) -> Self {
+        Self {
+            state: StreamState::new(config, url),
+            body: None,
+        }
+    }
+
+    /// Id of the last event received.
+    pub fn last_event_id(&self) -> Option<&str> {
+        self.state.last_event_id()
+    }
+
+    /// Read the next chunk of the body, connecting first if needed. Returns `false` when the connection dropped.
+    fn read(&mut self) -> Result<bool, ReplicateError> {
+        let body = match &mut self.body {
+            Some(body) => body,
+            None => {
+                let request = self.state.request();
+                self.body
+                    .insert(self.state.config.send_streaming(request)?.body)
+            }
+        };
+
+        let mut buffer = [0; 8192];
+        match body.read(&mut buffer) {
+            Ok(0) => self.disconnect(None).map(|_| false),
+            Ok(read) => {
+                self.state.feed(&buffer[..read]);
+                Ok(true)
+            }
+            Err(error) => self.disconnect(Some(error)).map(|_| false),
+        }
+    }
+
+    /// Drop the connection and wait before reconnecting, or fail if the retry policy gives up.
+    fn disconnect(&mut self, error: Option<io::Error>) -> Result<(), ReplicateError> {
+        self.body = None;
+
+        match self.state.disconnect() {
+            Some(delay) => {
+                std::thread::sleep(delay);
+                Ok(())
+            }
+            None => Err(disconnected(error)),
+        }
+    }
+}
+
+impl Iterator for PredictionStream {
+    type Item = Result<StreamEvent, ReplicateError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.state.done {
+            if let Some(event) = self.state.next_event() {
+                return Some(Ok(event));
+            }
+
+            if let Err(error) = self.read() {
+                self.state.done = true;
+                return Some(Err(error));
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::retry::{RetryPolicy, RetryStrategy};
+    use httpmock::{Method::GET, MockServer};
+
+    #[test]
+    fn test_parser() {
+        let mut parser = EventParser::default();
+        parser.feed(b": keep-alive\n\nevent: output\nid: 1:0\ndata: Once\n\nevent: out");
+
+        assert_eq!(
+            parser.next_event(),
+            Some(StreamEvent::Output("Once".into()))
+        );
+        assert_eq!(parser.next_event(), None);
+        assert_eq!(parser.last_event_id.as_deref(), Some("1:0"));
+
+        parser.feed(b"put\r\nid: 1:1\r\ndata:  upon\r\ndata: a time\r\n\r\n");
+        assert_eq!(
+            parser.next_event(),
+            Some(StreamEvent::Output(" upon\na time".into()))
+        );
+        assert_eq!(parser.last_event_id.as_deref(), Some("1:1"));
+
+        parser.feed(b"retry: 500\nevent: done\ndata: {\"reason\": \"canceled\"}\n\n");
+        assert_eq!(
+            parser.next_event(),
+            Some(StreamEvent::Done {
+                reason: Some("canceled".into())
+            })
+        );
+        assert_eq!(parser.retry, Some(Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn test_reconnect() -> Result<(), ReplicateError> {
+        let server = MockServer::start();
+
+        // Matched first when resuming after the third event.
+        let last_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/stream/p1")
+                .header("last-event-id", "3");
+            then.status(200).body("event: done\nid: 4\ndata: {}\n\n");
+        });
+
+        // Resuming after the second event, the connection drops again.
+        let resume_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/stream/p1")
+                .header("last-event-id", "2");
+            then.status(200)
+                .body("event: output\nid: 3\ndata: time\n\nevent: done\nid: 4\nda");
+        });
+
+        // The connection drops in the middle of the third event.
+        let first_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/stream/p1")
+                .header("accept", "text/event-stream");
+            then.status(200)
+                .body("event: output\nid: 1\ndata: Once\n\nevent: output\nid: 2\ndata: upon\n\nevent: output\nid: 3\ndata: ti");
+        });
+
+        // A single retry is enough for every drop, as the connections received events in between.
+        let config = Config::builder()
+            .auth("test")
+            .base_url(server.base_url())
+            .retry_policy(RetryPolicy::new(1, RetryStrategy::FixedDelay(1)))
+            .build();
+
+        let stream = PredictionStream::new(config, format!("{}/stream/p1", server.base_url()));
+        let events = stream.collect::<Result<Vec<_>, _>>()?;
+
+        assert_eq!(
+            events,
+            vec![
+                StreamEvent::Output("Once".into()),
+                StreamEvent::Output("upon".into()),
+                StreamEvent::Output("time".into()),
+                StreamEvent::Done { reason: None },
+            ]
+        );
+
+        first_mock.assert();
+        resume_mock.assert();
+        last_mock.assert();
+
+        Ok(())
+    }
+}
@@ -3,7 +3,7 @@
 //!
 //! # Example
 //!
-//! ```
+//! ```no_run
 //! use replicate_rust::{Replicate, config::Config, training::TrainingOptions};
 //! use std::collections::HashMap;
 //! 
@@ -21,7 +21,7 @@
 //!         destination: String::from("new_owner/new_name"),
 //!         input,
 //!         webhook: String::from("https://example.com/my-webhook"),
-//!         _webhook_events_filter: None,
+//!         webhook_events_filter: None,
 //!     },
 //! )?;
 //! # Ok::<(), replicate_rust::errors::ReplicateError>(())
@@ -31,7 +31,9 @@
 
 use std::collections::HashMap;
 
-use crate::{api_definitions::{CreateTraining, GetTraining, ListTraining, WebhookEvents}, errors::ReplicateError};
+use reqwest::Method;
+
+use crate::{api_definitions::{CreateTraining, GetTraining, ListTraining, ListTrainingItem, WebhookEvents}, config::Config, errors::ReplicateError, pagination::Paginator, transport::Request};
 
 /// Contains all the options for creating a training.
 pub struct TrainingOptions {
@@ -45,8 +47,8 @@ pub struct TrainingOptions {
     /// An HTTPS URL for receiving a webhook when the training completes. The webhook will be a POST request where the request body is the same as the response body of the get training operation. If there are network problems, we will retry the webhook a few times, so make sure it can be safely called more than once.
     pub webhook: String,
 
-    /// TO only send specifc events to the webhook, use this field. If not specified, all events will be sent. TODO : Add this to the API 
-    pub _webhook_events_filter: Option<WebhookEvents>,
+    /// To only send specific events to the webhook, use this field. If not specified, all events will be sent.
+    pub webhook_events_filter: Option<Vec<WebhookEvents>>,
 }
 
 
@@ -62,6 +64,10 @@ pub struct CreateTrainingPayload {
 
     /// An HTTPS URL for receiving a webhook when the training completes. The webhook will be a POST request where the request body is the same as the response body of the get training operation. If there are network problems, we will retry the webhook a few times, so make sure it can be safely called more than once.
     pub webhook: String,
+
+    /// To only send specific events to the webhook, use this field. If not specified, all events will be sent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook_events_filter: Option<Vec<WebhookEvents>>,
 }
 
 /// Used to interact with the [Training Endpoints](https://replicate.com/docs/reference/http#trainings.create).
@@ -79,6 +85,13 @@ impl Training {
         Self { parent: rep }
     }
 
+    /// Return a copy of this endpoint using the given policy to retry its requests, e.g. to override the client policy for a single call.
+    pub fn with_retry_policy(&self, policy: crate::retry::RetryPolicy) -> Self {
+        let mut config = self.parent.clone();
+        config.retry_policy = policy;
+        Self::new(config)
+    }
+
     /// Create a new training.
     /// 
     /// # Arguments
@@ -89,10 +102,10 @@ impl Training {
     ///     * `destination` - A string representing the desired model to push to in the format {destination_model_owner}/{destination_model_name}. This should be an existing model owned by the user or organization making the API request. If the destination is invalid, the server returns an appropriate 4XX response.
     ///    * `input` - An object containing inputs to the Cog model's train() function.
     ///   * `webhook` - An HTTPS URL for receiving a webhook when the training completes. The webhook will be a POST request where the request body is the same as the response body of the get training operation. If there are network problems, we will retry the webhook a few times, so make sure it can be safely called more than once.
-    ///  * `_webhook_events_filter` - TO only send specifc events to the webhook, use this field. If not specified, all events will be sent. The following events are supported:
+    ///  * `webhook_events_filter` - To only send specific events to the webhook, use this field. If not specified, all events will be sent. The following events are supported: `start`, `output`, `logs` and `completed`.
     /// 
     /// # Example
-    /// ```
+    /// ```no_run
     /// use replicate_rust::{Replicate, config::Config, training::TrainingOptions};
     /// use std::collections::HashMap;
     /// 
@@ -110,7 +123,7 @@ impl Training {
     ///     destination: String::from("new_owner/new_name"),
     ///     input,
     ///     webhook: String::from("https://example.com/my-webhook"),
-    ///     _webhook_events_filter: None,
+    ///     webhook_events_filter: None,
     /// },
     /// )?;
     /// 
@@ -124,32 +137,9 @@ impl Training {
         version_id: &str,
         options: TrainingOptions,
     ) -> Result<CreateTraining, ReplicateError> {
-        let client = reqwest::blocking::Client::new();
-
-        let payload = CreateTrainingPayload {
-            destination: options.destination,
-            input: options.input,
-            webhook: options.webhook,
-        };
-
-        let response = client
-            .post(format!(
-                "{}/models/{}/{}/versions/{}/trainings",
-                self.parent.base_url, model_owner, model_name, version_id,
-            ))
-            .header("Authorization", format!("Token {}", self.parent.auth))
-            .header("User-Agent", &self.parent.user_agent)
-            .json(&payload)
-                .send()?;
-
-        if !response.status().is_success() {
-            return Err(ReplicateError::ResponseError(response.text()?));
-        }
+        let request = create_request(&self.parent, model_owner, model_name, version_id, options)?;
 
-        let response_string = response.text()?;
-        let response_struct: CreateTraining = serde_json::from_str(&response_string)?;
-
-        Ok(response_struct)
+        self.parent.send_json(request)
     }
 
 
@@ -159,7 +149,7 @@ impl Training {
     /// * `training_id` - The ID of the training you want to get.
     /// 
     /// # Example
-    /// ```
+    /// ```no_run
     /// use replicate_rust::{Replicate, config::Config};
     /// 
     /// let config = Config::default();
@@ -171,31 +161,13 @@ impl Training {
     /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
     /// ``` 
     pub fn get(&self, training_id: &str) -> Result<GetTraining, ReplicateError> {
-        let client = reqwest::blocking::Client::new();
-
-        let response = client
-            .get(format!(
-                "{}/trainings/{}",
-                self.parent.base_url, training_id,
-            ))
-            .header("Authorization", format!("Token {}", self.parent.auth))
-            .header("User-Agent", &self.parent.user_agent)
-                .send()?;
-
-        if !response.status().is_success() {
-            return Err(ReplicateError::ResponseError(response.text()?));
-        }
-
-        let response_string = response.text()?;
-        let response_struct: GetTraining = serde_json::from_str(&response_string)?;
-
-        Ok(response_struct)
+        self.parent.send_json(get_request(&self.parent, training_id))
     }
 
     /// Get a paginated list of trainings that you've created with your account. Returns 100 records per page.
     /// 
     /// # Example
-    /// ```
+    /// ```no_run
     /// use replicate_rust::{Replicate, config::Config};
     /// 
     /// let config = Config::default();
@@ -207,22 +179,27 @@ impl Training {
     /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
     /// ```
     pub fn list(&self) -> Result<ListTraining, ReplicateError> {
-        let client = reqwest::blocking::Client::new();
-
-        let response = client
-            .get(format!("{}/trainings", self.parent.base_url,))
-            .header("Authorization", format!("Token {}", self.parent.auth))
-            .header("User-Agent", &self.parent.user_agent)
-                .send()?;
-
-        if !response.status().is_success() {
-            return Err(ReplicateError::ResponseError(response.text()?));
-        }
-
-        let response_string = response.text()?;
-        let response_struct: ListTraining = serde_json::from_str(&response_string)?;
+        self.parent.send_json(list_request(&self.parent))
+    }
 
-        Ok(response_struct)
+    /// Iterate over all the trainings, following the pages lazily. See [`Paginator`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// for training in replicate.trainings.list_all() {
+    ///     println!("Training : {:?}", training?);
+    /// }
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn list_all(&self) -> Paginator<ListTrainingItem> {
+        Paginator::new(self.parent.clone(), list_request(&self.parent).url)
     }
 
     /// Cancel a training.
@@ -231,7 +208,7 @@ impl Training {
     /// * `training_id` - The ID of the training you want to cancel.
     /// 
     /// # Example
-    /// ```
+    /// ```no_run
     /// use replicate_rust::{Replicate, config::Config};
     /// 
     /// let config = Config::default();
@@ -243,27 +220,51 @@ impl Training {
     /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
     /// ```
     pub fn cancel(&self, training_id: &str) -> Result<GetTraining, ReplicateError> {
-        let client = reqwest::blocking::Client::new();
-
-        let response = client
-            .post(format!(
-                "{}/trainings/{}/cancel",
-                self.parent.base_url, training_id
-            ))
-            .header("Authorization", format!("Token {}", self.parent.auth))
-            .header("User-Agent", &self.parent.user_agent)
-                .send()?;
-
-        if !response.status().is_success() {
-            return Err(ReplicateError::ResponseError(response.text()?));
-        }
-        let response_string = response.text()?;
-        let response_struct: GetTraining = serde_json::from_str(&response_string)?;
-
-        Ok(response_struct)
+        self.parent.send_json(cancel_request(&self.parent, training_id))
     }
 }
 
+/// Request creating a training of a model version, shared with the [async client](crate::nonblocking::training).
+pub(crate) fn create_request(
+    config: &Config,
+    model_owner: &str,
+    model_name: &str,
+    version_id: &str,
+    options: TrainingOptions,
+) -> Result<Request, ReplicateError> {
+    let payload = CreateTrainingPayload {
+        destination: options.destination,
+        input: options.input,
+        webhook: options.webhook,
+        webhook_events_filter: options.webhook_events_filter,
+    };
+
+    config
+        .request(
+            Method::POST,
+            &format!(
+                "/models/{}/{}/versions/{}/trainings",
+                model_owner, model_name, version_id
+            ),
+        )
+        .json(&payload)
+}
+
+/// Request getting a training, shared with the [async client](crate::nonblocking::training).
+pub(crate) fn get_request(config: &Config, training_id: &str) -> Request {
+    config.request(Method::GET, &format!("/trainings/{}", training_id))
+}
+
+/// Request listing the trainings, shared with the [async client](crate::nonblocking::training).
+pub(crate) fn list_request(config: &Config) -> Request {
+    config.request(Method::GET, "/trainings")
+}
+
+/// Request canceling a training, shared with the [async client](crate::nonblocking::training).
+pub(crate) fn cancel_request(config: &Config, training_id: &str) -> Request {
+    config.request(Method::POST, &format!("/trainings/{}/cancel", training_id))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{api_definitions::PredictionStatus, config::Config, Replicate};
@@ -280,7 +281,14 @@ mod tests {
         let server = MockServer::start();
 
         let post_mock = server.mock(|when, then| {
-            when.method(POST).path("/models/owner/model/versions/632231d0d49d34d5c4633bd838aee3d81d936e59a886fbf28524702003b4c532/trainings");
+            when.method(POST)
+                .path("/models/owner/model/versions/632231d0d49d34d5c4633bd838aee3d81d936e59a886fbf28524702003b4c532/trainings")
+                .json_body_obj(&json!({
+                    "destination": "new_owner/new_model",
+                    "input": {"text": "..."},
+                    "webhook": "webhook",
+                    "webhook_events_filter": ["completed"],
+                }));
             then.status(200).json_body_obj(&json!( {
                 "id": "zz4ibbonubfz7carwiefibzgga",
                 "version": "{version}",
@@ -316,7 +324,7 @@ mod tests {
                 destination: String::from("new_owner/new_model"),
                 input,
                 webhook: String::from("webhook"),
-                _webhook_events_filter: None,
+                webhook_events_filter: Some(vec![WebhookEvents::Completed]),
             },
         );
 
@@ -366,7 +374,7 @@ mod tests {
             .trainings
             .get("zz4ibbonubfz7carwiefibzgga");
 
-        assert_eq!(result?.status, PredictionStatus::succeeded);
+        assert_eq!(result?.status, PredictionStatus::Succeeded);
         // Ensure the mocks were called as expected
         get_mock.assert();
 
@@ -412,7 +420,7 @@ mod tests {
             .trainings
             .cancel("zz4ibbonubfz7carwiefibzgga")?;
 
-        assert_eq!(result.status, PredictionStatus::canceled);
+        assert_eq!(result.status, PredictionStatus::Canceled);
         // Ensure the mocks were called as expected
         get_mock.assert();
 
@@ -0,0 +1,468 @@
+//! Pluggable HTTP transport used by every endpoint to talk to the API.
+//!
+//! Each endpoint builds a [`Request`] and hands it to the [`Transport`] stored in the [`Config`](crate::config::Config), which returns a [`Response`].
+//! By default requests are sent with `reqwest` through [`HttpTransport`], but any type implementing [`Transport`] can be plugged in,
+//! e.g. an in-memory fake for tests, a recording layer or a middleware stack wrapping another transport.
+//!
+//! # Example
+//!
+//! ```
+//! use replicate_rust::{
+//!     config::Config,
+//!     errors::ReplicateError,
+//!     transport::{Request, Response, Transport},
+//!     Replicate,
+//! };
+//!
+//! /// Answers every request with the same collection.
+//! #[derive(Debug)]
+//! struct FakeTransport;
+//!
+//! impl Transport for FakeTransport {
+//!     fn send(&self, request: Request) -> Result<Response, ReplicateError> {
+//!         assert_eq!(request.url, "https://api.replicate.com/v1/collections/audio-generation");
+//!
+//!         Ok(Response::new(
+//!             reqwest::StatusCode::OK,
+//!             r#"{"name": "Audio generation", "slug": "audio-generation", "description": "", "models": []}"#,
+//!         ))
+//!     }
+//! }
+//!
+//! let config = Config::builder()
+//!     .auth("REPLICATE_API_TOKEN")
+//!     .transport(FakeTransport)
+//!     .build();
+//! let replicate = Replicate::new(config);
+//!
+//! let collection = replicate.collections.get("audio-generation")?;
+//! assert_eq!(collection.name, "Audio generation");
+//! # Ok::<(), ReplicateError>(())
+//! ```
+
+use std::{
+    fmt::Debug,
+    io::{Cursor, Read},
+};
+
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE},
+    Method, StatusCode,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::errors::{ApiError, ReplicateError};
+
+/// A request to the API, as handed to a [`Transport`].
+#[derive(Clone, Debug)]
+pub struct Request {
+    /// HTTP method of the request.
+    pub method: Method,
+
+    /// Absolute url of the request.
+    pub url: String,
+
+    /// Headers of the request, including the `Authorization` and `User-Agent` headers.
+    pub headers: HeaderMap,
+
+    /// Body of the request, if any.
+    pub body: Option<Vec<u8>>,
+}
+
+impl Request {
+    /// Create a new request without headers or body.
+    pub fn new(method: Method, url: impl Into<String>) -> Self {
+        Self {
+            method,
+            url: url.into(),
+            headers: HeaderMap::new(),
+            body: None,
+        }
+    }
+
+    /// Add a header to the request.
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Set the body of the request.
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+
+    /// Serialize the given value as the JSON body of the request.
+    pub fn json<T: Serialize + ?Sized>(self, body: &T) -> Result<Self, ReplicateError> {
+        let body = serde_json::to_vec(body)?;
+
+        Ok(self
+            .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+            .body(body))
+    }
+}
+
+/// A response from the API, as returned by a [`Transport`].
+#[derive(Clone, Debug)]
+pub struct Response {
+    /// HTTP status of the response.
+    pub status: StatusCode,
+
+    /// Headers of the response.
+    pub headers: HeaderMap,
+
+    /// Body of the response.
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// Create a new response without headers.
+    pub fn new(status: StatusCode, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            headers: HeaderMap::new(),
+            body: body.into(),
+        }
+    }
+
+    /// Body of the response as text. Invalid UTF-8 sequences are replaced.
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    /// Parse the body of the response as JSON.
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, ReplicateError> {
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+
+    /// Turn a non 2xx response to the given request into a [`ReplicateError::ResponseError`].
+    pub fn error_for_status(self, request: &Request) -> Result<Self, ReplicateError> {
+        if self.status.is_success() {
+            return Ok(self);
+        }
+
+        Err(ReplicateError::ResponseError(Box::new(ApiError::new(
+            request, &self,
+        ))))
+    }
+}
+
+/// A response from the API whose body is read as it arrives, as returned by [`Transport::send_streaming`].
+pub struct StreamingResponse {
+    /// HTTP status of the response.
+    pub status: StatusCode,
+
+    /// Headers of the response.
+    pub headers: HeaderMap,
+
+    /// Body of the response.
+    pub body: Box<dyn Read + Send>,
+}
+
+impl StreamingResponse {
+    /// Read the rest of the body into a [`Response`].
+    pub fn into_response(mut self) -> Result<Response, ReplicateError> {
+        let mut body = Vec::new();
+        self.body.read_to_end(&mut body)?;
+
+        Ok(Response {
+            status: self.status,
+            headers: self.headers,
+            body,
+        })
+    }
+}
+
+/// Sends requests to the API on behalf of the blocking endpoints.
+pub trait Transport: Debug + Send + Sync {
+    /// Send the request and return the response, whatever its status.
+    fn send(&self, request: Request) -> Result<Response, ReplicateError>;
+
+    /// Send the request and return the response without waiting for its body, e.g. to read a stream of server-sent events.
+    ///
+    /// Defaults to [`send`](Self::send), so the whole body is received before being handed over.
+    fn send_streaming(&self, request: Request) -> Result<StreamingResponse, ReplicateError> {
+        let response = self.send(request)?;
+
+        Ok(StreamingResponse {
+            status: response.status,
+            headers: response.headers,
+            body: Box::new(Cursor::new(response.body)),
+        })
+    }
+}
+
+/// Default blocking transport, sending requests with a `reqwest` client.
+#[derive(Clone, Debug)]
+pub struct HttpTransport {
+    client: reqwest::blocking::Client,
+}
+
+impl HttpTransport {
+    /// Create a new transport sending requests with the given client.
+    pub fn new(client: reqwest::blocking::Client) -> Self {
+        Self { client }
+    }
+}
+
+impl Transport for HttpTransport {
+    fn send(&self, request: Request) -> Result<Response, ReplicateError> {
+        let mut builder = self
+            .client
+            .request(request.method, request.url)
+            .headers(request.headers);
+
+        if let Some(body) = request.body {
+            builder = builder.body(body);
+        }
+
+        let response = builder.send()?;
+
+        Ok(Response {
+            status: response.status(),
+            headers: response.headers().clone(),
+            body: response.bytes()?.to_vec(),
+        })
+    }
+
+    fn send_streaming(&self, request: Request) -> Result<StreamingResponse, ReplicateError> {
+        let mut builder = self
+            .client
+            .request(request.method, request.url)
+            .headers(request.headers);
+
+        if let Some(body) = request.body {
+            builder = builder.body(body);
+        }
+
+        let response = builder.send()?;
+
+        Ok(StreamingResponse {
+            status: response.status(),
+            headers: response.headers().clone(),
+            body: Box::new(response),
+        })
+    }
+}
+
+/// Future returned by an [`AsyncTransport`].
+#[cfg(feature = "async")]
+pub type BoxFuture<'a, T> = std::pin::Pin<Box<dyn std::future::Future<Output = T> + Send + 'a>>;
+
+/// Body of an [`AsyncStreamingResponse`], yielding chunks of bytes as they arrive.
+#[cfg(feature = "async")]
+pub type ByteStream = futures_util::stream::BoxStream<'static, Result<Vec<u8>, ReplicateError>>;
+
+/// A response from the API whose body is read as it arrives, as returned by [`AsyncTransport::send_streaming`].
+#[cfg(feature = "async")]
+pub struct AsyncStreamingResponse {
+    /// HTTP status of the response.
+    pub status: StatusCode,
+
+    /// Headers of the response.
+    pub headers: HeaderMap,
+
+    /// Body of the response.
+    pub body: ByteStream,
+}
+
+#[cfg(feature = "async")]
+impl AsyncStreamingResponse {
+    /// Read the rest of the body into a [`Response`].
+    pub async fn into_response(mut self) -> Result<Response, ReplicateError> {
+        use futures_util::StreamExt;
+
+        let mut body = Vec::new();
+        while let Some(chunk) = self.body.next().await {
+            body.extend(chunk?);
+        }
+
+        Ok(Response {
+            status: self.status,
+            headers: self.headers,
+            body,
+        })
+    }
+}
+
+/// Sends requests to the API on behalf of the async endpoints.
+#[cfg(feature = "async")]
+pub trait AsyncTransport: Debug + Send + Sync {
+    /// Send the request and return the response, whatever its status.
+    fn send(&self, request: Request) -> BoxFuture<'_, Result<Response, ReplicateError>>;
+
+    /// Send the request and return the response without waiting for its body, e.g. to read a stream of server-sent events.
+    ///
+    /// Defaults to [`send`](Self::send), so the whole body is received before being handed over.
+    fn send_streaming(
+        &self,
+        request: Request,
+    ) -> BoxFuture<'_, Result<AsyncStreamingResponse, ReplicateError>> {
+        Box::pin(async move {
+            let response = self.send(request).await?;
+            let body = futures_util::stream::once(async move { Ok(response.body) });
+
+            Ok(AsyncStreamingResponse {
+                status: response.status,
+                headers: response.headers,
+                body: Box::pin(body),
+            })
+        })
+    }
+}
+
+/// Default async transport, sending requests with a `reqwest` client.
+#[cfg(feature = "async")]
+#[derive(Clone, Debug)]
+pub struct AsyncHttpTransport {
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "async")]
+impl AsyncHttpTransport {
+    /// Create a new transport sending requests with the given client.
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncTransport for AsyncHttpTransport {
+    fn send(&self, request: Request) -> BoxFuture<'_, Result<Response, ReplicateError>> {
+        Box::pin(async move {
+            let mut builder = self
+                .client
+                .request(request.method, request.url)
+                .headers(request.headers);
+
+            if let Some(body) = request.body {
+                builder = builder.body(body);
+            }
+
+            let response = builder.send().await?;
+
+            Ok(Response {
+                status: response.status(),
+                headers: response.headers().clone(),
+                body: response.bytes().await?.to_vec(),
+            })
+        })
+    }
+
+    fn send_streaming(
+        &self,
+        request: Request,
+    ) -> BoxFuture<'_, Result<AsyncStreamingResponse, ReplicateError>> {
+        use futures_util::StreamExt;
+
+        Box::pin(async move {
+            let mut builder = self
+                .client
+                .request(request.method, request.url)
+                .headers(request.headers);
+
+            if let Some(body) = request.body {
+                builder = builder.body(body);
+            }
+
+            let response = builder.send().await?;
+            let status = response.status();
+            let headers = response.headers().clone();
+            let body = response.bytes_stream().map(|chunk| Ok(chunk?.to_vec()));
+
+            Ok(AsyncStreamingResponse {
+                status,
+                headers,
+                body: Box::pin(body),
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::{api_definitions::PredictionStatus, config::Config, Replicate};
+
+    /// Records the requests and answers them with canned responses, in order.
+    #[derive(Debug, Default)]
+    struct FakeTransport {
+        requests: Mutex<Vec<Request>>,
+        responses: Mutex<Vec<Response>>,
+    }
+
+    impl Transport for Arc<FakeTransport> {
+        fn send(&self, request: Request) -> Result<Response, ReplicateError> {
+            self.requests.lock().unwrap().push(request);
+            Ok(self.responses.lock().unwrap().remove(0))
+        }
+    }
+
+    #[test]
+    fn test_fake_transport() -> Result<(), ReplicateError> {
+        let fake = Arc::new(FakeTransport::default());
+        fake.responses.lock().unwrap().push(Response::new(
+            StatusCode::OK,
+            serde_json::to_vec(&serde_json::json!({
+                "id": "zz4ibbonubfz7carwiefibzgga",
+                "version": "{version}",
+                "status": "canceled",
+                "input": None::<String>,
+                "output": None::<String>,
+                "error": None::<String>,
+                "logs": None::<String>,
+                "webhook_completed": None::<String>,
+                "started_at": None::<String>,
+                "created_at": "2023-03-28T21:47:58.566434Z",
+                "completed_at": None::<String>,
+            }))?,
+        ));
+
+        let config = Config::builder()
+            .auth("test")
+            .base_url("http://replicate.test")
+            .transport(fake.clone())
+            .build();
+        let replicate = Replicate::new(config);
+
+        let result = replicate.trainings.cancel("zz4ibbonubfz7carwiefibzgga")?;
+        assert_eq!(result.status, PredictionStatus::Canceled);
+
+        let requests = fake.requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, Method::POST);
+        assert_eq!(
+            requests[0].url,
+            "http://replicate.test/trainings/zz4ibbonubfz7carwiefibzgga/cancel"
+        );
+        assert_eq!(requests[0].headers["authorization"], "Token test");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_error_status() {
+        let fake = Arc::new(FakeTransport::default());
+        fake.responses
+            .lock()
+            .unwrap()
+            .push(Response::new(StatusCode::NOT_FOUND, "Not found."));
+
+        let config = Config::builder().auth("test").transport(fake).build();
+        let replicate = Replicate::new(config);
+
+        let result = replicate.predictions.get("missing");
+        let error = result.unwrap_err();
+        assert!(error.is_not_found());
+
+        let error = error.api_error().unwrap();
+        assert_eq!(error.method, Method::GET);
+        assert_eq!(
+            error.url,
+            "https://api.replicate.com/v1/predictions/missing"
+        );
+        assert_eq!(error.body, "Not found.");
+    }
+}
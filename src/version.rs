@@ -3,7 +3,7 @@
 //!
 //! # Example
 //!
-//! ```
+//! ```no_run
 //! use replicate_rust::{Replicate, config::Config};
 //!
 //! let config = Config::default();
@@ -25,9 +25,14 @@
 //! ```
 //!
 
+use reqwest::Method;
+
 use crate::{
     api_definitions::{GetModelVersion, ListModelVersions},
+    config::Config,
     errors::ReplicateError,
+    pagination::Paginator,
+    transport::Request,
 };
 
 /// Used to interact with the [Model Versions Endpoints](https://replicate.com/docs/refer   ence/http#models.versions.get).
@@ -44,8 +49,15 @@ impl Version {
         Self { parent: rep }
     }
 
+    /// Return a copy of this endpoint using the given policy to retry its requests, e.g. to override the client policy for a single call.
+    pub fn with_retry_policy(&self, policy: crate::retry::RetryPolicy) -> Self {
+        let mut config = self.parent.clone();
+        config.retry_policy = policy;
+        Self::new(config)
+    }
+
     /// Get the details of a model version.
-    /// ```
+    /// ```no_run
     /// use replicate_rust::{Replicate, config::Config};
     ///
     /// let config = Config::default();
@@ -67,29 +79,16 @@ impl Version {
         model_name: &str,
         version_id: &str,
     ) -> Result<GetModelVersion, ReplicateError> {
-        let client = reqwest::blocking::Client::new();
-
-        let response = client
-            .get(format!(
-                "{}/models/{}/{}/versions/{}",
-                self.parent.base_url, model_owner, model_name, version_id
-            ))
-            .header("Authorization", format!("Token {}", self.parent.auth))
-            .header("User-Agent", &self.parent.user_agent)
-            .send()?;
-
-        if !response.status().is_success() {
-            return Err(ReplicateError::ResponseError(response.text()?));
-        }
-
-        let response_string = response.text()?;
-        let response_struct: GetModelVersion = serde_json::from_str(&response_string)?;
-
-        Ok(response_struct)
+        self.parent.send_json(get_request(
+            &self.parent,
+            model_owner,
+            model_name,
+            version_id,
+        ))
     }
 
     /// List the versions of a model.
-    /// ```
+    /// ```no_run
     /// use replicate_rust::{Replicate, config::Config};
     ///
     /// let config = Config::default();
@@ -105,24 +104,102 @@ impl Version {
         model_owner: &str,
         model_name: &str,
     ) -> Result<ListModelVersions, ReplicateError> {
-        let client = reqwest::blocking::Client::new();
+        self.parent
+            .send_json(list_request(&self.parent, model_owner, model_name))
+    }
+
+    /// Iterate over all the versions of a model, following the pages lazily. See [`Paginator`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// for version in replicate.models.versions.list_all("replicate", "hello-world") {
+    ///     println!("Version : {:?}", version?);
+    /// }
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn list_all(&self, model_owner: &str, model_name: &str) -> Paginator<GetModelVersion> {
+        Paginator::new(
+            self.parent.clone(),
+            list_request(&self.parent, model_owner, model_name).url,
+        )
+    }
+    /// Delete a version of a model, along with its predictions and their outputs.
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// replicate.models.versions.delete(
+    ///         "acme",
+    ///         "wombat-upscaler",
+    ///         "5797a99edc939ea0e9242d5e8c9cb3bc7d125b1eac21bda852e5cb79ede2cd9b",
+    ///     )?;
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn delete(
+        &self,
+        model_owner: &str,
+        model_name: &str,
+        version_id: &str,
+    ) -> Result<(), ReplicateError> {
+        self.parent.send(delete_request(
+            &self.parent,
+            model_owner,
+            model_name,
+            version_id,
+        ))?;
 
-        let response = client
-            .get(format!(
-                "{}/models/{}/{}/versions",
-                self.parent.base_url, model_owner, model_name
-            ))
-            .header("Authorization", format!("Token {}", self.parent.auth))
-            .header("User-Agent", &self.parent.user_agent)
-            .send()?;
+        Ok(())
+    }
+}
 
-        if !response.status().is_success() {
-            return Err(ReplicateError::ResponseError(response.text()?));
-        }
+/// Path of a version of a model.
+fn version_path(model_owner: &str, model_name: &str, version_id: &str) -> String {
+    format!(
+        "/models/{}/{}/versions/{}",
+        model_owner, model_name, version_id
+    )
+}
 
-        let response_string = response.text()?;
-        let response_struct: ListModelVersions = serde_json::from_str(&response_string)?;
+/// Request getting a version of a model, shared with the [async client](crate::nonblocking::version).
+pub(crate) fn get_request(
+    config: &Config,
+    model_owner: &str,
+    model_name: &str,
+    version_id: &str,
+) -> Request {
+    config.request(
+        Method::GET,
+        &version_path(model_owner, model_name, version_id),
+    )
+}
 
-        Ok(response_struct)
-    }
+/// Request listing the versions of a model, shared with the [async client](crate::nonblocking::version).
+pub(crate) fn list_request(config: &Config, model_owner: &str, model_name: &str) -> Request {
+    config.request(
+        Method::GET,
+        &format!("/models/{}/{}/versions", model_owner, model_name),
+    )
+}
+
+/// Request deleting a version of a model, shared with the [async client](crate::nonblocking::version).
+pub(crate) fn delete_request(
+    config: &Config,
+    model_owner: &str,
+    model_name: &str,
+    version_id: &str,
+) -> Request {
+    config.request(
+        Method::DELETE,
+        &version_path(model_owner, model_name, version_id),
+    )
 }
@@ -0,0 +1,354 @@
+//! Used to receive the [webhooks](https://replicate.com/docs/webhooks) sent by Replicate.
+//!
+//! Every webhook carries `webhook-id`, `webhook-timestamp` and `webhook-signature` headers. A [`WebhookVerifier`] built from the
+//! signing secret of the account checks the signature against the body, and rejects webhooks sent too long ago to prevent replay attacks.
+//!
+//! Enable the `webhook-server` feature to receive the webhooks with the small HTTP server of the [`server`](crate::webhooks::server) module instead.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use replicate_rust::{Replicate, config::Config, webhooks::WebhookVerifier};
+//! # let headers = reqwest::header::HeaderMap::new();
+//! # let body: &[u8] = b"";
+//!
+//! let config = Config::default();
+//! let replicate = Replicate::new(config);
+//!
+//! // Fetch the signing secret once, and keep the verifier around.
+//! let secret = replicate.webhooks.default_secret()?;
+//! let verifier = WebhookVerifier::new(&secret.key)?;
+//!
+//! // In the handler receiving the webhook, with the headers and raw body of the request.
+//! let prediction = verifier.parse_prediction(&headers, body)?;
+//! println!("Prediction {} is {:?}", prediction.id, prediction.status);
+//!
+//! # Ok::<(), replicate_rust::errors::ReplicateError>(())
+//! ```
+//!
+
+use std::{
+    fmt,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hmac::{Hmac, Mac};
+use reqwest::{header::HeaderMap, Method};
+use serde::de::DeserializeOwned;
+use sha2::Sha256;
+
+use crate::{
+    api_definitions::{GetPrediction, GetTraining, GetWebhookSecret},
+    config::Config,
+    errors::ReplicateError,
+    transport::Request,
+};
+
+#[cfg(feature = "webhook-server")]
+pub mod server;
+
+/// Maximum difference between the timestamp of a webhook and the current time accepted by default.
+pub const DEFAULT_TOLERANCE: Duration = Duration::from_secs(5 * 60);
+
+/// Used to interact with the [Webhook Endpoints](https://replicate.com/docs/reference/http#webhooks.default.secret.get).
+#[derive(Clone, Debug)]
+pub struct Webhooks {
+    /// Holds a reference to a Config struct, which contains the base url,  auth token among other settings.
+    pub parent: crate::config::Config,
+}
+
+impl Webhooks {
+    /// Create a new Webhooks struct.
+    pub fn new(rep: crate::config::Config) -> Self {
+        Self { parent: rep }
+    }
+
+    /// Return a copy of this endpoint using the given policy to retry its requests, e.g. to override the client policy for a single call.
+    pub fn with_retry_policy(&self, policy: crate::retry::RetryPolicy) -> Self {
+        let mut config = self.parent.clone();
+        config.retry_policy = policy;
+        Self::new(config)
+    }
+
+    /// Get the secret used to sign the webhooks sent to the account.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use replicate_rust::{Replicate, config::Config};
+    ///
+    /// let config = Config::default();
+    /// let replicate = Replicate::new(config);
+    ///
+    /// let secret = replicate.webhooks.default_secret()?;
+    /// println!("Secret : {}", secret.key);
+    ///
+    /// # Ok::<(), replicate_rust::errors::ReplicateError>(())
+    /// ```
+    pub fn default_secret(&self) -> Result<GetWebhookSecret, ReplicateError> {
+        self.parent.send_json(default_secret_request(&self.parent))
+    }
+}
+
+/// Request getting the default signing secret, shared with the [async client](crate::nonblocking::webhooks).
+pub(crate) fn default_secret_request(config: &Config) -> Request {
+    config.request(Method::GET, "/webhooks/default/secret")
+}
+
+/// Verifies the signature of the webhooks sent by Replicate.
+#[derive(Clone)]
+pub struct WebhookVerifier {
+    key: Vec<u8>,
+    tolerance: Duration,
+}
+
+impl fmt::Debug for WebhookVerifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebhookVerifier")
+            .field("key", &"<redacted>")
+            .field("tolerance", &self.tolerance)
+            .finish()
+    }
+}
+
+impl WebhookVerifier {
+    /// Create a new verifier from the signing secret, as returned by [`Webhooks::default_secret`].
+    ///
+    /// Returns a [`ReplicateError::InvalidWebhook`] error if the secret is not valid base64.
+    pub fn new(secret: &str) -> Result<Self, ReplicateError> {
+        let secret = secret.trim();
+        let key = STANDARD
+            .decode(secret.strip_prefix("whsec_").unwrap_or(secret))
+            .map_err(|_| ReplicateError::InvalidWebhook("the secret is not valid base64".into()))?;
+
+        Ok(Self {
+            key,
+            tolerance: DEFAULT_TOLERANCE,
+        })
+    }
+
+    /// Set the maximum difference between the timestamp of a webhook and the current time, [`DEFAULT_TOLERANCE`] by default.
+    pub fn tolerance(mut self, tolerance: Duration) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Verify a webhook given the headers and the raw body of the request.
+    ///
+    /// Returns a [`ReplicateError::InvalidWebhook`] error if a header is missing, if none of the signatures match,
+    /// or if the webhook was sent outside of the tolerance.
+    pub fn verify(&self, headers: &HeaderMap, body: &[u8]) -> Result<(), ReplicateError> {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .ok_or_else(|| ReplicateError::InvalidWebhook(format!("missing {} header", name)))
+        };
+
+        self.verify_parts(
+            header("webhook-id")?,
+            header("webhook-timestamp")?,
+            header("webhook-signature")?,
+            body,
+        )
+    }
+
+    /// Verify a webhook given the values of its `webhook-id`, `webhook-timestamp` and `webhook-signature` headers, e.g. when they are not stored in a [`HeaderMap`].
+    pub fn verify_parts(
+        &self,
+        id: &str,
+        timestamp: &str,
+        signature: &str,
+        body: &[u8],
+    ) -> Result<(), ReplicateError> {
+        self.verify_at(id, timestamp, signature, body, SystemTime::now())
+    }
+
+    fn verify_at(
+        &self,
+        id: &str,
+        timestamp: &str,
+        signature: &str,
+        body: &[u8],
+        now: SystemTime,
+    ) -> Result<(), ReplicateError> {
+        // The timestamp is not authenticated yet, so a time past the range of `SystemTime` is rejected rather than added.
+        let sent_at = timestamp
+            .trim()
+            .parse::<u64>()
+            .ok()
+            .and_then(|seconds| UNIX_EPOCH.checked_add(Duration::from_secs(seconds)))
+            .ok_or_else(|| {
+                ReplicateError::InvalidWebhook("invalid webhook-timestamp header".into())
+            })?;
+
+        let skew = now
+            .duration_since(sent_at)
+            .unwrap_or_else(|error| error.duration());
+        if skew > self.tolerance {
+            return Err(ReplicateError::InvalidWebhook(
+                "the timestamp is outside of the tolerance".into(),
+            ));
+        }
+
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC can take a key of any size");
+        mac.update(id.as_bytes());
+        mac.update(b".");
+        mac.update(timestamp.as_bytes());
+        mac.update(b".");
+        mac.update(body);
+
+        // The header holds space separated `v1,<signature>` pairs, one per active secret.
+        let matches = signature
+            .split(' ')
+            .filter_map(|signature| signature.split_once(','))
+            .filter(|(version, _)| *version == "v1")
+            .filter_map(|(_, signature)| STANDARD.decode(signature).ok())
+            // Compared in constant time, so the signature cannot be guessed from the response time.
+            .any(|signature| mac.clone().verify_slice(&signature).is_ok());
+
+        if matches {
+            Ok(())
+        } else {
+            Err(ReplicateError::InvalidWebhook(
+                "no signature matches the body".into(),
+            ))
+        }
+    }
+
+    /// Verify a webhook, then parse its body.
+    pub fn parse<T: DeserializeOwned>(
+        &self,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Result<T, ReplicateError> {
+        self.verify(headers, body)?;
+
+        Ok(serde_json::from_slice(body)?)
+    }
+
+    /// Verify a webhook sent for a prediction, then parse its body.
+    pub fn parse_prediction(
+        &self,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Result<GetPrediction, ReplicateError> {
+        self.parse(headers, body)
+    }
+
+    /// Verify a webhook sent for a training, then parse its body.
+    pub fn parse_training(
+        &self,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Result<GetTraining, ReplicateError> {
+        self.parse(headers, body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{api_definitions::PredictionStatus, config::Config, Replicate};
+    use httpmock::{Method::GET, MockServer};
+    use reqwest::header::HeaderValue;
+    use serde_json::json;
+
+    const SECRET: &str = "whsec_cmVwbGljYXRlLXJ1c3Qtd2ViaG9vay10ZXN0LWtleSE=";
+    const BODY: &str = r#"{"id":"ufawqhfynnddngldkgtslldrkq","version":"5c7d5dc6dd8bf75c1acaa8565735e7986bc5b66206b55cca93cb72c9bf15ccaa","urls":{"get":"https://api.replicate.com/v1/predictions/ufawqhfynnddngldkgtslldrkq","cancel":"https://api.replicate.com/v1/predictions/ufawqhfynnddngldkgtslldrkq/cancel"},"created_at":"2022-04-26T22:13:06.224088Z","status":"succeeded","input":{"text":"Alice"},"output":"hello Alice","error":null,"logs":null}"#;
+    const SIGNATURE: &str = "v1,oR8yZjvz4EPkf3UyrJ461i9FRaPTHxwHPvmyIFdlXeg=";
+    const ID: &str = "msg_p5jXN8AQM9LWM0D4loKWxJek";
+    const TIMESTAMP: &str = "1614265330";
+
+    #[test]
+    fn test_default_secret() -> Result<(), ReplicateError> {
+        let server = MockServer::start();
+
+        let get_mock = server.mock(|when, then| {
+            when.method(GET).path("/webhooks/default/secret");
+            then.status(200).json_body_obj(&json!({ "key": SECRET }));
+        });
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: server.base_url(),
+            ..Config::default()
+        };
+        let replicate = Replicate::new(config);
+
+        let secret = replicate.webhooks.default_secret()?;
+        assert_eq!(secret.key, SECRET);
+
+        // Ensure the mocks were called as expected
+        get_mock.assert();
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify() -> Result<(), ReplicateError> {
+        let verifier = WebhookVerifier::new(SECRET)?;
+        let sent_at = UNIX_EPOCH + Duration::from_secs(1614265330);
+        let verify = |signature: &str, body: &str, now: SystemTime| {
+            verifier.verify_at(ID, TIMESTAMP, signature, body.as_bytes(), now)
+        };
+
+        verify(SIGNATURE, BODY, sent_at)?;
+        verify(SIGNATURE, BODY, sent_at + Duration::from_secs(60))?;
+        verify(SIGNATURE, BODY, sent_at - Duration::from_secs(60))?;
+
+        // Any of the signatures may match, e.g. while the secret is rotated.
+        verify(
+            &format!("v1,bm90IGEgc2lnbmF0dXJl {} v2,abc", SIGNATURE),
+            BODY,
+            sent_at,
+        )?;
+
+        assert!(verify(SIGNATURE, &BODY.replace("Alice", "Bob"), sent_at).is_err());
+        assert!(verify("v1,bm90IGEgc2lnbmF0dXJl", BODY, sent_at).is_err());
+        assert!(verify("", BODY, sent_at).is_err());
+        assert!(verify(SIGNATURE, BODY, sent_at + Duration::from_secs(301)).is_err());
+        assert!(verify(SIGNATURE, BODY, sent_at - Duration::from_secs(301)).is_err());
+
+        // A timestamp overflowing the system time is rejected, before the signature is checked.
+        for timestamp in [u64::MAX.to_string(), String::from("-1"), String::new()] {
+            assert!(matches!(
+                verifier.verify_at(ID, &timestamp, SIGNATURE, BODY.as_bytes(), sent_at),
+                Err(ReplicateError::InvalidWebhook(_))
+            ));
+        }
+
+        let verifier = verifier.tolerance(Duration::from_secs(3600));
+        verifier.verify_at(
+            ID,
+            TIMESTAMP,
+            SIGNATURE,
+            BODY.as_bytes(),
+            sent_at + Duration::from_secs(600),
+        )?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_prediction() -> Result<(), ReplicateError> {
+        let verifier = WebhookVerifier::new(SECRET)?.tolerance(Duration::MAX);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("webhook-id", HeaderValue::from_static(ID));
+        headers.insert("webhook-timestamp", HeaderValue::from_static(TIMESTAMP));
+
+        let error = verifier.parse_prediction(&headers, BODY.as_bytes());
+        assert!(matches!(error, Err(ReplicateError::InvalidWebhook(_))));
+
+        headers.insert("webhook-signature", HeaderValue::from_static(SIGNATURE));
+        let prediction = verifier.parse_prediction(&headers, BODY.as_bytes())?;
+
+        assert_eq!(prediction.id, "ufawqhfynnddngldkgtslldrkq");
+        assert_eq!(prediction.status, PredictionStatus::Succeeded);
+
+        Ok(())
+    }
+}
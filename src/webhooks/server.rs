@@ -0,0 +1,631 @@
+//! A small HTTP server receiving the [webhooks](https://replicate.com/docs/webhooks) sent by Replicate. Requires the `webhook-server` feature.
+//!
+//! The server verifies every webhook with a [`WebhookVerifier`], then dispatches it as a [`WebhookEvent`] to the registered handlers
+//! and channels. It can also drive [`PredictionClient::wait_with_webhooks`](crate::prediction_client::PredictionClient::wait_with_webhooks),
+//! which returns as soon as the `completed` webhook of the prediction is received.
+//!
+//! Replicate does not say which event triggered a webhook, so it is inferred from the prediction it carries, see [`WebhookEvent::new`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use replicate_rust::{
+//!     api_definitions::WebhookEvents,
+//!     config::Config,
+//!     prediction::PredictionOptions,
+//!     webhooks::{server::WebhookServer, WebhookVerifier},
+//!     Replicate,
+//! };
+//!
+//! let config = Config::default();
+//! let replicate = Replicate::new(config);
+//!
+//! // Listen on port 8080, reachable from Replicate at the given public url.
+//! let secret = replicate.webhooks.default_secret()?;
+//! let server = WebhookServer::bind("0.0.0.0:8080", WebhookVerifier::new(&secret.key)?)?
+//!     .public_url("https://example.com/webhooks");
+//!
+//! server.on(WebhookEvents::Logs, |event| {
+//!     println!("Logs : {:?}", event.prediction.logs);
+//! });
+//!
+//! // Construct the inputs.
+//! let mut inputs = std::collections::HashMap::new();
+//! inputs.insert("prompt", "a 19th century portrait of a wombat gentleman");
+//!
+//! let version = "stability-ai/stable-diffusion:27b93a2413e7f36cd83da926f3656280b2931564ff050bf9575f1fdf9bcd7478";
+//!
+//! let prediction = replicate.predictions.create_with_options(
+//!     version,
+//!     inputs,
+//!     PredictionOptions {
+//!         webhook: Some(server.url().to_string()),
+//!         ..Default::default()
+//!     },
+//! )?;
+//!
+//! // Returns as soon as the `completed` webhook is received.
+//! let result = prediction.wait_with_webhooks(&server)?;
+//! println!("Result : {:?}", result.output);
+//!
+//! # Ok::<(), replicate_rust::errors::ReplicateError>(())
+//! ```
+//!
+
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{self, Read},
+    net::{SocketAddr, TcpListener, ToSocketAddrs},
+    panic::{self, AssertUnwindSafe},
+    sync::{mpsc, Arc, Mutex, MutexGuard, PoisonError},
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use socket2::{Domain, Protocol, Socket, Type};
+use tiny_http::{Method, Request, Response, Server};
+
+use crate::{
+    api_definitions::{GetPrediction, PredictionStatus, WebhookEvents},
+    errors::ReplicateError,
+};
+
+use super::WebhookVerifier;
+
+/// Maximum size of the body of a webhook, larger requests are answered with `413 Payload Too Large` before being verified.
+pub const MAX_BODY_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Number of completed predictions kept around for a later [`PredictionClient::wait_with_webhooks`](crate::prediction_client::PredictionClient::wait_with_webhooks) call.
+const COMPLETED_CAPACITY: usize = 256;
+
+/// Number of threads receiving the webhooks, so that a slow client does not hold back the others.
+const WORKERS: usize = 4;
+
+/// Time after which a connection sending nothing is dropped.
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A verified webhook received by the [`WebhookServer`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct WebhookEvent {
+    /// Event which triggered the webhook.
+    pub event: WebhookEvents,
+
+    /// Latest state of the prediction.
+    pub prediction: GetPrediction,
+}
+
+impl WebhookEvent {
+    /// Create a new event from the prediction sent in a webhook, inferring the event from its state:
+    /// * `completed` once the prediction succeeded, failed or was canceled,
+    /// * `start` while it is starting,
+    /// * `output` while it is processing, or has a status unknown by this crate, and has some output, `logs` otherwise.
+    pub fn new(prediction: GetPrediction) -> Self {
+        let event = match prediction.status {
+            PredictionStatus::Succeeded | PredictionStatus::Failed | PredictionStatus::Canceled => {
+                WebhookEvents::Completed
+            }
+            PredictionStatus::Starting => WebhookEvents::Start,
+            PredictionStatus::Processing | PredictionStatus::Unknown(_)
+                if prediction.output.is_some() =>
+            {
+                WebhookEvents::Output
+            }
+            PredictionStatus::Processing | PredictionStatus::Unknown(_) => WebhookEvents::Logs,
+        };
+
+        Self { event, prediction }
+    }
+}
+
+type Handler = Box<dyn Fn(&WebhookEvent) + Send + Sync>;
+type Waiter = Box<dyn FnOnce(GetPrediction) + Send>;
+
+/// Receivers of the events, shared between the server thread and the [`WebhookServer`].
+#[derive(Default)]
+struct Dispatcher {
+    handlers: Vec<(Option<WebhookEvents>, Handler)>,
+    subscribers: Vec<mpsc::Sender<WebhookEvent>>,
+    waiters: HashMap<String, Vec<(u64, Waiter)>>,
+    next_token: u64,
+    completed: VecDeque<GetPrediction>,
+}
+
+impl Dispatcher {
+    fn dispatch(&mut self, event: WebhookEvent) {
+        for (filter, handler) in &self.handlers {
+            if filter.is_none() || filter.as_ref() == Some(&event.event) {
+                handler(&event);
+            }
+        }
+
+        // Drop the channels whose receiver is gone.
+        self.subscribers
+            .retain(|subscriber| subscriber.send(event.clone()).is_ok());
+
+        if event.event == WebhookEvents::Completed {
+            match self.waiters.remove(&event.prediction.id) {
+                Some(waiters) => waiters
+                    .into_iter()
+                    .for_each(|(_, waiter)| waiter(event.prediction.clone())),
+                None => {
+                    // Keep it for a waiter registering after the webhook was received.
+                    self.completed.push_back(event.prediction);
+                    if self.completed.len() > COMPLETED_CAPACITY {
+                        self.completed.pop_front();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Register the waiter, returning the token to unregister it.
+    fn watch(&mut self, id: &str, waiter: Waiter) -> u64 {
+        let token = self.next_token;
+        self.next_token += 1;
+
+        match self
+            .completed
+            .iter()
+            .position(|prediction| prediction.id == id)
+        {
+            Some(index) => waiter(self.completed.remove(index).unwrap()),
+            None => self
+                .waiters
+                .entry(id.to_string())
+                .or_default()
+                .push((token, waiter)),
+        }
+
+        token
+    }
+
+    /// Remove the waiter, if it was not called yet.
+    fn unwatch(&mut self, id: &str, token: u64) {
+        if let Some(waiters) = self.waiters.get_mut(id) {
+            waiters.retain(|(waiter_token, _)| *waiter_token != token);
+            if waiters.is_empty() {
+                self.waiters.remove(id);
+            }
+        }
+    }
+}
+
+/// Registration of a waiter with [`WebhookServer::watch`], removed when dropped so that a waiter that was not called does not outlive the wait.
+pub(crate) struct Watch {
+    dispatcher: Arc<Mutex<Dispatcher>>,
+    id: String,
+    token: u64,
+}
+
+impl Drop for Watch {
+    fn drop(&mut self) {
+        self.dispatcher
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .unwatch(&self.id, self.token);
+    }
+}
+
+/// HTTP server receiving the webhooks sent by Replicate, on background threads. The server stops when dropped.
+///
+/// The handlers run on the server threads, one webhook at a time, so they should return quickly and must not register other handlers.
+/// A webhook whose handler panics is answered with `500 Internal Server Error`, the following ones are still dispatched.
+pub struct WebhookServer {
+    server: Arc<Server>,
+    dispatcher: Arc<Mutex<Dispatcher>>,
+    url: String,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl std::fmt::Debug for WebhookServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookServer")
+            .field("local_addr", &self.server.server_addr().to_ip())
+            .field("url", &self.url)
+            .finish()
+    }
+}
+
+impl WebhookServer {
+    /// Start a server listening on the given address, e.g. `0.0.0.0:8080`, verifying the webhooks with the given verifier.
+    pub fn bind(
+        addr: impl ToSocketAddrs,
+        verifier: WebhookVerifier,
+    ) -> Result<Self, ReplicateError> {
+        let server = Server::from_listener(listen(addr)?, None)
+            .map_err(io::Error::other)
+            .map(Arc::new)?;
+        let dispatcher = Arc::new(Mutex::new(Dispatcher::default()));
+
+        let url = match server.server_addr().to_ip() {
+            Some(addr) => format!("http://{}/", addr),
+            None => String::new(),
+        };
+
+        let verifier = Arc::new(verifier);
+        let threads = (0..WORKERS)
+            .map(|_| {
+                let server = server.clone();
+                let verifier = verifier.clone();
+                let dispatcher = dispatcher.clone();
+                std::thread::spawn(move || {
+                    // Ends when the server is unblocked, on drop.
+                    for request in server.incoming_requests() {
+                        handle(request, &verifier, &dispatcher);
+                    }
+                })
+            })
+            .collect();
+
+        Ok(Self {
+            server,
+            dispatcher,
+            url,
+            threads,
+        })
+    }
+
+    /// Set the url Replicate sends the webhooks to, e.g. when the server is behind a proxy or a tunnel.
+    pub fn public_url(mut self, url: impl Into<String>) -> Self {
+        self.url = url.into();
+        self
+    }
+
+    /// Url to pass as the `webhook` of a prediction, the [public url](Self::public_url) if set, or the local address of the server otherwise.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Address the server listens on, e.g. to find the port picked by the OS when binding to port 0.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.server.server_addr().to_ip()
+    }
+
+    /// Call the handler for every webhook of the given event.
+    pub fn on(
+        &self,
+        event: WebhookEvents,
+        handler: impl Fn(&WebhookEvent) + Send + Sync + 'static,
+    ) -> &Self {
+        self.dispatcher()
+            .handlers
+            .push((Some(event), Box::new(handler)));
+        self
+    }
+
+    /// Call the handler for every webhook, whatever the event.
+    pub fn on_any(&self, handler: impl Fn(&WebhookEvent) + Send + Sync + 'static) -> &Self {
+        self.dispatcher().handlers.push((None, Box::new(handler)));
+        self
+    }
+
+    /// Receive every webhook on a channel, from the time of the call.
+    pub fn subscribe(&self) -> mpsc::Receiver<WebhookEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.dispatcher().subscribers.push(sender);
+        receiver
+    }
+
+    /// Call the waiter once with the completed prediction, right away if its webhook was already received.
+    /// The waiter is removed when the returned [`Watch`] is dropped.
+    #[must_use]
+    pub(crate) fn watch(
+        &self,
+        id: &str,
+        waiter: impl FnOnce(GetPrediction) + Send + 'static,
+    ) -> Watch {
+        let token = self.dispatcher().watch(id, Box::new(waiter));
+
+        Watch {
+            dispatcher: self.dispatcher.clone(),
+            id: id.to_string(),
+            token,
+        }
+    }
+
+    fn dispatcher(&self) -> MutexGuard<'_, Dispatcher> {
+        self.dispatcher
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Drop for WebhookServer {
+    fn drop(&mut self) {
+        // Each call stops a single thread.
+        for _ in &self.threads {
+            self.server.unblock();
+        }
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Bind a listener to the first address that works, with a [`READ_TIMEOUT`] inherited by the connections it accepts.
+fn listen(addr: impl ToSocketAddrs) -> Result<TcpListener, io::Error> {
+    let mut error = None;
+
+    for addr in addr.to_socket_addrs()? {
+        let listener = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))
+            .and_then(|socket| {
+                socket.set_reuse_address(true)?;
+                socket.set_read_timeout(Some(READ_TIMEOUT))?;
+                socket.bind(&addr.into())?;
+                socket.listen(128)?;
+                Ok(socket)
+            });
+
+        match listener {
+            Ok(socket) => return Ok(socket.into()),
+            Err(err) => error = Some(err),
+        }
+    }
+
+    Err(error
+        .unwrap_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no address to bind to")))
+}
+
+/// Verify and dispatch a request, answering `401 Unauthorized` if it is not signed by Replicate,
+/// `413 Payload Too Large` if its body is larger than [`MAX_BODY_SIZE`], and `500 Internal Server Error` if a handler panicked.
+fn handle(mut request: Request, verifier: &WebhookVerifier, dispatcher: &Mutex<Dispatcher>) {
+    let status = if *request.method() != Method::Post {
+        405
+    } else {
+        match read_body(&mut request) {
+            Ok(Some(body)) => match receive(&request, &body, verifier) {
+                Ok(event) => {
+                    let dispatched = panic::catch_unwind(AssertUnwindSafe(|| {
+                        dispatcher
+                            .lock()
+                            .unwrap_or_else(PoisonError::into_inner)
+                            .dispatch(event)
+                    }));
+                    if dispatched.is_ok() {
+                        200
+                    } else {
+                        500
+                    }
+                }
+                Err(ReplicateError::InvalidWebhook(_)) => 401,
+                Err(_) => 400,
+            },
+            Ok(None) => 413,
+            Err(_) => 400,
+        }
+    };
+
+    let _ = request.respond(Response::empty(status));
+}
+
+/// Read the body of the request, or `None` if it is larger than [`MAX_BODY_SIZE`].
+fn read_body(request: &mut Request) -> Result<Option<Vec<u8>>, io::Error> {
+    if request
+        .body_length()
+        .is_some_and(|length| length as u64 > MAX_BODY_SIZE)
+    {
+        return Ok(None);
+    }
+
+    let mut body = Vec::new();
+    request
+        .as_reader()
+        .take(MAX_BODY_SIZE + 1)
+        .read_to_end(&mut body)?;
+
+    Ok((body.len() as u64 <= MAX_BODY_SIZE).then_some(body))
+}
+
+/// Verify the webhook sent in the request.
+fn receive(
+    request: &Request,
+    body: &[u8],
+    verifier: &WebhookVerifier,
+) -> Result<WebhookEvent, ReplicateError> {
+    let header = |name: &'static str| {
+        request
+            .headers()
+            .iter()
+            .find(|header| header.field.equiv(name))
+            .map(|header| header.value.as_str().to_string())
+            .ok_or_else(|| ReplicateError::InvalidWebhook(format!("missing {} header", name)))
+    };
+
+    let id = header("webhook-id")?;
+    let timestamp = header("webhook-timestamp")?;
+    let signature = header("webhook-signature")?;
+
+    verifier.verify_parts(&id, &timestamp, &signature, body)?;
+
+    Ok(WebhookEvent::new(serde_json::from_slice(body)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::Config, prediction_client::PredictionClient, retry::RetryPolicy};
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use hmac::{Hmac, Mac};
+    use httpmock::{Method::GET, MockServer};
+    use serde_json::json;
+    use sha2::Sha256;
+    use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+    const KEY: &[u8] = b"replicate-rust-webhook-test-key!";
+
+    fn prediction(status: &str) -> serde_json::Value {
+        json!({
+            "id": "ufawqhfynnddngldkgtslldrkq",
+            "version": "v1",
+            "urls": {
+                "get": "https://api.replicate.com/v1/predictions/ufawqhfynnddngldkgtslldrkq",
+                "cancel": "https://api.replicate.com/v1/predictions/ufawqhfynnddngldkgtslldrkq/cancel",
+            },
+            "created_at": "2022-04-26T22:13:06.224088Z",
+            "status": status,
+            "input": {"text": "Alice"},
+            "output": if status == "succeeded" { json!("hello Alice") } else { json!(null) },
+            "error": None::<String>,
+            "logs": "",
+        })
+    }
+
+    /// Send a webhook signed with the test key, returning the response status.
+    fn send(server: &WebhookServer, body: &serde_json::Value, key: &[u8]) -> u16 {
+        let body = body.to_string();
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            .to_string();
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(key).unwrap();
+        mac.update(format!("msg_1.{}.{}", timestamp, body).as_bytes());
+        let signature = STANDARD.encode(mac.finalize().into_bytes());
+
+        reqwest::blocking::Client::new()
+            .post(server.url())
+            .header("webhook-id", "msg_1")
+            .header("webhook-timestamp", timestamp)
+            .header("webhook-signature", format!("v1,{}", signature))
+            .body(body)
+            .send()
+            .unwrap()
+            .status()
+            .as_u16()
+    }
+
+    #[test]
+    fn test_dispatch() {
+        let verifier = WebhookVerifier::new(&STANDARD.encode(KEY)).unwrap();
+        let server = WebhookServer::bind("127.0.0.1:0", verifier).unwrap();
+
+        let (sender, completed) = mpsc::channel();
+        let sender = Mutex::new(sender);
+        server.on(WebhookEvents::Completed, move |event| {
+            sender.lock().unwrap().send(event.clone()).unwrap();
+        });
+        let events = server.subscribe();
+
+        assert_eq!(send(&server, &prediction("starting"), KEY), 200);
+        assert_eq!(send(&server, &prediction("processing"), KEY), 200);
+        assert_eq!(send(&server, &prediction("succeeded"), KEY), 200);
+        assert_eq!(send(&server, &prediction("succeeded"), b"not the key"), 401);
+
+        let events: Vec<_> = events.try_iter().map(|event| event.event).collect();
+        assert_eq!(
+            events,
+            vec![
+                WebhookEvents::Start,
+                WebhookEvents::Logs,
+                WebhookEvents::Completed
+            ]
+        );
+
+        let event = completed.try_recv().unwrap();
+        assert_eq!(event.prediction.output, Some(json!("hello Alice")));
+        assert!(completed.try_recv().is_err());
+
+        // Oversized bodies are rejected before being verified.
+        let status = reqwest::blocking::Client::new()
+            .post(server.url())
+            .header("webhook-id", "msg_1")
+            .header("webhook-timestamp", "0")
+            .header("webhook-signature", "v1,")
+            .body(vec![b' '; MAX_BODY_SIZE as usize + 1])
+            .send()
+            .unwrap()
+            .status();
+        assert_eq!(status.as_u16(), 413);
+    }
+
+    #[test]
+    fn test_isolation() {
+        let verifier = WebhookVerifier::new(&STANDARD.encode(KEY)).unwrap();
+        let server = WebhookServer::bind("127.0.0.1:0", verifier).unwrap();
+
+        // A client which never sends the body it announced.
+        let mut stalled = std::net::TcpStream::connect(server.local_addr().unwrap()).unwrap();
+        std::io::Write::write_all(
+            &mut stalled,
+            b"POST / HTTP/1.1\r\nHost: localhost\r\nContent-Length: 100\r\n\r\n",
+        )
+        .unwrap();
+
+        server.on(WebhookEvents::Start, |_| panic!("handler failure"));
+        let events = server.subscribe();
+
+        assert_eq!(send(&server, &prediction("starting"), KEY), 500);
+        assert_eq!(send(&server, &prediction("succeeded"), KEY), 200);
+
+        let events: Vec<_> = events.try_iter().map(|event| event.event).collect();
+        assert_eq!(events, vec![WebhookEvents::Completed]);
+    }
+
+    #[test]
+    fn test_wait_with_webhooks() -> Result<(), ReplicateError> {
+        let api = MockServer::start();
+        let get_mock = api.mock(|when, then| {
+            when.method(GET)
+                .path("/predictions/ufawqhfynnddngldkgtslldrkq");
+            then.status(200).json_body_obj(&prediction("processing"));
+        });
+
+        let verifier = WebhookVerifier::new(&STANDARD.encode(KEY))?;
+        let server = WebhookServer::bind("127.0.0.1:0", verifier)?;
+
+        let config = Config {
+            auth: String::from("test"),
+            base_url: api.base_url(),
+            ..Config::default()
+        };
+        let prediction_client = PredictionClient {
+            parent: config,
+            id: String::from("ufawqhfynnddngldkgtslldrkq"),
+            version: String::from("v1"),
+            urls: serde_json::from_value(prediction("processing")["urls"].clone())?,
+            created_at: String::from("2022-04-26T22:13:06.224088Z"),
+            status: PredictionStatus::Processing,
+            input: HashMap::new(),
+            error: None,
+            logs: None,
+        }
+        // The API would only be checked again after a minute.
+        .with_poll_policy(RetryPolicy::new(
+            1,
+            crate::retry::RetryStrategy::FixedDelay(60_000),
+        ));
+
+        let started = Instant::now();
+        let result = std::thread::scope(|scope| {
+            scope.spawn(|| {
+                std::thread::sleep(Duration::from_millis(100));
+                send(&server, &prediction("succeeded"), KEY);
+            });
+
+            prediction_client.wait_with_webhooks(&server)
+        })?;
+
+        assert_eq!(result.status, PredictionStatus::Succeeded);
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        // A webhook received before waiting is not missed.
+        send(&server, &prediction("succeeded"), KEY);
+        let result = prediction_client.wait_with_webhooks(&server)?;
+        assert_eq!(result.status, PredictionStatus::Succeeded);
+
+        get_mock.assert_hits(2);
+
+        // Waiters are removed once the wait is over, even when the webhook never arrives.
+        let result = prediction_client
+            .with_poll_policy(RetryPolicy::new(
+                1,
+                crate::retry::RetryStrategy::FixedDelay(10),
+            ))
+            .wait_with_webhooks(&server);
+        assert!(matches!(result, Err(ReplicateError::WaitTimeout(_))));
+        assert!(server.dispatcher().waiters.is_empty());
+
+        Ok(())
+    }
+}
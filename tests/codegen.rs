@@ -0,0 +1,57 @@
+//! Compiles bindings generated from a checked-in schema, with names that are not valid Rust identifiers as they are.
+
+use replicate_rust::{codegen::Bindings, errors::ReplicateError};
+
+include!("fixtures/bindings.rs");
+
+#[test]
+fn test_bindings_up_to_date() -> Result<(), ReplicateError> {
+    let code = Bindings::from_file(
+        crate_::VERSION,
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/bindings.json"),
+    )?
+    .generate();
+
+    // Regenerate with `REGENERATE_BINDINGS=1 cargo test --test codegen`.
+    if std::env::var_os("REGENERATE_BINDINGS").is_some() {
+        std::fs::write(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/bindings.rs"),
+            &code,
+        )?;
+    }
+    assert_eq!(code, include_str!("fixtures/bindings.rs"));
+
+    Ok(())
+}
+
+#[test]
+fn test_bindings_serde() -> Result<(), ReplicateError> {
+    let input = crate_::Input {
+        prompt: String::from("a 19th century portrait of a wombat gentleman"),
+        crate_: Some(String::from("a")),
+        self_: Some(1),
+        num_outputs_: Some(2),
+        value: Some(0.5),
+        super_: Some(crate_::Super::High),
+        ..Default::default()
+    };
+    assert_eq!(
+        serde_json::to_value(&input)?,
+        serde_json::json!({
+            "prompt": "a 19th century portrait of a wombat gentleman",
+            "crate": "a",
+            "self": 1,
+            "num_outputs": 2,
+            "": 0.5,
+            "super": "high",
+        })
+    );
+
+    let output: crate_::Output =
+        serde_json::from_value(serde_json::json!({"self": "b", "crate": [1, 2]}))?;
+    assert_eq!(output.self_, "b");
+    assert_eq!(output.crate__, Some(vec![1, 2]));
+    assert_eq!(output.crate_, None);
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+// Generated by replicate_rust::codegen from the schema of acme/crate:5c7d5dc6dd8bf75c1acaa8565735e7986bc5b66206b55cca93cb72c9bf15ccaa. Do not edit.
+
+/// Typed bindings of the model version `acme/crate:5c7d5dc6dd8bf75c1acaa8565735e7986bc5b66206b55cca93cb72c9bf15ccaa`.
+#[allow(dead_code, clippy::all)]
+pub mod crate_ {
+    /// Model version of the bindings.
+    pub const VERSION: &str = "acme/crate:5c7d5dc6dd8bf75c1acaa8565735e7986bc5b66206b55cca93cb72c9bf15ccaa";
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, ::serde::Serialize, ::serde::Deserialize)]
+    pub enum Scheduler {
+        #[serde(rename = "DDIM")]
+        Ddim,
+        #[serde(rename = "K_EULER")]
+        KEuler,
+    }
+
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, ::serde::Serialize, ::serde::Deserialize)]
+    pub enum Super {
+        #[default]
+        #[serde(rename = "low")]
+        Low,
+        #[serde(rename = "high")]
+        High,
+    }
+
+    /// Inputs of the model.
+    #[derive(Clone, Debug, Default, PartialEq, ::serde::Serialize)]
+    pub struct Input {
+        /// Input prompt
+        pub prompt: String,
+        #[serde(rename = "crate")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub crate_: Option<String>,
+        #[serde(rename = "self")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub self_: Option<i64>,
+        #[serde(rename = "Self")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub self__: Option<bool>,
+        #[serde(rename = "num-outputs")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub num_outputs: Option<i64>,
+        #[serde(rename = "num_outputs")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub num_outputs_: Option<i64>,
+        #[serde(rename = "")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub value: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub r#type: Option<Vec<String>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub scheduler: Option<Scheduler>,
+        #[serde(rename = "super")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub super_: Option<Super>,
+    }
+
+    /// Output of the model.
+    #[derive(Clone, Debug, PartialEq, ::serde::Deserialize)]
+    pub struct Output {
+        #[serde(rename = "Crate")]
+        #[serde(default)]
+        pub crate_: Option<bool>,
+        #[serde(rename = "crate")]
+        #[serde(default)]
+        pub crate__: Option<Vec<i64>>,
+        #[serde(rename = "self")]
+        pub self_: String,
+    }
+
+    /// Run the model with the given inputs, and wait for its output.
+    pub fn run(
+        replicate: &::replicate_rust::Replicate,
+        input: &Input,
+    ) -> Result<Output, ::replicate_rust::errors::ReplicateError> {
+        ::replicate_rust::codegen::run(replicate, VERSION, input)
+    }
+}